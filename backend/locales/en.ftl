@@ -0,0 +1,113 @@
+# Client-facing messages, one per error code of the OpenAPI `ErrorCode` schema.
+# Overrides go into `<APP__LOCALES_DIR>/en.ftl`.
+
+InvalidJson = { $reason ->
+    [content-type] expected `Content-Type: application/json`
+    [eof] request body ends before the JSON value does
+    [syntax] request body is not valid JSON (line { $line }, column { $column })
+   *[other] request body could not be read
+}
+InvalidForm = form post could not be read: { $detail }
+ValidationFailed = { $count ->
+    [one] { $count } field failed validation
+   *[other] { $count } fields failed validation
+}
+FieldRequired = { $field } is required
+FieldInvalidType = { $field } must be { $expected ->
+    [string] a string
+    [list] an array of strings
+    [number] a number
+    [boolean] true or false
+    [date] a date as YYYY-MM-DD
+    [field-type] one of text, number, boolean, email, phone, date, choice
+   *[object] an object
+}
+SubjectTooLong = subject exceeds { $max } characters
+MessageTooLong = message exceeds { $max } characters
+FieldTooLong = { $field } exceeds { $max } characters
+BodyTooLong = message, contact and fields together exceed { $max } characters
+InvalidEmail = { $field } is not a valid address
+
+InvalidIdempotencyKey = Idempotency-Key must be 1-255 visible ASCII characters
+RequestInProgress = An identical request is still being processed
+IdempotencyKeyReused = Idempotency-Key was already used with a different payload
+
+InvalidApiKey = API key is not valid
+SignatureMalformed = X-Api-Key-Id, X-Signature-Timestamp and X-Signature must name a signing key and carry a hex signature
+SignatureInvalid = request signature does not match
+SignatureExpired = X-Signature-Timestamp is outside the accepted clock skew
+SignatureReplayed = this signed request was already received
+
+FormTokenMissing = form_token is required
+FormTokenInvalid = form_token is invalid
+FormTokenExpired = form_token has expired, reload the form
+FormTokenReused = form_token has already been used
+FormTokenTooFast = form was submitted too quickly
+
+PowMissing = pow solution is required
+PowInvalid = pow challenge is invalid
+PowExpired = pow challenge has expired, request a new one
+PowReused = pow challenge has already been used
+PowInsufficient = pow solution does not meet the difficulty
+
+CaptchaMissing = captcha_token is required
+CaptchaFailed = { $codes ->
+    [none] captcha verification failed
+   *[other] captcha verification failed: { $codes }
+}
+CaptchaScoreTooLow = captcha score is below the accepted threshold
+CaptchaUnavailable = captcha verification is temporarily unavailable
+
+IpQuotaExceeded = Quota 'ip' exceeded: at most { $limit } requests from your address per { $window }s. Please try again in { $retry }s.
+EmailQuotaExceeded = Quota 'email' exceeded: at most { $limit } requests from this email per { $window }s. Please try again in { $retry }s.
+RecipientQuotaExceeded = Quota 'recipient' exceeded: at most { $limit } messages to this recipient per { $window }s. Please try again in { $retry }s.
+DailyQuotaExceeded = Quota 'global_daily' exceeded: at most { $limit } messages the service may send per { $window }s. { $retry ->
+    [never] This request alone needs { $requested } messages and can never fit.
+   *[other] Please try again in { $retry }s.
+}
+ApiKeyQuotaExceeded = Quota 'api_key' exceeded: at most { $limit } requests with this API key per { $window }s. Please try again in { $retry }s.
+
+SpamRejected = submission was classified as spam
+RecipientNotAllowed = { $recipient } is not an allowed recipient for this API key
+NoRecipient = No recipient configured
+EmailSendFailed = { $detail }
+NotificationFailed = { $detail }
+
+InboundUnauthorized = the provider's API key or signature does not match
+InboundPayloadInvalid = payload not understood: { $detail }
+
+IpBlocked = Access from your network is blocked
+IpBanned = Too many violations, banned for another { $seconds }s
+NotFound = { $path ->
+    [none] no inbound adapter { $id }
+   *[other] No route for { $method } { $path }
+}
+Unauthorized = A valid admin bearer token or JWT is required
+FormTokenDisabled = form tokens are not enabled
+PowDisabled = proof of work is not enabled
+BanNotFound = { $ip } is not banned
+QuarantineItemNotFound = no quarantined submission { $id }
+
+# Sent to the submitter's `email` when APP__AUTO_REPLY is on. The address is
+# unverified, so nothing the sender wrote goes in but a short, link-free excerpt.
+AutoReplySubject = We received your message
+AutoReplyBody =
+    Hello,
+
+    thank you for getting in touch. We received your message and will answer as soon as we can.
+AutoReplyExcerpt = Your message began with:
+AutoReplyFooter = This is an automatic reply, there is no need to answer it.
+
+# Answer of POST /api/v2/request.
+SubmissionAccepted = Thank you! Your message has been received.
+
+# Labels of the contact details in the staff email, in APP__DEFAULT_LOCALE.
+ContactName = Name
+ContactPhone = Phone
+ContactCompany = Company
+
+# Lead context lines of the staff email.
+ContextPage = Page
+ContextReferer = Referrer
+ContextUtm = UTM
+ContextUserAgent = Browser
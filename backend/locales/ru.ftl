@@ -0,0 +1,114 @@
+# Сообщения для клиентов, по одному на каждый код ошибки из схемы OpenAPI `ErrorCode`.
+# Переопределения кладите в `<APP__LOCALES_DIR>/ru.ftl`.
+
+InvalidJson = { $reason ->
+    [content-type] ожидается `Content-Type: application/json`
+    [eof] тело запроса обрывается раньше конца JSON
+    [syntax] тело запроса не является корректным JSON (строка { $line }, столбец { $column })
+   *[other] не удалось прочитать тело запроса
+}
+InvalidForm = не удалось прочитать форму: { $detail }
+ValidationFailed = { $count ->
+    [one] { $count } поле не прошло проверку
+    [few] { $count } поля не прошли проверку
+   *[many] { $count } полей не прошли проверку
+}
+FieldRequired = поле { $field } обязательно
+FieldInvalidType = поле { $field } должно быть { $expected ->
+    [string] строкой
+    [list] массивом строк
+    [number] числом
+    [boolean] true или false
+    [date] датой в формате ГГГГ-ММ-ДД
+    [field-type] одним из text, number, boolean, email, phone, date, choice
+   *[object] объектом
+}
+SubjectTooLong = тема длиннее { $max } символов
+MessageTooLong = сообщение длиннее { $max } символов
+FieldTooLong = поле { $field } длиннее { $max } символов
+BodyTooLong = сообщение, контакты и поля вместе длиннее { $max } символов
+InvalidEmail = поле { $field }: некорректный адрес электронной почты
+
+InvalidIdempotencyKey = Idempotency-Key должен состоять из 1–255 видимых символов ASCII
+RequestInProgress = Такой же запрос ещё обрабатывается
+IdempotencyKeyReused = Idempotency-Key уже использован с другими данными
+
+InvalidApiKey = недействительный API-ключ
+SignatureMalformed = X-Api-Key-Id, X-Signature-Timestamp и X-Signature должны указывать ключ подписи и содержать подпись в hex
+SignatureInvalid = подпись запроса не совпадает
+SignatureExpired = X-Signature-Timestamp выходит за допустимое расхождение часов
+SignatureReplayed = этот подписанный запрос уже был получен
+
+FormTokenMissing = требуется form_token
+FormTokenInvalid = недействительный form_token
+FormTokenExpired = срок действия form_token истёк, обновите страницу с формой
+FormTokenReused = form_token уже использован
+FormTokenTooFast = форма отправлена слишком быстро
+
+PowMissing = требуется решение задачи proof of work
+PowInvalid = недействительная задача proof of work
+PowExpired = срок задачи proof of work истёк, запросите новую
+PowReused = задача proof of work уже использована
+PowInsufficient = решение proof of work не достигает нужной сложности
+
+CaptchaMissing = требуется captcha_token
+CaptchaFailed = { $codes ->
+    [none] проверка капчи не пройдена
+   *[other] проверка капчи не пройдена: { $codes }
+}
+CaptchaScoreTooLow = оценка капчи ниже допустимой
+CaptchaUnavailable = проверка капчи временно недоступна
+
+IpQuotaExceeded = Превышена квота 'ip': не более { $limit } запросов с вашего адреса за { $window } с. Повторите через { $retry } с.
+EmailQuotaExceeded = Превышена квота 'email': не более { $limit } запросов с этого адреса почты за { $window } с. Повторите через { $retry } с.
+RecipientQuotaExceeded = Превышена квота 'recipient': не более { $limit } писем этому получателю за { $window } с. Повторите через { $retry } с.
+DailyQuotaExceeded = Превышена квота 'global_daily': сервис может отправить не более { $limit } писем за { $window } с. { $retry ->
+    [never] Этому запросу нужно отправить писем: { $requested }, столько не пройдёт никогда.
+   *[other] Повторите через { $retry } с.
+}
+ApiKeyQuotaExceeded = Превышена квота 'api_key': не более { $limit } запросов с этим API-ключом за { $window } с. Повторите через { $retry } с.
+
+SpamRejected = сообщение распознано как спам
+RecipientNotAllowed = { $recipient } не входит в список разрешённых получателей для этого API-ключа
+NoRecipient = Получатель не настроен
+EmailSendFailed = не удалось отправить письмо. { $detail }
+NotificationFailed = не удалось отправить уведомление. { $detail }
+
+InboundUnauthorized = API-ключ или подпись провайдера не совпадает
+InboundPayloadInvalid = данные не распознаны: { $detail }
+
+IpBlocked = Доступ из вашей сети заблокирован
+IpBanned = Слишком много нарушений, доступ закрыт ещё на { $seconds } с
+NotFound = { $path ->
+    [none] нет входящего адаптера { $id }
+   *[other] Нет маршрута { $method } { $path }
+}
+Unauthorized = Требуется действующий токен администратора или JWT
+FormTokenDisabled = form-токены не включены
+PowDisabled = proof of work не включён
+BanNotFound = { $ip } не заблокирован
+QuarantineItemNotFound = нет сообщения { $id } в карантине
+
+# Отправляется на `email` отправителя, если включён APP__AUTO_REPLY. Адрес не
+# проверен, поэтому из текста отправителя попадает только короткий отрывок без ссылок.
+AutoReplySubject = Мы получили ваше сообщение
+AutoReplyBody =
+    Здравствуйте!
+
+    Спасибо, что написали нам. Мы получили ваше сообщение и ответим как можно скорее.
+AutoReplyExcerpt = Ваше сообщение начиналось так:
+AutoReplyFooter = Это автоматический ответ, отвечать на него не нужно.
+
+# Ответ POST /api/v2/request.
+SubmissionAccepted = Спасибо! Ваше сообщение получено.
+
+# Подписи контактных данных в письме сотрудникам, на языке APP__DEFAULT_LOCALE.
+ContactName = Имя
+ContactPhone = Телефон
+ContactCompany = Компания
+
+# Строки контекста заявки в письме сотрудникам.
+ContextPage = Страница
+ContextReferer = Источник перехода
+ContextUtm = UTM
+ContextUserAgent = Браузер
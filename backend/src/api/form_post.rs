@@ -0,0 +1,119 @@
+use axum::{
+    body::{self, Body, Bytes},
+    extract::{FromRequest, Multipart, Request},
+    http::{HeaderMap, header::CONTENT_TYPE},
+    response::{IntoResponse, Redirect, Response},
+};
+use serde_json::{Map, Value};
+
+use crate::api::validation;
+use crate::config::CONFIG;
+use crate::errors::app::AppError;
+use crate::types::RequestPayload;
+
+// NOTE: fields collected into an array when repeated, also accepted as `name[]`
+const LIST_FIELDS: [&str; 1] = ["recipients"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormKind {
+    UrlEncoded,
+    Multipart,
+}
+
+/// The form encoding of a classic `<form method="post">`, `None` for anything else.
+pub fn kind(headers: &HeaderMap) -> Option<FormKind> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    let mime = content_type.split(';').next()?.trim();
+    if mime.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+        Some(FormKind::UrlEncoded)
+    } else if mime.eq_ignore_ascii_case("multipart/form-data") {
+        Some(FormKind::Multipart)
+    } else {
+        None
+    }
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::InvalidForm(message.into())
+}
+
+/// Text fields in order; file parts are skipped.
+async fn fields(kind: FormKind, headers: &HeaderMap, body: Bytes) -> Result<Vec<(String, String)>, AppError> {
+    if kind == FormKind::UrlEncoded {
+        return Ok(form_urlencoded::parse(&body).into_owned().collect());
+    }
+    let mut request = Request::new(Body::from(body));
+    *request.headers_mut() = headers.clone();
+    let mut multipart = Multipart::from_request(request, &())
+        .await
+        .map_err(|e| invalid(e.body_text()))?;
+    let mut fields = Vec::new();
+    while let Some(field) = multipart.next_field().await.map_err(|e| invalid(e.body_text()))? {
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+        if field.file_name().is_some() {
+            continue;
+        }
+        let value = field.text().await.map_err(|e| invalid(e.body_text()))?;
+        fields.push((name, value));
+    }
+    Ok(fields)
+}
+
+/// Reads a form post into the JSON payload shape. Empty inputs count as not
+/// sent, so an untouched optional `email` field is not an invalid address.
+pub async fn parse(kind: FormKind, headers: &HeaderMap, body: Bytes) -> Result<RequestPayload, AppError> {
+    let mut object = Map::new();
+    for (name, value) in fields(kind, headers, body).await? {
+        if value.trim().is_empty() {
+            continue;
+        }
+        let name = name.strip_suffix("[]").unwrap_or(&name).to_string();
+        if LIST_FIELDS.contains(&name.as_str()) {
+            let list = object.entry(name).or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(list) = list {
+                list.push(Value::String(value));
+            }
+        } else {
+            object.insert(name, Value::String(value));
+        }
+    }
+    validation::payload(Value::Object(object))
+}
+
+/// `url` with `error=<code>` added to its query, ahead of any fragment.
+fn with_error(url: &str, code: &str) -> String {
+    let (base, fragment) = url.split_once('#').map_or((url, None), |(b, f)| (b, Some(f)));
+    let separator = if base.contains('?') { '&' } else { '?' };
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("error", code)
+        .finish();
+    match fragment {
+        Some(fragment) => format!("{base}{separator}{query}#{fragment}"),
+        None => format!("{base}{separator}{query}"),
+    }
+}
+
+/// Answers a browser form post with a 303 to `form_success_url` or, carrying
+/// the error code, `form_error_url`. Outcomes without a configured page keep
+/// the JSON answer.
+pub async fn redirect(response: Response) -> Response {
+    if response.status().is_success() {
+        return match &CONFIG.form_success_url {
+            Some(url) => Redirect::to(url).into_response(),
+            None => response,
+        };
+    }
+    let Some(url) = &CONFIG.form_error_url else {
+        return response;
+    };
+    let bytes = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap_or_default();
+    let code = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|v| v.get("error")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "RequestFailed".into());
+    Redirect::to(&with_error(url, &code)).into_response()
+}
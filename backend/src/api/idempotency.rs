@@ -0,0 +1,49 @@
+use axum::{
+    body::{self, Body},
+    http::{HeaderMap, HeaderName, HeaderValue, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+
+use crate::services::idempotency::{MAX_KEY_LEN, StoredResponse};
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+#[derive(Debug)]
+pub struct InvalidKey;
+
+/// The client's `Idempotency-Key`: 1 to 255 visible ASCII characters.
+pub fn read_key(headers: &HeaderMap) -> Result<Option<&str>, InvalidKey> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LEN && k.bytes().all(|b| b.is_ascii_graphic()))
+        .map(Some)
+        .ok_or(InvalidKey)
+}
+
+pub fn replay(stored: StoredResponse) -> Response {
+    let mut response = (stored.status, stored.body).into_response();
+    let headers = response.headers_mut();
+    if let Some(content_type) = stored.content_type {
+        headers.insert(CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// Buffers `response` so it can be both sent and kept for replay.
+pub async fn capture(response: Response) -> (Response, StoredResponse) {
+    let (parts, body) = response.into_parts();
+    let bytes = body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+    let stored = StoredResponse {
+        status: parts.status,
+        content_type: parts.headers.get(CONTENT_TYPE).cloned(),
+        body: bytes.clone(),
+    };
+    (Response::from_parts(parts, Body::from(bytes)), stored)
+}
@@ -7,7 +7,15 @@ use crate::config::ApiPaths;
 
 pub mod routes;
 pub mod openapi;
+pub mod idempotency;
+pub mod rate_limit;
+pub mod form_post;
+pub mod problem;
+pub mod request_id;
+pub mod validation;
 
 pub fn app() -> Router<Arc<AppState>> {
-    Router::new().nest(ApiPaths::V1_PREFIX, routes::router())
+    Router::new()
+        .nest(ApiPaths::V1_PREFIX, routes::router())
+        .nest(ApiPaths::V2_PREFIX, routes::v2_router())
 }
@@ -1,22 +1,144 @@
-use utoipa::OpenApi;
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        Content, Ref, RefOr,
+        security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityScheme},
+    },
+};
+
+use crate::api::problem::PROBLEM_JSON;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         crate::api::routes::health::handle_health,
         crate::api::routes::request::handle_request,
+        crate::api::routes::v2::handle_request,
+        crate::api::routes::inbound::handle_inbound,
+        crate::api::routes::form_token::handle_form_token,
+        crate::api::routes::pow::handle_pow_challenge,
+        crate::api::routes::admin::bans::handle_list_bans,
+        crate::api::routes::admin::bans::handle_lift_ban,
+        crate::api::routes::admin::quarantine::handle_list_quarantine,
+        crate::api::routes::admin::quarantine::handle_approve,
+        crate::api::routes::admin::quarantine::handle_reject,
+        crate::api::routes::admin::quarantine::handle_reject_and_ban,
+        crate::api::routes::admin::history::handle_list_submissions,
+        crate::api::routes::admin::history::handle_list_delivery_failures,
+        crate::api::routes::admin::rate_limits::handle_rate_limits,
+        crate::api::routes::admin::config::handle_config,
+        crate::api::routes::admin::history::handle_list_webhook_deliveries,
     ),
     components(
         schemas(
             crate::types::RequestPayload,
+            crate::types::Submission,
+            crate::types::Contact,
+            crate::types::Metadata,
+            crate::types::Utm,
+            crate::types::LeadContext,
+            crate::types::FormField,
+            crate::types::FieldKind,
+            crate::types::SubmissionResponse,
+            crate::types::SubmissionStatus,
             crate::types::ApiMessage,
             crate::types::ApiError,
-            crate::types::HealthResponse
+            crate::types::ErrorCode,
+            crate::types::FieldError,
+            crate::types::ProblemDetails,
+            crate::types::HealthResponse,
+            crate::types::FormTokenResponse,
+            crate::types::PowChallengeResponse,
+            crate::types::PowSolution,
+            crate::types::BanEntry,
+            crate::types::QuarantineEntry,
+            crate::types::SubmissionEntry,
+            crate::types::DeliveryFailureEntry,
+            crate::types::QuotaUsage,
+            crate::types::RateLimitState,
+            crate::types::ConfigView,
+            crate::types::WebhookAttemptEntry,
+            crate::types::WebhookDeliveryEntry
         )
     ),
     tags(
         (name = "health"),
-        (name = "requests")
-    )
+        (name = "requests"),
+        (name = "admin")
+    ),
+    modifiers(&SecuritySchemes, &ProblemResponses)
 )]
 pub struct ApiDoc;
+
+/// Registers the `admin_bearer` scheme (static token or JWT) used by the
+/// admin routes and the `api_key` / `request_signature` schemes for
+/// server-to-server callers.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_bearer",
+            SecurityScheme::Http(
+                Http::builder()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("A token from `APP__ADMIN_TOKENS` or an HS256/EdDSA JWT with `exp`"))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::Http(
+                Http::builder()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("API key from `[[api_keys]]`, stored there as `key_sha256`"))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "request_signature",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Signature",
+                "Hex HMAC-SHA256 of `<X-Signature-Timestamp>.<raw body>` with the key's `hmac_secret`; \
+                 also send `X-Api-Key-Id` and `X-Signature-Timestamp` (unix seconds). \
+                 Each signature is accepted once within `APP__SIGNATURE_TOLERANCE_SECONDS`.",
+            ))),
+        );
+    }
+}
+
+/// Lists `application/problem+json` next to every `ApiError` response, the
+/// form clients get by sending `Accept: application/problem+json`.
+struct ProblemResponses;
+
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let api_error = Ref::from_schema_name("ApiError").ref_location;
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.patch,
+            ];
+            for operation in operations.into_iter().flatten() {
+                for response in operation.responses.responses.values_mut() {
+                    let RefOr::T(response) = response else { continue };
+                    let is_api_error = response
+                        .content
+                        .get("application/json")
+                        .and_then(|content| content.schema.as_ref())
+                        .is_some_and(|schema| matches!(schema, RefOr::Ref(r) if r.ref_location == api_error));
+                    if is_api_error {
+                        response.content.insert(
+                            PROBLEM_JSON.to_string(),
+                            Content::new(Some(Ref::from_schema_name("ProblemDetails"))),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use axum::{
+    body::{self, Body},
+    http::{HeaderValue, header::{CONTENT_LENGTH, CONTENT_TYPE, VARY}},
+    response::Response,
+};
+use serde::Deserialize;
+
+use crate::types::{ErrorCode, FieldError, ProblemDetails};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Quality the `Accept` header gives `media_type`, counting exact matches only.
+fn quality(accept: &str, media_type: &str) -> Option<f32> {
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let mime = params.next()?.trim();
+            if !mime.eq_ignore_ascii_case(media_type) {
+                return None;
+            }
+            let q = params
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some(q)
+        })
+        .reduce(f32::max)
+}
+
+/// Whether the client ranks `application/problem+json` at least as high as
+/// plain `application/json`.
+pub fn wants_problem(accept: &str) -> bool {
+    match (quality(accept, PROBLEM_JSON), quality(accept, "application/json")) {
+        (Some(problem), Some(json)) => problem > 0.0 && problem >= json,
+        (Some(problem), None) => problem > 0.0,
+        _ => false,
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: ErrorCode,
+    message: String,
+    #[serde(default)]
+    errors: Vec<FieldError>,
+}
+
+/// Rewrites an `ApiError` answer as an RFC 7807 problem document for
+/// `instance`. Other answers, such as extractor rejections in plain text,
+/// pass through.
+pub async fn into_problem(response: Response, instance: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+    if !is_json {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let bytes = body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+    let Ok(error) = serde_json::from_slice::<ErrorBody>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let problem = ProblemDetails {
+        problem_type: format!("urn:problem:{}", error.error.as_str()),
+        title: error.error.title().into(),
+        status: parts.status.as_u16(),
+        detail: error.message,
+        instance: instance.into(),
+        code: error.error,
+        errors: error.errors,
+    };
+    parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    parts.headers.remove(CONTENT_LENGTH);
+    parts.headers.append(VARY, HeaderValue::from_static("accept"));
+    let body = serde_json::to_vec(&problem).unwrap_or_default();
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problem_json_must_rank_at_least_as_high_as_json() {
+        assert!(wants_problem("application/problem+json"));
+        assert!(wants_problem("application/problem+json, application/json"));
+        assert!(wants_problem("application/json;q=0.5, application/problem+json"));
+        assert!(!wants_problem("application/json, application/problem+json;q=0.9"));
+        assert!(!wants_problem("application/problem+json;q=0"));
+        assert!(!wants_problem("*/*"));
+        assert!(!wants_problem("text/html,application/xhtml+xml"));
+    }
+}
@@ -0,0 +1,17 @@
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::services::flood_control::QuotaStatus;
+
+// NOTE: header names follow draft-ietf-httpapi-ratelimit-headers
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+pub fn apply_headers(headers: &mut HeaderMap, status: &QuotaStatus) {
+    if headers.contains_key(RATELIMIT_LIMIT) {
+        return;
+    }
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(status.limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(status.remaining));
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(status.reset));
+}
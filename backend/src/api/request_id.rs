@@ -0,0 +1,61 @@
+use std::{fmt, future::Future};
+
+use axum::http::{HeaderName, HeaderValue};
+
+use crate::utils::crypto::random_hex;
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+// NOTE: ids from clients are kept when short and printable, so they cannot break log lines or mail headers
+const MAX_LEN: usize = 128;
+
+/// Correlates a request with its log spans, audit entries and emails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The client's `X-Request-Id` when usable, a fresh one otherwise.
+    pub fn accept_or_generate(header: Option<&HeaderValue>) -> Self {
+        let accepted = header
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| (1..=MAX_LEN).contains(&id.len()) && id.bytes().all(|b| b.is_ascii_graphic()));
+        Self(accepted.map_or_else(|| random_hex(16), str::to_string))
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// Id of the request being handled, `None` outside of one (e.g. background tasks).
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.0.clone()).ok()
+}
+
+/// Runs `f` as the request `id`.
+pub async fn scope<F: Future>(id: RequestId, f: F) -> F::Output {
+    CURRENT.scope(id, f).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_ids_are_kept_only_when_printable_and_short() {
+        let kept = RequestId::accept_or_generate(Some(&HeaderValue::from_static("req-42:abc")));
+        assert_eq!(kept.0, "req-42:abc");
+        for bad in ["", "has space", &"x".repeat(MAX_LEN + 1)] {
+            let id = RequestId::accept_or_generate(Some(&HeaderValue::from_str(bad).unwrap()));
+            assert_ne!(id.0, bad);
+            assert_eq!(id.0.len(), 32);
+        }
+        assert_eq!(RequestId::accept_or_generate(None).0.len(), 32);
+    }
+}
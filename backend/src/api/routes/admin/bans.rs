@@ -0,0 +1,67 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::info;
+
+use super::rfc3339;
+use crate::config::ApiPaths;
+use crate::errors::app::AppError;
+use crate::state::AppState;
+use crate::types::{ApiError, ApiMessage, BanEntry};
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_BANS,
+    responses(
+        (status = 200, description = "Active bans, soonest to expire first", body = [BanEntry]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_list_bans(State(state): State<Arc<AppState>>) -> Json<Vec<BanEntry>> {
+    let now = Utc::now().timestamp();
+    let bans = state.bans.lock().await.list(now);
+    Json(
+        bans.into_iter()
+            .map(|(ip, ban)| BanEntry {
+                ip,
+                reason: ban.reason,
+                strike: ban.strike,
+                banned_at: rfc3339(ban.banned_at),
+                until: rfc3339(ban.until),
+                remaining_seconds: ban.until - now,
+            })
+            .collect(),
+    )
+}
+
+#[utoipa::path(
+    delete,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_BAN,
+    params(("ip" = String, Path, description = "Banned IP address")),
+    responses(
+        (status = 200, description = "Ban lifted and strikes reset", body = ApiMessage),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 404, description = "IP is not banned", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_lift_ban(State(state): State<Arc<AppState>>, Path(ip): Path<String>) -> Response {
+    let mut bans = state.bans.lock().await;
+    if !bans.lift(&ip) {
+        return AppError::BanNotFound(ip).into_response();
+    }
+    bans.save();
+    info!(ip = %ip, "ban lifted");
+
+    Json(ApiMessage {
+        message: format!("ban on {ip} lifted"),
+    })
+    .into_response()
+}
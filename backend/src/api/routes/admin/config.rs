@@ -0,0 +1,20 @@
+use axum::Json;
+
+use crate::config::{ApiPaths, CONFIG, Redacted};
+use crate::types::{ApiError, ConfigView};
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_CONFIG,
+    responses(
+        (status = 200, description = "Effective configuration with secrets and addresses masked", body = ConfigView),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_config() -> Json<ConfigView> {
+    Json(ConfigView {
+        config: format!("{:#?}", Redacted(&CONFIG)),
+    })
+}
@@ -0,0 +1,121 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use std::sync::Arc;
+
+use super::rfc3339;
+use crate::config::{ApiPaths, CONFIG};
+use crate::state::AppState;
+use crate::types::{
+    ApiError, DeliveryFailureEntry, HistoryQuery, SubmissionEntry, WebhookAttemptEntry, WebhookDeliveryEntry,
+};
+
+const DEFAULT_LIMIT: usize = 50;
+
+fn limit(query: &HistoryQuery) -> usize {
+    query.limit.unwrap_or(DEFAULT_LIMIT).min(CONFIG.admin_history_size as usize)
+}
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_SUBMISSIONS,
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Recent submissions that passed validation, newest first", body = [SubmissionEntry]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_list_submissions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Vec<SubmissionEntry>> {
+    let submissions = state.submissions.lock().await;
+    Json(
+        submissions
+            .recent(limit(&query))
+            .map(|r| SubmissionEntry {
+                id: r.id.clone(),
+                received_at: rfc3339(r.received_at),
+                ip: r.ip.clone(),
+                api_key: r.api_key.clone(),
+                email: r.email.clone(),
+                subject: r.subject.clone(),
+                recipients: r.recipients.clone(),
+                outcome: r.outcome.as_str().to_string(),
+                crm_lead_id: r.crm_lead_id.clone(),
+                context: r.context.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_DELIVERY_FAILURES,
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Recent failed deliveries, one per recipient, newest first", body = [DeliveryFailureEntry]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_list_delivery_failures(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Vec<DeliveryFailureEntry>> {
+    let submissions = state.submissions.lock().await;
+    Json(
+        submissions
+            .failures(limit(&query))
+            .map(|f| DeliveryFailureEntry {
+                at: rfc3339(f.at),
+                submission_id: f.submission_id.clone(),
+                recipient: f.recipient.clone(),
+                error: f.error.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_WEBHOOK_DELIVERIES,
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Recent webhook deliveries with every attempt, newest first", body = [WebhookDeliveryEntry]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_list_webhook_deliveries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Vec<WebhookDeliveryEntry>> {
+    let webhooks = state.webhooks.lock().await;
+    Json(
+        webhooks
+            .recent(limit(&query))
+            .map(|d| WebhookDeliveryEntry {
+                id: d.id.clone(),
+                webhook: d.webhook.clone(),
+                event: d.event.as_str().to_string(),
+                submission_id: d.submission_id.clone(),
+                status: d.status.as_str().to_string(),
+                attempts: d
+                    .attempts
+                    .iter()
+                    .map(|a| WebhookAttemptEntry {
+                        at: rfc3339(a.at),
+                        status: a.status,
+                        error: a.error.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,39 @@
+use axum::{Router, middleware::from_fn, routing::{delete, get, post}};
+use chrono::DateTime;
+use std::sync::Arc;
+
+use crate::config::ApiPaths;
+use crate::middlewares;
+use crate::state::AppState;
+
+pub mod bans;
+pub mod config;
+pub mod history;
+pub mod quarantine;
+pub mod rate_limits;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(ApiPaths::ADMIN_BANS, get(bans::handle_list_bans))
+        .route(ApiPaths::ADMIN_BAN, delete(bans::handle_lift_ban))
+        .route(ApiPaths::ADMIN_QUARANTINE, get(quarantine::handle_list_quarantine))
+        .route(ApiPaths::ADMIN_QUARANTINE_APPROVE, post(quarantine::handle_approve))
+        .route(ApiPaths::ADMIN_QUARANTINE_REJECT, post(quarantine::handle_reject))
+        .route(
+            ApiPaths::ADMIN_QUARANTINE_REJECT_AND_BAN,
+            post(quarantine::handle_reject_and_ban),
+        )
+        .route(ApiPaths::ADMIN_SUBMISSIONS, get(history::handle_list_submissions))
+        .route(ApiPaths::ADMIN_DELIVERY_FAILURES, get(history::handle_list_delivery_failures))
+        .route(ApiPaths::ADMIN_RATE_LIMITS, get(rate_limits::handle_rate_limits))
+        .route(ApiPaths::ADMIN_CONFIG, get(config::handle_config))
+        .route(
+            ApiPaths::ADMIN_WEBHOOK_DELIVERIES,
+            get(history::handle_list_webhook_deliveries),
+        )
+        .route_layer(from_fn(middlewares::require_admin))
+}
+
+fn rfc3339(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0).unwrap_or_default().to_rfc3339()
+}
@@ -0,0 +1,227 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::info;
+
+use super::rfc3339;
+use crate::config::{ApiPaths, CONFIG};
+use crate::errors::app::AppError;
+use crate::services::{
+    audit::{self, AuditDecision},
+    auto_reply,
+    deliver::{deliver, failure_message},
+    channels::{self, Notification},
+    crm::{self, LeadSource},
+    submissions::SubmissionOutcome,
+    webhooks,
+};
+use crate::state::AppState;
+use crate::types::{ApiError, ApiMessage, QuarantineEntry};
+
+fn not_found(id: &str) -> Response {
+    AppError::QuarantineItemNotFound(id.to_string()).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_QUARANTINE,
+    responses(
+        (status = 200, description = "Submissions waiting for review, oldest first", body = [QuarantineEntry]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_list_quarantine(State(state): State<Arc<AppState>>) -> Json<Vec<QuarantineEntry>> {
+    let quarantine = state.quarantine.lock().await;
+    Json(
+        quarantine
+            .list()
+            .iter()
+            .map(|item| QuarantineEntry {
+                id: item.id.clone(),
+                received_at: rfc3339(item.received_at),
+                ip: item.ip.clone(),
+                email: item.email.clone(),
+                subject: item.subject.clone(),
+                message: item.message.clone(),
+                recipients: item.recipients.clone(),
+                reasons: item.reasons.iter().map(|r| r.as_str().to_string()).collect(),
+                spam_score: item.spam_score.clone(),
+                shadow_rule: item.shadow_rule.clone(),
+                context: item.context.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_QUARANTINE_APPROVE,
+    params(("id" = String, Path, description = "Quarantined submission id")),
+    responses(
+        (status = 200, description = "Sent to its recipients and removed from the queue", body = ApiMessage),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 404, description = "No such submission", body = ApiError),
+        (status = 429, description = "`DailyQuotaExceeded`: the global daily cap is reached, the submission stays queued", body = ApiError),
+        (status = 500, description = "Sending failed, the submission stays queued; a retry only mails the recipients not yet reached", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_approve(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    // NOTE: taken out while sending so a second approve cannot send it twice
+    let Some(mut item) = state.quarantine.lock().await.take(&id) else {
+        return not_found(&id);
+    };
+    let pending: Vec<String> = item
+        .recipients
+        .iter()
+        .filter(|r| !item.delivered_to.contains(r))
+        .cloned()
+        .collect();
+    let hit = state
+        .flood_control
+        .lock()
+        .await
+        .hit_global(pending.len(), Utc::now().timestamp());
+    if let Err(exceeded) = hit {
+        let mut quarantine = state.quarantine.lock().await;
+        quarantine.restore(item);
+        quarantine.save();
+        return AppError::Quota(exceeded).into_response();
+    }
+
+    let extra_headers = item.extra_headers();
+    let failures = deliver(&pending, &item.subject, &item.message, &extra_headers).await;
+    webhooks::emit_delivery(&state, &id, &pending, &failures).await;
+    {
+        let now = Utc::now().timestamp();
+        let mut submissions = state.submissions.lock().await;
+        submissions.record_failures(Some(&id), &failures, now);
+        if failures.is_empty() {
+            submissions.set_outcome(&id, SubmissionOutcome::Sent);
+        }
+    }
+    if !failures.is_empty() {
+        let message = failure_message(&failures, pending.len());
+        // NOTE: a retry must not mail, or announce via `email.sent`, the recipients reached now
+        item.delivered_to
+            .extend(pending.into_iter().filter(|r| !failures.iter().any(|(f, _)| f == r)));
+        let mut quarantine = state.quarantine.lock().await;
+        quarantine.restore(item);
+        quarantine.save();
+        return AppError::EmailSendFailed(message).into_response();
+    }
+
+    // NOTE: channels, auto-reply and CRM only once every recipient has the mail, so a retried approve fires them once
+    let notification = Notification {
+        submission_id: &id,
+        subject: &item.subject,
+        message: &item.message,
+        email: item.email.as_deref(),
+        recipients: &item.recipients,
+    };
+    let channel_failures = channels::notify(&state, &CONFIG.channels, &notification).await;
+    state
+        .submissions
+        .lock()
+        .await
+        .record_channel_failures(&id, &channel_failures, Utc::now().timestamp());
+    if let Some(email) = item.email.as_deref()
+        && !item.spam_tagged
+    {
+        auto_reply::send(&state, email, &item.submitted_message, item.locale).await;
+    }
+    crm::export(
+        &state,
+        &id,
+        &LeadSource {
+            notification: &notification,
+            ip: &item.ip,
+            extra: &item.extra,
+        },
+    );
+    let mut quarantine = state.quarantine.lock().await;
+    quarantine.mark_known(&item.ip, item.email.as_deref());
+    quarantine.save();
+    audit::record_moderation(&item, AuditDecision::Approved, "Approved");
+    info!(id = %id, ip = %item.ip, "quarantined submission approved");
+
+    Json(ApiMessage {
+        message: format!("submission {id} sent to {} recipient(s)", item.recipients.len()),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_QUARANTINE_REJECT,
+    params(("id" = String, Path, description = "Quarantined submission id")),
+    responses(
+        (status = 200, description = "Dropped without sending", body = ApiMessage),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 404, description = "No such submission", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_reject(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    let mut quarantine = state.quarantine.lock().await;
+    let Some(item) = quarantine.take(&id) else {
+        return not_found(&id);
+    };
+    quarantine.save();
+    audit::record_moderation(&item, AuditDecision::Rejected, "Rejected");
+    drop(quarantine);
+    state.submissions.lock().await.set_outcome(&id, SubmissionOutcome::Rejected);
+    info!(id = %id, ip = %item.ip, "quarantined submission rejected");
+
+    Json(ApiMessage {
+        message: format!("submission {id} rejected"),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_QUARANTINE_REJECT_AND_BAN,
+    params(("id" = String, Path, description = "Quarantined submission id")),
+    responses(
+        (status = 200, description = "Dropped with every other pending submission from the sender's IP, which is banned at its next escalation level", body = ApiMessage),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 404, description = "No such submission", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_reject_and_ban(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    let mut quarantine = state.quarantine.lock().await;
+    let Some(item) = quarantine.take(&id) else {
+        return not_found(&id);
+    };
+    let discarded = quarantine.discard_from(&item.ip);
+    quarantine.save();
+    audit::record_moderation(&item, AuditDecision::Rejected, "RejectedAndBanned");
+    drop(quarantine);
+    state.submissions.lock().await.set_outcome(&id, SubmissionOutcome::Rejected);
+
+    let now = Utc::now().timestamp();
+    let mut bans = state.bans.lock().await;
+    let ban = bans.ban(&item.ip, "moderator", now);
+    bans.save();
+    info!(id = %id, ip = %item.ip, discarded, banned = ban.is_some(), "quarantined submission rejected and sender banned");
+
+    let ban = match ban {
+        Some(ban) => format!("{} banned for {}s", item.ip, ban.until - now),
+        None => format!("{} not banned, no ban durations configured", item.ip),
+    };
+    Json(ApiMessage {
+        message: format!("submission {id} and {discarded} other(s) rejected; {ban}"),
+    })
+    .into_response()
+}
@@ -0,0 +1,42 @@
+use axum::{Json, extract::State};
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::config::{ApiPaths, CONFIG};
+use crate::services::flood_control::{KeyUsage, QuotaKind};
+use crate::state::AppState;
+use crate::types::{ApiError, QuotaUsage, RateLimitState};
+
+fn entry(usage: KeyUsage) -> QuotaUsage {
+    QuotaUsage {
+        key: usage.key,
+        hits: usage.hits,
+        limit: usage.limit,
+        window_seconds: usage.window,
+        reset_seconds: usage.reset,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::ADMIN_PREFIX + ApiPaths::ADMIN_RATE_LIMITS,
+    responses(
+        (status = 200, description = "Keys currently counted on each quota layer", body = RateLimitState),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    ),
+    security(("admin_bearer" = [])),
+    tag = "admin"
+)]
+pub async fn handle_rate_limits(State(state): State<Arc<AppState>>) -> Json<RateLimitState> {
+    let now = Utc::now().timestamp();
+    let flood_control = state.flood_control.lock().await;
+    let layer = |kind| flood_control.usage(kind, now).into_iter().map(entry).collect();
+    Json(RateLimitState {
+        enabled: CONFIG.use_rate_limit,
+        ip: layer(QuotaKind::Ip),
+        email: layer(QuotaKind::Email),
+        recipient: layer(QuotaKind::Recipient),
+        api_key: layer(QuotaKind::ApiKey),
+        global_daily: layer(QuotaKind::GlobalDaily),
+    })
+}
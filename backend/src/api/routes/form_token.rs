@@ -0,0 +1,32 @@
+use axum::{
+    Json,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+
+use crate::config::{ApiPaths, CONFIG};
+use crate::errors::app::AppError;
+use crate::services::form_token;
+use crate::types::{ApiError, FormTokenResponse};
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::FORM_TOKEN,
+    responses(
+        (status = 200, description = "Signed form token to send back as `form_token`", body = FormTokenResponse),
+        (status = 404, description = "Form tokens are disabled", body = ApiError)
+    ),
+    tag = "requests"
+)]
+pub async fn handle_form_token() -> Response {
+    let Some(token) = form_token::issue(Utc::now().timestamp()) else {
+        return AppError::FormTokenDisabled.into_response();
+    };
+
+    Json(FormTokenResponse {
+        token,
+        min_fill_seconds: CONFIG.form_token_min_fill_seconds,
+        expires_in: CONFIG.form_token_max_age_seconds,
+    })
+    .into_response()
+}
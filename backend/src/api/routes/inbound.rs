@@ -0,0 +1,82 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use tracing::{info, instrument};
+
+use super::request::{ApiVersion, process};
+use crate::{
+    api::{request_id::RequestId, validation},
+    config::{ApiPaths, CONFIG, InboundProvider},
+    errors::app::AppError,
+    middlewares::Allowlisted,
+    services::{inbound, lead_context},
+    state::AppState,
+    types::{ApiError, ApiMessage, Submission},
+};
+
+#[utoipa::path(
+    post,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::INBOUND,
+    params(
+        ("id" = String, Path, description = "Adapter id from `[[inbound]]`"),
+        ("Typeform-Signature" = Option<String>, Header, description = "Typeform: `sha256=<base64 HMAC-SHA256 of the body>`"),
+        ("X-Google-Forms-Signature" = Option<String>, Header, description = "Google Forms Apps Script: same scheme as Typeform")
+    ),
+    request_body(
+        description = "The provider's own format: Tilda form fields (form-encoded or JSON, API key in the configured field or header), a Typeform `form_response` webhook, or `{form_title, respondent_email, answers: [{title, answer}]}` from Google Forms",
+        content(
+            (String = "application/x-www-form-urlencoded"),
+            (String = "application/json")
+        )
+    ),
+    responses(
+        (status = 200, description = "Accepted, answered like `POST /request`", body = ApiMessage),
+        (status = 400, description = "`InboundPayloadInvalid`, or any validation error of `POST /request`", body = ApiError),
+        (status = 401, description = "`InboundUnauthorized`: API key or signature missing or wrong", body = ApiError),
+        (status = 404, description = "No adapter with this id", body = ApiError),
+        (status = 429, description = "The adapter's quota is exhausted", body = ApiError),
+        (status = 500, description = "Sending failed", body = ApiError)
+    ),
+    tag = "requests"
+)]
+#[instrument(skip(state, body, headers, request_id), fields(ip = %client_ip, request_id = %request_id))]
+pub async fn handle_inbound(
+    Extension(request_id): Extension<RequestId>,
+    Extension(client_ip): Extension<IpAddr>,
+    Extension(Allowlisted(allowlisted)): Extension<Allowlisted>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(settings) = CONFIG.inbound.iter().find(|a| a.id() == id) else {
+        return AppError::InboundAdapterNotFound(id).into_response();
+    };
+    if settings.provider == InboundProvider::Tilda && inbound::is_tilda_test(&headers, &body) {
+        return Json(ApiMessage { message: "ok".into() }).into_response();
+    }
+
+    let form = match inbound::parse(settings, &headers, &body) {
+        Ok(form) => form,
+        Err(e) => {
+            info!(adapter = %id, error = e.error_code().as_str(), "inbound post rejected");
+            return AppError::Inbound(e).into_response();
+        }
+    };
+    let payload = form.into_payload(settings);
+    let ip = client_ip.to_string();
+    let now = Utc::now().timestamp();
+    let payload = validation::check_content(Submission::from(payload)).map(|mut payload| {
+        // NOTE: the headers are the provider's, only the form's own page and tags count
+        lead_context::capture(&mut payload, None);
+        payload
+    });
+    process(&state, &ip, now, allowlisted, Some(&settings.caller), &headers, payload, ApiVersion::V1).await
+}
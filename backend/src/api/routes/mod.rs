@@ -1,26 +1,39 @@
-use axum::{routing::{get, post}, Router, extract::OriginalUri, http::Method, response::IntoResponse, Json, http::StatusCode};
+use axum::{routing::{get, post}, Router, extract::OriginalUri, http::Method};
 use std::sync::Arc;
 
 use crate::state::AppState;
 use crate::config::ApiPaths;
-use crate::types::ApiError;
+use crate::errors::app::AppError;
 
+pub mod admin;
 pub mod health;
+pub mod inbound;
+pub mod form_token;
+pub mod pow;
 pub mod request;
+pub mod v2;
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route(ApiPaths::HEALTH, get(health::handle_health))
         .route(ApiPaths::REQUEST, post(request::handle_request))
+        .route(ApiPaths::INBOUND, post(inbound::handle_inbound))
+        .route(ApiPaths::FORM_TOKEN, get(form_token::handle_form_token))
+        .route(ApiPaths::POW_CHALLENGE, get(pow::handle_pow_challenge))
+        .nest(ApiPaths::ADMIN_PREFIX, admin::router())
         .fallback(api_not_found)
 }
 
-async fn api_not_found(OriginalUri(uri): OriginalUri, method: Method) -> impl IntoResponse {
-    (
-        StatusCode::NOT_FOUND,
-        Json(ApiError {
-            error: "NotFound".into(),
-            message: format!("No route for {} {}", method, uri.path()),
-        }),
-    )
+/// `/api/v2`: only the endpoints whose contract changed, the rest stay on v1.
+pub fn v2_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(ApiPaths::REQUEST, post(v2::handle_request))
+        .fallback(api_not_found)
+}
+
+async fn api_not_found(OriginalUri(uri): OriginalUri, method: Method) -> AppError {
+    AppError::RouteNotFound {
+        method,
+        path: uri.path().to_string(),
+    }
 }
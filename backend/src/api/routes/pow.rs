@@ -0,0 +1,47 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+
+use crate::config::{ApiPaths, CONFIG};
+use crate::errors::app::AppError;
+use crate::services::pow;
+use crate::state::AppState;
+use crate::types::{ApiError, PowChallengeResponse};
+
+#[utoipa::path(
+    get,
+    path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::POW_CHALLENGE,
+    description = "Issues a signed proof-of-work puzzle. Find any `solution` such that \
+        SHA-256 of `challenge` immediately followed by `solution` starts with `difficulty` zero bits, \
+        then send both as `pow` with the request. Each challenge is accepted once.",
+    responses(
+        (status = 200, description = "Challenge to solve", body = PowChallengeResponse),
+        (status = 404, description = "Proof of work is disabled", body = ApiError)
+    ),
+    tag = "requests"
+)]
+pub async fn handle_pow_challenge(
+    Extension(client_ip): Extension<IpAddr>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let now = Utc::now().timestamp();
+    let ip_hits = state.flood_control.lock().await.ip_hits(&client_ip.to_string(), now);
+    let difficulty = pow::difficulty_for(ip_hits);
+
+    let Some(challenge) = pow::issue(difficulty, now) else {
+        return AppError::PowDisabled.into_response();
+    };
+
+    Json(PowChallengeResponse {
+        challenge,
+        difficulty,
+        algorithm: "sha256".into(),
+        expires_in: CONFIG.pow_max_age_seconds,
+    })
+    .into_response()
+}
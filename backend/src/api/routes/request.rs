@@ -2,104 +2,643 @@ use std::{net::IpAddr, sync::Arc};
 
 use axum::{
     Json,
-    extract::{State},
+    body::{Body, Bytes},
+    extract::{FromRequest, Request, State, rejection::JsonRejection},
     Extension,
-    http::{HeaderMap, StatusCode},
+    http::HeaderMap,
     response::IntoResponse,
 };
-use chrono::Utc;
-use tracing::{debug, info, instrument, warn};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use tracing::{info, instrument, warn};
 
 use crate::{
-    config::{ApiPaths, CONFIG, MailConfig},
-    services::send_email,
+    config::{ApiKeySettings, ApiPaths, CONFIG, ShadowMode, WebhookEvent},
+    api::{form_post::{self, FormKind}, idempotency, rate_limit, request_id::{self, RequestId}, validation},
+    errors::app::{AppError, JsonProblem},
+    middlewares::Allowlisted,
+    services::{
+        api_auth,
+        bans::{Violation, record_violation},
+        captcha::{self, CaptchaError},
+        form_token::{self, FormTokenError},
+        idempotency::{Begin, DedupKey},
+        audit::{self, AuditDecision, AuditEntry},
+        quarantine::{QuarantineReason, QuarantinedSubmission},
+        submissions::{SubmissionOutcome, SubmissionRecord},
+        webhooks,
+        channels::{self, ChannelFailure, Notification},
+        auto_reply,
+        i18n,
+        crm::{self, LeadSource},
+        lead_context,
+        pow,
+        spam::{self, SpamAction},
+        deliver::{REQUEST_ID_HEADER, deliver, failure_message},
+    },
     state::AppState,
-    types::{ApiError, ApiMessage, RequestPayload},
-    utils::mask_string::mask_email,
+    types::{ApiError, ApiMessage, ErrorCode, RequestPayload, Submission, SubmissionResponse, SubmissionStatus},
+    utils::crypto::random_hex,
 };
 
+const SPAM_SUBJECT_TAG: &str = "[SPAM?] ";
+
+/// Which API a submission came through; it decides the shape of the answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ApiVersion {
+    V1,
+    V2,
+}
+
+/// A submission taken over, whether delivered, quarantined or silently dropped.
+pub(super) struct Accepted {
+    id: String,
+    received_at: i64,
+}
+
+impl ApiVersion {
+    fn answer(self, accepted: Accepted) -> axum::response::Response {
+        match self {
+            Self::V1 => Json(ApiMessage { message: "ok".into() }).into_response(),
+            Self::V2 => Json(SubmissionResponse {
+                id: accepted.id,
+                status: SubmissionStatus::Accepted,
+                received_at: DateTime::from_timestamp(accepted.received_at, 0).unwrap_or_default().to_rfc3339(),
+                request_id: request_id::current(),
+                message: i18n::text(i18n::current(), "SubmissionAccepted", &[]).unwrap_or_default(),
+            })
+            .into_response(),
+        }
+    }
+}
+
 #[utoipa::path(
     post,
     path = String::from(ApiPaths::V1_PREFIX) + ApiPaths::REQUEST,
-    request_body = RequestPayload,
+    request_body(
+        description = "JSON, or a classic HTML form post where `recipients` may repeat and empty inputs are ignored",
+        content(
+            (RequestPayload = "application/json"),
+            (RequestPayload = "application/x-www-form-urlencoded"),
+            (RequestPayload = "multipart/form-data")
+        )
+    ),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key; repeats within the idempotency window get the first answer back")
+    ),
     responses(
-        (status = 200, description = "Accepted", body = ApiMessage),
-        (status = 400, description = "Bad request", body = ApiError),
-        (status = 429, description = "Too many requests", body = ApiError),
-        (status = 500, description = "Internal server error", body = ApiError)
+        (status = 200, description = "Accepted", body = ApiMessage, headers(
+            ("RateLimit-Limit" = u32, description = "Requests allowed per window for the client IP"),
+            ("RateLimit-Remaining" = u32, description = "Requests left in the current window"),
+            ("RateLimit-Reset" = u32, description = "Seconds until the window frees a slot"),
+            ("Idempotent-Replayed" = bool, description = "Present on answers replayed for a repeated request")
+        )),
+        (status = 303, description = "Form post redirected to the configured success URL, or to the error URL with `?error=<code>`", headers(
+            ("Location" = String, description = "Success or error page")
+        )),
+        (status = 400, description = "Bad request; `ValidationFailed` lists every invalid field in `errors` with a JSON pointer, code and message", body = ApiError, headers(
+            ("RateLimit-Limit" = u32, description = "Requests allowed per window for the client IP"),
+            ("RateLimit-Remaining" = u32, description = "Requests left in the current window"),
+            ("RateLimit-Reset" = u32, description = "Seconds until the window frees a slot")
+        )),
+        (status = 429, description = "Too many requests; `error` names the exceeded quota", body = ApiError, headers(
+            ("RateLimit-Limit" = u32, description = "Limit of the exceeded quota"),
+            ("RateLimit-Remaining" = u32, description = "Always 0"),
+            ("RateLimit-Reset" = u32, description = "Seconds until the quota frees a slot"),
+            ("Retry-After" = u32, description = "Seconds to wait before retrying")
+        )),
+        (status = 401, description = "Invalid API key or request signature; `error` says which check failed", body = ApiError),
+        (status = 403, description = "Client IP is denylisted or temporarily banned, or a recipient is not allowed for the API key", body = ApiError),
+        (status = 409, description = "The same submission is still being processed", body = ApiError),
+        (status = 422, description = "Idempotency-Key reused with a different payload", body = ApiError),
+        (status = 503, description = "CAPTCHA verifier unreachable", body = ApiError),
+        (status = 500, description = "Sending failed: `EmailSendFailed`, or `NotificationFailed` for a channel with `on_failure = \"fail\"`", body = ApiError, headers(
+            ("RateLimit-Limit" = u32, description = "Requests allowed per window for the client IP"),
+            ("RateLimit-Remaining" = u32, description = "Requests left in the current window"),
+            ("RateLimit-Reset" = u32, description = "Seconds until the window frees a slot")
+        ))
     ),
+    security((), ("api_key" = []), ("request_signature" = [])),
     tag = "requests"
 )]
 
 #[allow(unused_variables)]
-#[instrument(skip(state, payload, headers), fields(ip = %client_ip))]
+#[instrument(skip(state, body, headers, request_id), fields(ip = %client_ip, request_id = %request_id))]
 pub async fn handle_request(
+    Extension(request_id): Extension<RequestId>,
     Extension(client_ip): Extension<IpAddr>,
+    Extension(Allowlisted(allowlisted)): Extension<Allowlisted>,
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
-    payload: Result<Json<RequestPayload>, axum::extract::rejection::JsonRejection>,
+    body: Bytes,
 ) -> axum::response::Response {
     let ip = client_ip.to_string();
+    let form = form_post::kind(&headers);
+    let response = respond(&state, &ip, allowlisted, &headers, body, form, ApiVersion::V1).await;
+    match form {
+        Some(_) => form_post::redirect(response).await,
+        None => response,
+    }
+}
+
+/// Everything between reading the body and the JSON answer, which a form
+/// post turns into a redirect.
+pub(super) async fn respond(
+    state: &AppState,
+    ip: &str,
+    allowlisted: bool,
+    headers: &HeaderMap,
+    body: Bytes,
+    form: Option<FormKind>,
+    version: ApiVersion,
+) -> axum::response::Response {
     let now = Utc::now().timestamp();
 
-    if CONFIG.use_rate_limit {
-        let mut flood = state.flood_control.lock().await;
-        let entry = flood.entry(ip.clone()).or_default();
-        let before = entry.len();
+    // NOTE: signatures cover the raw body, so authenticate before parsing it
+    let caller = api_auth::authenticate(headers, &body, now, &mut *state.signatures.lock().await);
+    let caller = match caller {
+        Ok(caller) => caller,
+        Err(e) => {
+            info!(ip = %ip, error = e.error_code().as_str(), "API authentication failed");
+            return AppError::ApiAuth(e).into_response();
+        }
+    };
+    let payload = match (version, form) {
+        (ApiVersion::V1, Some(kind)) => form_post::parse(kind, headers, body).await.map(Submission::from),
+        (ApiVersion::V1, None) => parse_json(headers, body, validation::payload).await.map(Submission::from),
+        (ApiVersion::V2, _) => parse_json(headers, body, validation::submission).await,
+    };
+    let payload = payload.map(|mut payload| {
+        lead_context::capture(&mut payload, Some(headers));
+        payload
+    });
+    process(state, ip, now, allowlisted, caller, headers, payload, version).await
+}
+
+/// Idempotency, quotas, validation, spam and routing, then delivery: the
+/// pipeline every submission runs once its sender is known and its body read.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn process(
+    state: &AppState,
+    ip: &str,
+    now: i64,
+    allowlisted: bool,
+    caller: Option<&'static ApiKeySettings>,
+    headers: &HeaderMap,
+    payload: Result<Submission, AppError>,
+    version: ApiVersion,
+) -> axum::response::Response {
+    let idempotency_key = match idempotency::read_key(headers) {
+        Ok(key) => key,
+        Err(_) => return AppError::InvalidIdempotencyKey.into_response(),
+    };
+    let mut scope = caller.map_or_else(|| ip.to_string(), |key| format!("api_key={}", key.id));
+    // NOTE: a key reused across versions must not replay the other answer shape
+    if version == ApiVersion::V2 {
+        scope.insert_str(0, "v2:");
+    }
+    let dedup = payload
+        .as_ref()
+        .ok()
+        .and_then(|p| DedupKey::new(&scope, idempotency_key, p));
+    if let Some(key) = &dedup {
+        let begin = state.idempotency.lock().await.begin(key, now);
+        match begin {
+            Begin::New => {}
+            Begin::Replay(stored) => {
+                info!(ip = %ip, explicit_key = key.is_explicit(), "repeated submission, replaying answer");
+                return idempotency::replay(stored);
+            }
+            Begin::InProgress => return AppError::RequestInProgress.into_response(),
+            Begin::Mismatch => return AppError::IdempotencyKeyReused.into_response(),
+        }
+    }
 
-        entry.retain(|&t| now - t < 3600);
-        let after = entry.len();
-        if before != after {
-            debug!(removed = (before - after), "pruned");
+    let response = limited_submit(state, ip, now, allowlisted, caller, payload, version).await;
+    let Some(key) = dedup else {
+        return response;
+    };
+    let (response, stored) = idempotency::capture(response).await;
+    state.idempotency.lock().await.finish(&key, stored, now);
+    response
+}
+
+async fn limited_submit(
+    state: &AppState,
+    ip: &str,
+    now: i64,
+    allowlisted: bool,
+    caller: Option<&'static ApiKeySettings>,
+    payload: Result<Submission, AppError>,
+    version: ApiVersion,
+) -> axum::response::Response {
+    let rate_limited = CONFIG.use_rate_limit && !allowlisted && caller.is_none();
+    let quota = if let Some(key) = caller {
+        let hit = state.flood_control.lock().await.hit_api_key(key, now);
+        match hit {
+            Ok(status) => status,
+            Err(exceeded) => {
+                info!(api_key = %key.id, "API key quota exceeded");
+                return AppError::Quota(exceeded).into_response();
+            }
         }
-        if entry.len() >= CONFIG.rate_limit_max as usize {
-            info!(ip = %ip, "rate limit exceeded");
-            return (
-                StatusCode::TOO_MANY_REQUESTS,
-                Json(ApiError {
-                    error: "TooManyRequests".into(),
-                    message: "You have reached the maximum number of requests per hour. Please try again later.".into(),
-                })
-            ).into_response();
+    } else if rate_limited {
+        let hit = state.flood_control.lock().await.hit_ip(ip, now);
+        match hit {
+            Ok(status) => status,
+            Err(exceeded) => {
+                info!(ip = %ip, quota = exceeded.kind.as_str(), "rate limit exceeded");
+                record_violation(state, ip, Violation::RateLimited, now).await;
+                return AppError::Quota(exceeded).into_response();
+            }
         }
-        entry.push(now);
+    } else {
+        None
+    };
+
+    let mut response = submit(state, ip, now, rate_limited, caller, payload)
+        .await
+        .map(|accepted| version.answer(accepted))
+        .into_response();
+    if let Some(status) = &quota {
+        rate_limit::apply_headers(response.headers_mut(), status);
     }
+    response
+}
 
-    let Json(payload) = match payload {
-        Ok(p) => p,
-        Err(e) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ApiError {
-                    error: "InvalidJson".into(),
-                    message: e.to_string(),
-                }),
-            )
-                .into_response();
+async fn submit(
+    state: &AppState,
+    ip: &str,
+    now: i64,
+    rate_limited: bool,
+    caller: Option<&'static ApiKeySettings>,
+    payload: Result<Submission, AppError>,
+) -> Result<Accepted, AppError> {
+    let payload = payload?;
+
+    if caller.is_none()
+        && let Some(accepted) = check_bot_defenses(state, ip, now, &payload).await?
+    {
+        return Ok(accepted);
+    }
+
+    // NOTE: judged and echoed without the lead context, whose URLs are not the sender's
+    let text = payload.body(staff_label);
+    let message = payload.staff_body(staff_label);
+    let verdict = spam::score(&payload.subject, &text, payload.email(), &CONFIG.spam);
+    if verdict.action > SpamAction::Accept {
+        info!(ip = %ip, spam_score = %verdict, action = ?verdict.action, "spam rules matched");
+    }
+    match verdict.action {
+        SpamAction::Reject => {
+            let mode = CONFIG.shadow.spam;
+            if mode != ShadowMode::Off {
+                let score = Some(verdict.header_value());
+                let code = ErrorCode::SpamRejected.as_str();
+                return Ok(silently_drop(state, ip, caller, now, mode, "spam", code, &payload, score).await);
+            }
+            record_violation(state, ip, Violation::Spam, now).await;
+            return Err(AppError::SpamRejected);
         }
+        SpamAction::Quarantine | SpamAction::Tag | SpamAction::Accept => {}
+    }
+
+    let mut extra_headers: Vec<(&'static str, String)> = Vec::new();
+    if let Some(id) = request_id::current() {
+        extra_headers.push((REQUEST_ID_HEADER, id));
+    }
+    if CONFIG.spam.enabled {
+        extra_headers.push(("X-Spam-Score", verdict.header_value()));
+    }
+    let subject = if verdict.action == SpamAction::Tag {
+        extra_headers.push(("X-Spam-Flag", "YES".into()));
+        format!("{SPAM_SUBJECT_TAG}{}", payload.subject)
+    } else {
+        payload.subject.clone()
     };
 
-    if payload.subject.len() > MailConfig::MAX_SUBJECT {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ApiError {
-                error: "SubjectTooLong".into(),
-                message: format!("subject exceeds {} characters", MailConfig::MAX_SUBJECT),
-            }),
-        )
-            .into_response();
-    }
-    if payload.message.len() > MailConfig::MAX_MESSAGE {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ApiError {
-                error: "MessageTooLong".into(),
-                message: format!("message exceeds {} characters", MailConfig::MAX_MESSAGE),
-            }),
-        )
-            .into_response();
+    let recipients = match caller {
+        Some(key) => match api_key_recipients(key, &payload) {
+            Ok(recipients) => recipients,
+            Err(denied) => {
+                info!(api_key = %key.id, "recipient not allowed for API key");
+                return Err(AppError::RecipientNotAllowed(denied));
+            }
+        },
+        None => resolve_recipients(&payload),
+    };
+    if recipients.is_empty() {
+        return Err(AppError::NoRecipient);
+    }
+
+    let hit = {
+        let mut flood_control = state.flood_control.lock().await;
+        if rate_limited {
+            flood_control.hit_submission(payload.email(), &recipients, now)
+        } else {
+            // NOTE: API keys, inbound adapters and allowlisted IPs skip the per-sender quotas, not the daily cap
+            flood_control.hit_global(recipients.len(), now)
+        }
+    };
+    if let Err(exceeded) = hit {
+        info!(ip = %ip, quota = exceeded.kind.as_str(), "rate limit exceeded");
+        if rate_limited {
+            record_violation(state, ip, Violation::RateLimited, now).await;
+        }
+        return Err(AppError::Quota(exceeded));
+    }
+
+    {
+        let mut quarantine = state.quarantine.lock().await;
+        let mut reasons = quarantine.reasons(ip, payload.email(), &verdict);
+        // NOTE: API callers are known senders, only their content is judged
+        if caller.is_some() {
+            reasons.retain(|r| matches!(r, QuarantineReason::SpamScore | QuarantineReason::BlockedDomain));
+        }
+        if !reasons.is_empty() {
+            let (id, evicted) = quarantine.push(QuarantinedSubmission {
+                id: String::new(),
+                received_at: now,
+                ip: ip.to_string(),
+                email: payload.email().map(str::to_string),
+                subject,
+                message: message.clone(),
+                submitted_message: text.clone(),
+                recipients: recipients.clone(),
+                delivered_to: Vec::new(),
+                reasons: reasons.clone(),
+                spam_score: CONFIG.spam.enabled.then(|| verdict.header_value()),
+                spam_tagged: verdict.action == SpamAction::Tag,
+                shadow_rule: None,
+                extra: payload.lead_fields(),
+                context: payload.context.clone(),
+                locale: i18n::current(),
+                request_id: request_id::current(),
+            });
+            quarantine.save();
+            let reasons = reasons.iter().map(|r| r.as_str()).collect::<Vec<_>>().join(",");
+            info!(ip = %ip, id = %id, reasons = %reasons, "submission quarantined");
+            audit::record(&AuditEntry {
+                ip,
+                decision: AuditDecision::Quarantined,
+                rule: &reasons,
+                code: "Quarantined",
+                email: payload.email(),
+                subject: &payload.subject,
+                quarantine_id: Some(&id),
+            });
+            drop(quarantine);
+            record_eviction(state, evicted).await;
+            let data = received_event(&id, now, caller, &payload, &message, &recipients, true);
+            webhooks::emit(state, WebhookEvent::SubmissionReceived, Some(&id), data).await;
+            state.submissions.lock().await.record(SubmissionRecord {
+                id: id.clone(),
+                received_at: now,
+                ip: ip.to_string(),
+                api_key: caller.map(|k| k.id.clone()),
+                email: payload.email().map(str::to_string),
+                subject: payload.subject.clone(),
+                recipients,
+                outcome: SubmissionOutcome::Quarantined,
+                crm_lead_id: None,
+                context: payload.context.clone(),
+            });
+            return Ok(Accepted { id, received_at: now });
+        }
+    }
+
+    let id = random_hex(8);
+    let data = received_event(&id, now, caller, &payload, &message, &recipients, false);
+    webhooks::emit(state, WebhookEvent::SubmissionReceived, Some(&id), data).await;
+    let notification = Notification {
+        submission_id: &id,
+        subject: &subject,
+        message: &message,
+        email: payload.email(),
+        recipients: &recipients,
+    };
+    let (failures, channel_failures) = tokio::join!(
+        deliver(&recipients, &subject, &message, &extra_headers),
+        channels::notify(state, &CONFIG.channels, &notification),
+    );
+    webhooks::emit_delivery(state, &id, &recipients, &failures).await;
+    {
+        let mut submissions = state.submissions.lock().await;
+        submissions.record_failures(Some(&id), &failures, now);
+        submissions.record_channel_failures(&id, &channel_failures, now);
+        submissions.record(SubmissionRecord {
+            id: id.clone(),
+            received_at: now,
+            ip: ip.to_string(),
+            api_key: caller.map(|k| k.id.clone()),
+            email: payload.email().map(str::to_string),
+            subject: payload.subject.clone(),
+            recipients: recipients.clone(),
+            outcome: if failures.is_empty() && !channel_failures.iter().any(|f| f.required) {
+                SubmissionOutcome::Sent
+            } else {
+                SubmissionOutcome::Failed
+            },
+            crm_lead_id: None,
+            context: payload.context.clone(),
+        });
+    }
+    if !failures.is_empty() {
+        return Err(AppError::EmailSendFailed(failure_message(&failures, recipients.len())));
+    }
+    if let Some(e) = channels_failed(&channel_failures) {
+        return Err(e);
+    }
+    // NOTE: only once the answer is 200, a retried submission must not make a second lead
+    crm::export(
+        state,
+        &id,
+        &LeadSource {
+            notification: &notification,
+            ip,
+            extra: &payload.lead_fields(),
+        },
+    );
+    // NOTE: tagged spam gets no auto-reply, it may carry a forged address
+    if let Some(email) = payload.email()
+        && verdict.action != SpamAction::Tag
+    {
+        auto_reply::send(state, email, &text, i18n::current()).await;
+    }
+
+    let mut quarantine = state.quarantine.lock().await;
+    if quarantine.mark_known(ip, payload.email()) {
+        quarantine.save();
+    }
+    drop(quarantine);
+
+    info!(ip = %ip, "accepted");
+    Ok(Accepted { id, received_at: now })
+}
+
+/// Honeypot, form token, proof of work and CAPTCHA: the checks aimed at
+/// browser submissions. `Ok(Some)` is a silently dropped submission to
+/// answer as accepted instead of continuing.
+async fn check_bot_defenses(
+    state: &AppState,
+    ip: &str,
+    now: i64,
+    payload: &Submission,
+) -> Result<Option<Accepted>, AppError> {
+    if let Some(field) = &CONFIG.honeypot_field
+        && payload.extra.get(field).is_some_and(|v| !is_blank(v))
+    {
+        info!(ip = %ip, "honeypot field filled, dropping submission");
+        return Ok(Some(silently_drop(state, ip, None, now, ShadowMode::Discard, "honeypot", "HoneypotFilled", payload, None).await));
+    }
+
+    let verdict = form_token::verify(
+        payload.form_token.as_deref(),
+        now,
+        &mut *state.form_tokens.lock().await,
+    );
+    match verdict {
+        Ok(()) => {}
+        Err(FormTokenError::TooFast) => {
+            info!(ip = %ip, "form filled too fast, dropping submission");
+            let code = FormTokenError::TooFast.error_code().as_str();
+            return Ok(Some(silently_drop(state, ip, None, now, ShadowMode::Discard, "form_token", code, payload, None).await));
+        }
+        Err(e) => {
+            let code = e.error_code().as_str();
+            info!(ip = %ip, error = code, "form token rejected");
+            let mode = CONFIG.shadow.form_token;
+            if mode != ShadowMode::Off {
+                return Ok(Some(silently_drop(state, ip, None, now, mode, "form_token", code, payload, None).await));
+            }
+            return Err(AppError::FormToken(e));
+        }
+    }
+
+    let verdict = pow::verify(
+        payload.pow.as_ref().map(|p| p.challenge.as_str()),
+        payload.pow.as_ref().map(|p| p.solution.as_str()),
+        now,
+        &mut *state.pow_challenges.lock().await,
+    );
+    if let Err(e) = verdict {
+        let code = e.error_code().as_str();
+        info!(ip = %ip, error = code, "proof of work rejected");
+        let mode = CONFIG.shadow.pow;
+        if mode != ShadowMode::Off {
+            return Ok(Some(silently_drop(state, ip, None, now, mode, "pow", code, payload, None).await));
+        }
+        return Err(AppError::Pow(e));
+    }
+
+    let widget_token = CONFIG
+        .captcha_provider
+        .and_then(|p| payload.extra.get(p.widget_field()))
+        .and_then(Value::as_str);
+    let captcha_token = payload.captcha_token.as_deref().or(widget_token);
+    if let Err(e) = captcha::verify(&state.http, captcha_token, ip).await {
+        let code = e.error_code().as_str();
+        info!(ip = %ip, error = code, "captcha rejected");
+        let mode = CONFIG.shadow.captcha;
+        if mode != ShadowMode::Off && !matches!(e, CaptchaError::Unavailable(_)) {
+            return Ok(Some(silently_drop(state, ip, None, now, mode, "captcha", code, payload, None).await));
+        }
+        return Err(AppError::Captcha(e));
+    }
+
+    Ok(None)
+}
+
+/// `NotificationFailed` when a channel with `on_failure = "fail"` could not
+/// be notified.
+fn channels_failed(failures: &[ChannelFailure]) -> Option<AppError> {
+    let required: Vec<String> = failures
+        .iter()
+        .filter(|f| f.required)
+        .map(|f| format!("{}: {}", f.channel, f.error))
+        .collect();
+    if required.is_empty() {
+        return None;
+    }
+    Some(AppError::NotificationFailed(format!(
+        "Failed to notify {} channel(s). {}",
+        required.len(),
+        required.join("; ")
+    )))
+}
+
+/// `submission.received` payload: what the submitter sent, without the
+/// anti-bot answers.
+fn received_event(
+    id: &str,
+    now: i64,
+    caller: Option<&ApiKeySettings>,
+    payload: &Submission,
+    message: &str,
+    recipients: &[String],
+    quarantined: bool,
+) -> Value {
+    json!({
+        "submission_id": id,
+        "received_at": DateTime::from_timestamp(now, 0).unwrap_or_default().to_rfc3339(),
+        "api_key": caller.map(|k| &k.id),
+        "email": payload.email(),
+        "subject": payload.subject,
+        "message": message,
+        "contact": payload.contact,
+        "fields": payload.fields,
+        "context": payload.context,
+        "recipients": recipients,
+        "quarantined": quarantined,
+    })
+}
+
+/// Reads a JSON body, describing syntax errors by position only so serde's
+/// wording does not reach clients, and hands it to `validate`.
+async fn parse_json<T>(
+    headers: &HeaderMap,
+    body: Bytes,
+    validate: fn(Value) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let mut request = Request::new(Body::from(body.clone()));
+    *request.headers_mut() = headers.clone();
+    match Json::<Value>::from_request(request, &()).await {
+        Ok(Json(value)) => validate(value),
+        Err(JsonRejection::MissingJsonContentType(_)) => Err(AppError::InvalidJson(JsonProblem::ContentType)),
+        Err(_) => Err(AppError::InvalidJson(match serde_json::from_slice::<Value>(&body) {
+            Err(e) if e.is_eof() => JsonProblem::Eof,
+            Err(e) => JsonProblem::Syntax {
+                line: e.line(),
+                column: e.column(),
+            },
+            Ok(_) => JsonProblem::Unreadable,
+        })),
+    }
+}
+
+/// Recipients an API caller asked for, each of which must be on its
+/// allowlist; without any the default emails are used. `Err` names the first
+/// address that is not allowed.
+fn api_key_recipients(key: &ApiKeySettings, payload: &Submission) -> Result<Vec<String>, String> {
+    let mut recipients: Vec<String> = payload
+        .recipients
+        .iter()
+        .flatten()
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty())
+        .collect();
+    if let Some(denied) = recipients.iter().find(|r| !key.allows_recipient(r)) {
+        return Err(denied.clone());
+    }
+    if CONFIG.duplicate_emails_to_deafult_recipients_everytime || recipients.is_empty() {
+        recipients.extend(CONFIG.emails.iter().flatten().cloned());
     }
+    recipients.sort();
+    recipients.dedup();
+    Ok(recipients)
+}
 
+fn resolve_recipients(payload: &Submission) -> Vec<String> {
     let mut recipients: Vec<String> = Vec::new();
 
     if CONFIG.allow_email_input {
@@ -112,88 +651,115 @@ pub async fn handle_request(
 
     let default_emails: &[String] = CONFIG.emails.as_deref().unwrap_or(&[]);
 
-    if CONFIG.duplicate_emails_to_deafult_recipients_everytime {
-        recipients.extend(default_emails.iter().cloned());
-    } else if recipients.is_empty() {
+    if CONFIG.duplicate_emails_to_deafult_recipients_everytime || recipients.is_empty() {
         recipients.extend(default_emails.iter().cloned());
     }
 
     recipients.retain(|s| !s.trim().is_empty());
     recipients.sort();
     recipients.dedup();
+    recipients
+}
 
-    if recipients.is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ApiError {
-                error: "NoRecipient".into(),
-                message: "No recipient configured".into(),
-            }),
-        )
-            .into_response();
-    }
-
-    let mut failures: Vec<(String, String)> = Vec::new();
-    let mut handles = Vec::with_capacity(recipients.len());
-
-    for r in recipients.iter().cloned() {
-        debug!(recipient = mask_email(&r), "sending email");
-        let subject = payload.subject.clone();
-        let message = payload.message.clone();
-
-        handles.push(tokio::task::spawn_blocking(move || {
-            (r.clone(), send_email(&r, &subject, &message))
-        }));
-    }
+/// Answers `200 ok` exactly like a delivered submission while discarding or
+/// quarantining it, and records the decision in the audit trail.
+/// A submission a full quarantine dropped unreviewed; its client was told
+/// it went through, so the drop goes into the audit trail.
+async fn record_eviction(state: &AppState, evicted: Option<QuarantinedSubmission>) {
+    let Some(item) = evicted else {
+        return;
+    };
+    warn!(id = %item.id, ip = %item.ip, "quarantine full, dropped oldest submission");
+    audit::record_moderation(&item, AuditDecision::Discarded, "QuarantineFull");
+    state.submissions.lock().await.set_outcome(&item.id, SubmissionOutcome::Evicted);
+}
 
-    for h in handles {
-        match h.await {
-            Ok((rcpt, res)) => match res {
-                Ok(_) => debug!(recipient = mask_email(&rcpt), "email sent"),
-                Err(e) => {
-                    warn!(recipient = mask_email(&rcpt), error=%e, "send failed");
-                    failures.push((rcpt, e.to_string()));
-                }
-            },
-            Err(join_err) => {
-                warn!(error=%join_err, "send task join failed");
-            }
+#[allow(clippy::too_many_arguments)]
+async fn silently_drop(
+    state: &AppState,
+    ip: &str,
+    caller: Option<&ApiKeySettings>,
+    now: i64,
+    mode: ShadowMode,
+    rule: &str,
+    code: &str,
+    payload: &Submission,
+    spam_score: Option<String>,
+) -> Accepted {
+    let recipients = resolve_recipients(payload);
+    let quarantine_id = match mode {
+        ShadowMode::Off | ShadowMode::Discard => None,
+        ShadowMode::Quarantine => {
+            let mut quarantine = state.quarantine.lock().await;
+            let (id, evicted) = quarantine.push(QuarantinedSubmission {
+                id: String::new(),
+                received_at: now,
+                ip: ip.to_string(),
+                email: payload.email().map(str::to_string),
+                subject: payload.subject.clone(),
+                message: payload.staff_body(staff_label),
+                submitted_message: payload.body(staff_label),
+                recipients: recipients.clone(),
+                delivered_to: Vec::new(),
+                reasons: vec![QuarantineReason::ShadowBanned],
+                spam_score,
+                spam_tagged: false,
+                shadow_rule: Some(rule.to_string()),
+                extra: payload.lead_fields(),
+                context: payload.context.clone(),
+                locale: i18n::current(),
+                request_id: request_id::current(),
+            });
+            quarantine.save();
+            drop(quarantine);
+            record_eviction(state, evicted).await;
+            Some(id)
         }
-    }
+    };
+    audit::record(&AuditEntry {
+        ip,
+        decision: if quarantine_id.is_some() {
+            AuditDecision::Quarantined
+        } else {
+            AuditDecision::Discarded
+        },
+        rule,
+        code,
+        email: payload.email(),
+        subject: &payload.subject,
+        quarantine_id: quarantine_id.as_deref(),
+    });
+    let outcome = if quarantine_id.is_some() {
+        SubmissionOutcome::Quarantined
+    } else {
+        SubmissionOutcome::Discarded
+    };
+    let id = quarantine_id.unwrap_or_else(|| random_hex(8));
+    state.submissions.lock().await.record(SubmissionRecord {
+        id: id.clone(),
+        received_at: now,
+        ip: ip.to_string(),
+        api_key: caller.map(|k| k.id.clone()),
+        email: payload.email().map(str::to_string),
+        subject: payload.subject.clone(),
+        recipients,
+        outcome,
+        crm_lead_id: None,
+        context: payload.context.clone(),
+    });
+    Accepted { id, received_at: now }
+}
 
-    if !failures.is_empty() {
-        let (failed_count, total) = (failures.len(), recipients.len());
-        let detail = failures
-            .iter()
-            .take(3)
-            .map(|(addr, err)| format!("{addr}: {err}"))
-            .collect::<Vec<_>>()
-            .join("; ");
-
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiError {
-                error: "EmailSendFailed".into(),
-                message: format!(
-                    "Failed to send {failed_count} of {total} email(s). {}{}",
-                    if failed_count > 3 {
-                        "Some errors omitted. "
-                    } else {
-                        ""
-                    },
-                    detail
-                ),
-            }),
-        )
-            .into_response();
-    }
+/// Contact, field and context labels of the staff email, in the default
+/// language.
+fn staff_label(id: &str) -> String {
+    i18n::text(CONFIG.default_locale, id, &[]).unwrap_or_else(|| id.to_string())
+}
 
-    info!(ip = %ip, "accepted");
-    (
-        StatusCode::OK,
-        Json(ApiMessage {
-            message: "ok".into(),
-        }),
-    )
-        .into_response()
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
 }
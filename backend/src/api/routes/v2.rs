@@ -0,0 +1,57 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{
+    Extension,
+    body::Bytes,
+    extract::State,
+    http::HeaderMap,
+    response::Response,
+};
+use tracing::instrument;
+
+use super::request::{ApiVersion, respond};
+use crate::{
+    api::request_id::RequestId,
+    config::ApiPaths,
+    middlewares::Allowlisted,
+    state::AppState,
+    types::{ApiError, Submission, SubmissionResponse},
+};
+
+#[utoipa::path(
+    post,
+    path = String::from(ApiPaths::V2_PREFIX) + ApiPaths::REQUEST,
+    request_body(
+        content = Submission,
+        description = "JSON only. The email lists the contact details and typed fields under the message; `metadata` reaches webhooks, the CRM lead and the quarantine",
+        content_type = "application/json"
+    ),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key; repeats within the idempotency window get the first answer back")
+    ),
+    responses(
+        (status = 200, description = "Accepted; the same pipeline, quotas and headers as `POST /api/v1/request`", body = SubmissionResponse),
+        (status = 400, description = "Bad request; `ValidationFailed` lists every invalid field, e.g. `/fields/0/value` of the wrong type", body = ApiError),
+        (status = 401, description = "Invalid API key or request signature", body = ApiError),
+        (status = 403, description = "Client IP is denylisted or temporarily banned, or a recipient is not allowed for the API key", body = ApiError),
+        (status = 409, description = "The same submission is still being processed", body = ApiError),
+        (status = 422, description = "Idempotency-Key reused with a different payload", body = ApiError),
+        (status = 429, description = "Too many requests; `error` names the exceeded quota", body = ApiError),
+        (status = 503, description = "CAPTCHA verifier unreachable", body = ApiError),
+        (status = 500, description = "Sending failed", body = ApiError)
+    ),
+    security((), ("api_key" = []), ("request_signature" = [])),
+    tag = "requests"
+)]
+#[instrument(skip(state, body, headers, request_id), fields(ip = %client_ip, request_id = %request_id))]
+pub async fn handle_request(
+    Extension(request_id): Extension<RequestId>,
+    Extension(client_ip): Extension<IpAddr>,
+    Extension(Allowlisted(allowlisted)): Extension<Allowlisted>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let ip = client_ip.to_string();
+    respond(&state, &ip, allowlisted, &headers, body, None, ApiVersion::V2).await
+}
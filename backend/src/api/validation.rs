@@ -0,0 +1,421 @@
+use chrono::NaiveDate;
+use fluent_bundle::FluentValue;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::config::MailConfig;
+use crate::errors::app::AppError;
+use crate::services::i18n;
+use crate::types::{ErrorCode, FieldError, FieldKind, RequestPayload, Submission, locale::Locale};
+use crate::utils::looks_like_email;
+
+#[derive(Clone, Copy)]
+enum Shape {
+    Text,
+    TextList,
+    Object(&'static [Field]),
+    /// Typed inputs of a v2 submission.
+    FormFields,
+}
+
+/// Name, shape and whether it is required.
+type Field = (&'static str, Shape, bool);
+
+const POW: &[Field] = &[("challenge", Shape::Text, true), ("solution", Shape::Text, true)];
+
+// NOTE: fields of `RequestPayload` checked by hand, everything else lands in `extra`
+const V1: &[Field] = &[
+    ("subject", Shape::Text, true),
+    ("message", Shape::Text, true),
+    ("email", Shape::Text, false),
+    ("recipients", Shape::TextList, false),
+    ("form_token", Shape::Text, false),
+    ("captcha_token", Shape::Text, false),
+    ("pow", Shape::Object(POW), false),
+    ("locale", Shape::Text, false),
+];
+
+const CONTACT: &[Field] = &[
+    ("name", Shape::Text, false),
+    ("email", Shape::Text, false),
+    ("phone", Shape::Text, false),
+    ("company", Shape::Text, false),
+];
+
+const UTM: &[Field] = &[
+    ("source", Shape::Text, false),
+    ("medium", Shape::Text, false),
+    ("campaign", Shape::Text, false),
+    ("term", Shape::Text, false),
+    ("content", Shape::Text, false),
+];
+
+const METADATA: &[Field] = &[
+    ("page_url", Shape::Text, false),
+    ("utm", Shape::Object(UTM), false),
+    ("locale", Shape::Text, false),
+];
+
+const V2: &[Field] = &[
+    ("contact", Shape::Object(CONTACT), false),
+    ("subject", Shape::Text, true),
+    ("message", Shape::Text, false),
+    ("fields", Shape::FormFields, false),
+    ("recipients", Shape::TextList, false),
+    ("metadata", Shape::Object(METADATA), false),
+    ("form_token", Shape::Text, false),
+    ("captcha_token", Shape::Text, false),
+    ("pow", Shape::Object(POW), false),
+];
+
+/// `/fields/0/value` -> `fields[0].value`, the field name in messages.
+fn field_name(pointer: &str) -> String {
+    let mut name = String::new();
+    for segment in pointer.split('/').skip(1) {
+        if segment.bytes().all(|b| b.is_ascii_digit()) {
+            name.push_str(&format!("[{segment}]"));
+        } else {
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(segment);
+        }
+    }
+    if name.is_empty() { "body".into() } else { name }
+}
+
+/// A field error whose message comes from the current request's catalog.
+fn field_error(pointer: &str, code: ErrorCode, args: &[(&str, FluentValue<'static>)]) -> FieldError {
+    let mut args = args.to_vec();
+    args.push(("field", field_name(pointer).into()));
+    FieldError {
+        pointer: pointer.to_string(),
+        code,
+        message: i18n::text(i18n::current(), code.as_str(), &args).unwrap_or_else(|| code.title().into()),
+    }
+}
+
+/// `expected` selects the wording: `string`, `list`, `object`, `number`,
+/// `boolean`, `date` or `field-type`.
+fn wrong_type(pointer: &str, expected: &'static str) -> FieldError {
+    field_error(pointer, ErrorCode::FieldInvalidType, &[("expected", expected.into())])
+}
+
+/// Content rule of a text field: lengths and the email address format.
+fn content(pointer: &str, value: &str) -> Option<FieldError> {
+    match pointer {
+        "/subject" if value.len() > MailConfig::MAX_SUBJECT => Some(field_error(
+            pointer,
+            ErrorCode::SubjectTooLong,
+            &[("max", MailConfig::MAX_SUBJECT.into())],
+        )),
+        "/message" if value.len() > MailConfig::MAX_MESSAGE => Some(field_error(
+            pointer,
+            ErrorCode::MessageTooLong,
+            &[("max", MailConfig::MAX_MESSAGE.into())],
+        )),
+        _ if in_body(pointer) && value.chars().count() > MailConfig::MAX_FIELD => Some(field_error(
+            pointer,
+            ErrorCode::FieldTooLong,
+            &[("max", MailConfig::MAX_FIELD.into())],
+        )),
+        "/email" | "/contact/email" if !looks_like_email(value.trim()) => {
+            Some(field_error(pointer, ErrorCode::InvalidEmail, &[]))
+        }
+        _ => None,
+    }
+}
+
+/// Text besides subject and message that ends up in the email.
+fn in_body(pointer: &str) -> bool {
+    pointer.starts_with("/contact/") || pointer.starts_with("/fields/")
+}
+
+/// The whole email text, labels in the request's language, against
+/// `MAX_MESSAGE`; reported at the root since no single field is at fault.
+fn rendered(submission: &Submission) -> Option<FieldError> {
+    let label = |id: &str| i18n::text(i18n::current(), id, &[]).unwrap_or_else(|| id.to_string());
+    let length = submission.body(label).chars().count();
+    (length > MailConfig::MAX_MESSAGE)
+        .then(|| field_error("", ErrorCode::BodyTooLong, &[("max", MailConfig::MAX_MESSAGE.into())]))
+}
+
+fn check_object(object: &Map<String, Value>, pointer: &str, fields: &[Field], errors: &mut Vec<FieldError>) {
+    for (name, shape, required) in fields {
+        check(object.get(*name), &format!("{pointer}/{name}"), *shape, *required, errors);
+    }
+}
+
+fn check(value: Option<&Value>, pointer: &str, shape: Shape, required: bool, errors: &mut Vec<FieldError>) {
+    let value = match value {
+        None | Some(Value::Null) if required => {
+            errors.push(field_error(pointer, ErrorCode::FieldRequired, &[]));
+            return;
+        }
+        None | Some(Value::Null) => return,
+        Some(value) => value,
+    };
+    match shape {
+        Shape::Text => match value.as_str() {
+            Some(text) => errors.extend(content(pointer, text)),
+            None => errors.push(wrong_type(pointer, "string")),
+        },
+        Shape::TextList => match value.as_array() {
+            Some(list) => errors.extend(
+                list.iter()
+                    .enumerate()
+                    .filter(|(_, item)| !item.is_string())
+                    .map(|(i, _)| wrong_type(&format!("{pointer}/{i}"), "string")),
+            ),
+            None => errors.push(wrong_type(pointer, "list")),
+        },
+        Shape::Object(fields) => match value.as_object() {
+            Some(object) => check_object(object, pointer, fields, errors),
+            None => errors.push(wrong_type(pointer, "object")),
+        },
+        Shape::FormFields => match value.as_array() {
+            Some(list) => {
+                for (i, field) in list.iter().enumerate() {
+                    check_form_field(field, &format!("{pointer}/{i}"), errors);
+                }
+            }
+            None => errors.push(wrong_type(pointer, "list")),
+        },
+    }
+}
+
+fn check_form_field(field: &Value, pointer: &str, errors: &mut Vec<FieldError>) {
+    let Some(object) = field.as_object() else {
+        return errors.push(wrong_type(pointer, "object"));
+    };
+    check_object(object, pointer, &[("name", Shape::Text, true), ("label", Shape::Text, false)], errors);
+
+    let type_pointer = format!("{pointer}/type");
+    let kind = match object.get("type") {
+        None | Some(Value::Null) => return errors.push(field_error(&type_pointer, ErrorCode::FieldRequired, &[])),
+        Some(kind) => kind.as_str().and_then(|k| FieldKind::ALL.into_iter().find(|f| f.as_str() == k)),
+    };
+    let Some(kind) = kind else {
+        return errors.push(wrong_type(&type_pointer, "field-type"));
+    };
+
+    let pointer = format!("{pointer}/value");
+    let Some(value) = object.get("value").filter(|v| !v.is_null()) else {
+        return errors.push(field_error(&pointer, ErrorCode::FieldRequired, &[]));
+    };
+    let error = match (kind, value) {
+        (_, Value::String(text)) if content(&pointer, text).is_some() => content(&pointer, text),
+        (FieldKind::Choice, Value::Array(items)) if items.iter().all(Value::is_string) => {
+            return errors.extend(items.iter().enumerate().filter_map(|(i, item)| {
+                content(&format!("{pointer}/{i}"), item.as_str().unwrap_or_default())
+            }));
+        }
+        (FieldKind::Text | FieldKind::Phone, Value::String(_)) => None,
+        (FieldKind::Email, Value::String(email)) if looks_like_email(email.trim()) => None,
+        (FieldKind::Email, Value::String(_)) => Some(field_error(&pointer, ErrorCode::InvalidEmail, &[])),
+        (FieldKind::Number, Value::Number(_)) | (FieldKind::Boolean, Value::Bool(_)) => None,
+        (FieldKind::Date, Value::String(date)) if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => None,
+        (FieldKind::Text | FieldKind::Phone | FieldKind::Email, _) => Some(wrong_type(&pointer, "string")),
+        (FieldKind::Number, _) => Some(wrong_type(&pointer, "number")),
+        (FieldKind::Boolean, _) => Some(wrong_type(&pointer, "boolean")),
+        (FieldKind::Date, _) => Some(wrong_type(&pointer, "date")),
+        (FieldKind::Choice, _) => Some(wrong_type(&pointer, "list")),
+    };
+    errors.extend(error);
+}
+
+fn failed(errors: Vec<FieldError>) -> Result<(), AppError> {
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(AppError::Validation(errors)),
+    }
+}
+
+/// Checks `value` against `fields`, switching the request's language to a
+/// supported `locale` at `locale_pointer` first so the messages use it.
+fn validate<T: DeserializeOwned>(value: Value, fields: &[Field], locale_pointer: &str) -> Result<T, AppError> {
+    let Value::Object(object) = &value else {
+        return Err(AppError::Validation(vec![wrong_type("", "object")]));
+    };
+    if let Some(locale) = value.pointer(locale_pointer).and_then(Value::as_str).and_then(Locale::parse) {
+        i18n::set_current(locale);
+    }
+    let mut errors = Vec::new();
+    check_object(object, "", fields, &mut errors);
+    failed(errors)?;
+    Ok(serde_json::from_value(value).expect("the field table matches the type, so checked values deserialize"))
+}
+
+/// Reads a v1 JSON or form body into the payload, reporting every missing,
+/// mistyped or invalid field at once instead of the first serde error.
+pub fn payload(value: Value) -> Result<RequestPayload, AppError> {
+    validate(value, V1, "/locale")
+}
+
+/// Reads a `POST /api/v2/request` body the same way.
+pub fn submission(value: Value) -> Result<Submission, AppError> {
+    let submission = validate(value, V2, "/metadata/locale")?;
+    failed(rendered(&submission).into_iter().collect())?;
+    Ok(submission)
+}
+
+/// Content rules for a submission built on the server, such as an inbound
+/// adapter's, whose shape is right by construction.
+pub fn check_content(submission: Submission) -> Result<Submission, AppError> {
+    let errors = [
+        content("/subject", &submission.subject),
+        content("/message", &submission.message),
+        submission.email().and_then(|email| content("/contact/email", email)),
+        rendered(&submission),
+    ];
+    failed(errors.into_iter().flatten().collect())?;
+    Ok(submission)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn errors(value: Value) -> Vec<(String, ErrorCode)> {
+        match payload(value) {
+            Err(AppError::Validation(errors)) => errors.into_iter().map(|e| (e.pointer, e.code)).collect(),
+            other => panic!("expected validation errors, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn every_bad_field_is_reported_with_its_pointer() {
+        let found = errors(json!({
+            "subject": "s".repeat(MailConfig::MAX_SUBJECT + 1),
+            "message": "m".repeat(MailConfig::MAX_MESSAGE + 1),
+            "email": "nope",
+            "recipients": ["a@example.com", 7],
+            "pow": {"challenge": 1},
+        }));
+        assert_eq!(
+            found,
+            [
+                ("/subject".to_string(), ErrorCode::SubjectTooLong),
+                ("/message".into(), ErrorCode::MessageTooLong),
+                ("/email".into(), ErrorCode::InvalidEmail),
+                ("/recipients/1".into(), ErrorCode::FieldInvalidType),
+                ("/pow/challenge".into(), ErrorCode::FieldInvalidType),
+                ("/pow/solution".into(), ErrorCode::FieldRequired),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_and_mistyped_fields() {
+        let found = errors(json!({"message": 5, "email": null}));
+        assert_eq!(
+            found,
+            [
+                ("/subject".to_string(), ErrorCode::FieldRequired),
+                ("/message".into(), ErrorCode::FieldInvalidType),
+            ]
+        );
+        assert_eq!(errors(json!([1])), [(String::new(), ErrorCode::FieldInvalidType)]);
+    }
+
+    #[test]
+    fn typed_fields_must_match_their_type() {
+        let found = match submission(json!({
+            "subject": "Hi",
+            "contact": {"email": "nope"},
+            "fields": [
+                {"name": "budget", "type": "number", "value": "lots"},
+                {"name": "start", "type": "date", "value": "2026-02-30"},
+                {"name": "size", "type": "huge", "value": 1},
+                {"name": "tags", "type": "choice", "value": ["a", "b"]},
+            ],
+            "metadata": {"utm": {"source": 1}},
+        })) {
+            Err(AppError::Validation(errors)) => errors,
+            other => panic!("expected validation errors, got {other:?}"),
+        };
+        let pointers: Vec<_> = found.iter().map(|e| (e.pointer.as_str(), e.code)).collect();
+        assert_eq!(
+            pointers,
+            [
+                ("/contact/email", ErrorCode::InvalidEmail),
+                ("/fields/0/value", ErrorCode::FieldInvalidType),
+                ("/fields/1/value", ErrorCode::FieldInvalidType),
+                ("/fields/2/type", ErrorCode::FieldInvalidType),
+                ("/metadata/utm/source", ErrorCode::FieldInvalidType),
+            ]
+        );
+        assert!(found[2].message.contains("fields[1].value"), "{}", found[2].message);
+    }
+
+    #[test]
+    fn text_that_reaches_the_email_is_capped() {
+        let long = "ж".repeat(MailConfig::MAX_FIELD + 1);
+        let found = match submission(json!({
+            "subject": "Hi",
+            "contact": {"name": long, "phone": "ж".repeat(MailConfig::MAX_FIELD)},
+            "fields": [
+                {"name": "note", "label": long, "type": "text", "value": long},
+                {"name": "tags", "type": "choice", "value": ["a", long]},
+                {"name": "mail", "type": "email", "value": long},
+            ],
+            "captcha_token": "t".repeat(2 * MailConfig::MAX_FIELD),
+        })) {
+            Err(AppError::Validation(errors)) => errors,
+            other => panic!("expected validation errors, got {other:?}"),
+        };
+        let pointers: Vec<_> = found.iter().map(|e| (e.pointer.as_str(), e.code)).collect();
+        assert_eq!(
+            pointers,
+            [
+                ("/contact/name", ErrorCode::FieldTooLong),
+                ("/fields/0/label", ErrorCode::FieldTooLong),
+                ("/fields/0/value", ErrorCode::FieldTooLong),
+                ("/fields/1/value/1", ErrorCode::FieldTooLong),
+                ("/fields/2/value", ErrorCode::FieldTooLong),
+            ]
+        );
+        assert!(found[3].message.contains("fields[1].value[1]"), "{}", found[3].message);
+    }
+
+    #[test]
+    fn the_whole_email_text_is_capped() {
+        let fields: Vec<Value> = (0..30)
+            .map(|i| json!({"name": format!("f{i}"), "type": "text", "value": "v".repeat(MailConfig::MAX_FIELD)}))
+            .collect();
+        let found = match submission(json!({"subject": "Hi", "fields": fields})) {
+            Err(AppError::Validation(errors)) => errors,
+            other => panic!("expected validation errors, got {other:?}"),
+        };
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].pointer.as_str(), found[0].code), ("", ErrorCode::BodyTooLong));
+
+        let fields = &fields[..10];
+        assert!(submission(json!({"subject": "Hi", "message": "m".repeat(4000), "fields": fields})).is_ok());
+    }
+
+    #[test]
+    fn null_optional_blocks_count_as_not_sent() {
+        for block in ["contact", "message", "fields", "recipients", "metadata", "form_token", "captcha_token", "pow"] {
+            let mut body = json!({"subject": "Hi"});
+            body[block] = Value::Null;
+            let submission = submission(body).unwrap_or_else(|e| panic!("null {block}: {e:?}"));
+            assert!(submission.message.is_empty() && submission.fields.is_empty());
+        }
+        let submission = submission(json!({
+            "subject": "Hi",
+            "contact": {"name": null, "email": null},
+            "metadata": {"utm": null, "page_url": null, "locale": null},
+        }))
+        .unwrap();
+        assert!(submission.contact.name.is_none() && submission.metadata.utm.is_empty());
+    }
+
+    #[test]
+    fn valid_payload_keeps_extra_fields() {
+        let payload = payload(json!({"subject": "Hi", "message": "Hello", "website": ""})).unwrap();
+        assert_eq!(payload.subject, "Hi");
+        assert!(payload.extra.contains_key("website"));
+    }
+}
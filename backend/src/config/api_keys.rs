@@ -0,0 +1,51 @@
+use serde::Deserialize;
+use std::fmt;
+
+use crate::utils::{
+    mask_string::{mask_email, mask_secret},
+    rate_defaults::rate_limit_timeframe_seconds_default,
+};
+
+/// A server-to-server caller, declared as `[[api_keys]]` in `Settings.toml`.
+#[derive(Clone, Deserialize)]
+pub struct ApiKeySettings {
+    pub id: String,
+    // NOTE: hex SHA-256 of the key sent as `Authorization: Bearer <key>`
+    #[serde(default)]
+    pub key_sha256: Option<String>,
+    // NOTE: enables signed requests with X-Api-Key-Id / X-Signature-Timestamp / X-Signature
+    #[serde(default)]
+    pub hmac_secret: Option<String>,
+    // NOTE: replaces the anonymous quotas for this caller; 0 disables
+    #[serde(default)]
+    pub rate_limit_max: u32,
+    #[serde(default = "rate_limit_timeframe_seconds_default")]
+    pub rate_limit_timeframe_seconds: u32,
+    // NOTE: addresses the caller may put in `recipients`; empty = default emails only
+    #[serde(default)]
+    pub allowed_recipients: Vec<String>,
+}
+
+impl ApiKeySettings {
+    pub fn allows_recipient(&self, recipient: &str) -> bool {
+        self.allowed_recipients
+            .iter()
+            .any(|r| r.trim().eq_ignore_ascii_case(recipient.trim()))
+    }
+}
+
+impl fmt::Debug for ApiKeySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeySettings")
+            .field("id", &self.id)
+            .field("key_sha256", &self.key_sha256.as_ref().map(|v| mask_secret(v, 4)))
+            .field("hmac_secret", &self.hmac_secret.as_ref().map(|v| mask_secret(v, 0)))
+            .field("rate_limit_max", &self.rate_limit_max)
+            .field("rate_limit_timeframe_seconds", &self.rate_limit_timeframe_seconds)
+            .field(
+                "allowed_recipients",
+                &self.allowed_recipients.iter().map(|e| mask_email(e)).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,83 @@
+use serde::Deserialize;
+use std::fmt;
+
+use crate::utils::{
+    channel_defaults::telegram_base_url_default,
+    mask_string::{mask_email, mask_secret, mask_url},
+};
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ChannelKind {
+    /// Extra mailboxes with their own body template.
+    Email { to: Vec<String> },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+        // NOTE: Bot API root, overridable for a local stand-in
+        #[serde(default = "telegram_base_url_default")]
+        base_url: String,
+    },
+    /// Slack incoming webhook; Mattermost accepts the same payload.
+    #[serde(alias = "mattermost")]
+    Slack { webhook_url: String },
+    /// Generic JSON POST.
+    Http { url: String },
+}
+
+impl ChannelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Email { .. } => "email",
+            Self::Telegram { .. } => "telegram",
+            Self::Slack { .. } => "slack",
+            Self::Http { .. } => "http",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnFailure {
+    /// Record the failure, the submission still succeeds.
+    #[default]
+    Log,
+    /// Answer the submission with 500 like a failed email.
+    Fail,
+}
+
+/// A notification target, declared as `[[channels]]` in `Settings.toml`.
+#[derive(Clone, Deserialize)]
+pub struct ChannelSettings {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: ChannelKind,
+    // NOTE: placeholders {submission_id} {subject} {message} {email} {recipients}
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub on_failure: OnFailure,
+}
+
+impl fmt::Debug for ChannelSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("ChannelSettings");
+        s.field("id", &self.id).field("kind", &self.kind.as_str());
+        match &self.kind {
+            ChannelKind::Email { to } => s.field("to", &to.iter().map(|e| mask_email(e)).collect::<Vec<_>>()),
+            ChannelKind::Telegram {
+                bot_token,
+                chat_id,
+                base_url,
+            } => s
+                .field("bot_token", &mask_secret(bot_token, 0))
+                .field("chat_id", chat_id)
+                .field("base_url", base_url),
+            ChannelKind::Slack { webhook_url } => s.field("webhook_url", &mask_url(webhook_url)),
+            ChannelKind::Http { url } => s.field("url", &mask_url(url)),
+        };
+        s.field("template", &self.template.is_some())
+            .field("on_failure", &self.on_failure)
+            .finish()
+    }
+}
@@ -1,13 +1,44 @@
+use ipnet::IpNet;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::{fmt, str::FromStr};
 
+use super::{
+    ApiKeySettings, ChannelKind, ChannelSettings, CrmSettings, InboundSettings, LeadContextSettings, ShadowSettings, SpamSettings,
+    WebhookSettings,
+};
 use crate::errors::config::ConfigError;
+use crate::types::captcha::CaptchaProvider;
+use crate::types::locale::Locale;
 use crate::types::logger::LogLevel;
+use crate::utils::client_ip::ProxyHeader;
+use crate::utils::crypto::parse_ed25519_key;
 use crate::utils::mask_string::{mask_email, mask_secret};
 use crate::utils::{
-    allow_email_input_default, csv_to_vec, default_log_level, looks_like_email,
-    rate_defaults::{rate_limit_max_default, rate_limit_timeframe_seconds_default, use_rate_limit_default},
+    allow_email_input_default, audit_log_file_default, csv_to_durations, csv_to_ip_nets, csv_to_vec, default_log_level, looks_like_email,
+    ban_defaults::{
+        ban_durations_default, ban_store_file_default, ban_strike_ttl_seconds_default,
+        ban_violation_window_seconds_default, ban_violations_default, use_bans_default,
+    },
+    admin_defaults::{admin_history_size_default, admin_jwt_leeway_seconds_default},
+    api_key_defaults::signature_tolerance_seconds_default,
+    webhook_defaults::{webhook_max_attempts_default, webhook_retry_base_seconds_default},
+    captcha_defaults::captcha_min_score_default,
+    idempotency_defaults::{duplicate_window_seconds_default, idempotency_window_seconds_default},
+    ip_lists_reload_seconds_default,
+    form_token_defaults::{form_token_max_age_seconds_default, form_token_min_fill_seconds_default},
+    quarantine_defaults::{
+        quarantine_digest_seconds_default, quarantine_max_items_default, quarantine_store_file_default,
+    },
+    pow_defaults::{
+        pow_adaptive_difficulty_default, pow_difficulty_default, pow_difficulty_step_default,
+        pow_max_age_seconds_default, pow_max_difficulty_default,
+    },
+    rate_defaults::{
+        rate_limit_email_max_default, rate_limit_global_daily_max_default, rate_limit_max_default,
+        rate_limit_recipient_max_default, rate_limit_timeframe_seconds_default,
+        use_rate_limit_default,
+    },
     duplicate_emails_to_deafult_recipients_everytime_default,
 };
 
@@ -37,12 +68,186 @@ pub struct Settings {
     pub rate_limit_timeframe_seconds: u32,
     #[serde(default = "rate_limit_max_default")]
     pub rate_limit_max: u32,
+    // NOTE: 0 disables the quota
+    #[serde(default = "rate_limit_email_max_default")]
+    pub rate_limit_email_max: u32,
+    #[serde(default = "rate_limit_recipient_max_default")]
+    pub rate_limit_recipient_max: u32,
+    #[serde(default = "rate_limit_global_daily_max_default")]
+    pub rate_limit_global_daily_max: u32,
 
     #[serde(deserialize_with = "csv_to_vec")]
     pub emails: Option<Vec<String>>,
 
     #[serde(deserialize_with = "csv_to_vec")]
     pub cors_origins: Option<Vec<String>>,
+
+    // NOTE: extra payload field bots tend to fill; must stay empty
+    #[serde(default)]
+    pub honeypot_field: Option<String>,
+    // NOTE: enables signed form tokens when set
+    #[serde(default)]
+    pub form_token_secret: Option<String>,
+    #[serde(default = "form_token_min_fill_seconds_default")]
+    pub form_token_min_fill_seconds: u32,
+    #[serde(default = "form_token_max_age_seconds_default")]
+    pub form_token_max_age_seconds: u32,
+
+    // NOTE: HTML form posts are redirected (303) here after a success / failure,
+    // the failure URL gets `?error=<code>`; unset keeps the JSON answer
+    #[serde(default)]
+    pub form_success_url: Option<String>,
+    #[serde(default)]
+    pub form_error_url: Option<String>,
+
+    // NOTE: en | ru; answers use it when neither Accept-Language nor the payload `locale` names a supported one
+    #[serde(default)]
+    pub default_locale: Locale,
+    // NOTE: `<locale>.ftl` files here override single messages of the built-in catalogs
+    #[serde(default)]
+    pub locales_dir: Option<String>,
+    // NOTE: confirms receipt to the submitter's `email` in their locale
+    #[serde(default)]
+    pub auto_reply: bool,
+
+    // NOTE: referer, user agent, page URL and UTM tags of each lead, each one switchable
+    #[serde(default)]
+    pub lead_context: LeadContextSettings,
+
+    // NOTE: turnstile | hcaptcha | recaptcha | smartcaptcha; unset disables CAPTCHA
+    #[serde(default)]
+    pub captcha_provider: Option<CaptchaProvider>,
+    #[serde(default)]
+    pub captcha_secret: Option<String>,
+    // NOTE: minimum human-likeness 0.0..=1.0 for providers that return a score
+    #[serde(default = "captcha_min_score_default")]
+    pub captcha_min_score: f64,
+    // NOTE: overrides the provider's siteverify URL
+    #[serde(default)]
+    pub captcha_verify_url: Option<String>,
+
+    // NOTE: enables the proof-of-work challenge when set
+    #[serde(default)]
+    pub pow_secret: Option<String>,
+    // NOTE: leading zero bits of SHA-256
+    #[serde(default = "pow_difficulty_default")]
+    pub pow_difficulty: u8,
+    // NOTE: add pow_difficulty_step bits per request already counted for the IP
+    #[serde(default = "pow_adaptive_difficulty_default")]
+    pub pow_adaptive_difficulty: bool,
+    #[serde(default = "pow_difficulty_step_default")]
+    pub pow_difficulty_step: u8,
+    #[serde(default = "pow_max_difficulty_default")]
+    pub pow_max_difficulty: u8,
+    #[serde(default = "pow_max_age_seconds_default")]
+    pub pow_max_age_seconds: u32,
+
+    #[serde(default)]
+    pub spam: SpamSettings,
+    // NOTE: per-rule fake success instead of an error
+    #[serde(default)]
+    pub shadow: ShadowSettings,
+    // NOTE: JSON lines of silent decisions (shadow bans, honeypot, too-fast forms)
+    #[serde(default = "audit_log_file_default")]
+    pub audit_log_file: String,
+
+    // NOTE: allowlisted IPs/CIDRs skip rate limiting and override the denylist
+    #[serde(default, deserialize_with = "csv_to_ip_nets")]
+    pub ip_allowlist: Vec<IpNet>,
+    #[serde(default, deserialize_with = "csv_to_ip_nets")]
+    pub ip_denylist: Vec<IpNet>,
+    // NOTE: extra `allow|deny <ip or cidr>` lines, reloaded on change or SIGHUP
+    #[serde(default)]
+    pub ip_lists_file: Option<String>,
+    #[serde(default = "ip_lists_reload_seconds_default")]
+    pub ip_lists_reload_seconds: u32,
+
+    // NOTE: escalating temporary bans for repeated 429s and spam rejections
+    #[serde(default = "use_bans_default")]
+    pub use_bans: bool,
+    #[serde(default = "ban_violations_default")]
+    pub ban_violations: u32,
+    #[serde(default = "ban_violation_window_seconds_default")]
+    pub ban_violation_window_seconds: u32,
+    // NOTE: e.g. `10m,1h,24h`; repeat offenders stay on the last entry
+    #[serde(default = "ban_durations_default", deserialize_with = "csv_to_durations")]
+    pub ban_durations: Vec<u64>,
+    #[serde(default = "ban_strike_ttl_seconds_default")]
+    pub ban_strike_ttl_seconds: u32,
+    #[serde(default = "ban_store_file_default")]
+    pub ban_store_file: String,
+
+    // NOTE: held-back submissions wait here for an admin decision
+    #[serde(default = "quarantine_store_file_default")]
+    pub quarantine_store_file: String,
+    #[serde(default = "quarantine_max_items_default")]
+    pub quarantine_max_items: u32,
+    // NOTE: extra triggers besides the spam quarantine threshold
+    #[serde(default)]
+    pub quarantine_blocked_domains: bool,
+    #[serde(default)]
+    pub quarantine_new_domains: bool,
+    #[serde(default)]
+    pub quarantine_first_time_ips: bool,
+    // NOTE: 0 disables the digest; recipients default to `emails`
+    #[serde(default = "quarantine_digest_seconds_default")]
+    pub quarantine_digest_seconds: u32,
+    #[serde(default, deserialize_with = "csv_to_vec")]
+    pub quarantine_digest_recipients: Option<Vec<String>>,
+
+    // NOTE: how long an Idempotency-Key answer is replayed
+    #[serde(default = "idempotency_window_seconds_default")]
+    pub idempotency_window_seconds: u32,
+    // NOTE: identical content from one IP inside this window is dropped; 0 disables
+    #[serde(default = "duplicate_window_seconds_default")]
+    pub duplicate_window_seconds: u32,
+
+    // NOTE: server-to-server callers; list them as [[api_keys]] in Settings.toml
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeySettings>,
+    // NOTE: max clock skew of signed requests, also how long signatures are remembered
+    #[serde(default = "signature_tolerance_seconds_default")]
+    pub signature_tolerance_seconds: u32,
+    // NOTE: site builders posting their forms to /api/v1/inbound/{id}; list them as [[inbound]] in Settings.toml
+    #[serde(default)]
+    pub inbound: Vec<InboundSettings>,
+
+    // NOTE: notification targets besides the recipients' mailboxes; list them as [[channels]] in Settings.toml
+    #[serde(default)]
+    pub channels: Vec<ChannelSettings>,
+
+    // NOTE: every delivered submission becomes a lead; configure it as [crm] in Settings.toml
+    #[serde(default)]
+    pub crm: Option<CrmSettings>,
+
+    // NOTE: outbound event subscriptions; list them as [[webhooks]] in Settings.toml
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSettings>,
+    // NOTE: tries per delivery; the wait doubles from webhook_retry_base_seconds
+    #[serde(default = "webhook_max_attempts_default")]
+    pub webhook_max_attempts: u32,
+    #[serde(default = "webhook_retry_base_seconds_default")]
+    pub webhook_retry_base_seconds: u32,
+
+    // NOTE: bearer tokens for /api/v1/admin; unset disables the admin API
+    #[serde(default, deserialize_with = "csv_to_vec")]
+    pub admin_tokens: Option<Vec<String>>,
+    // NOTE: admin JWTs signed with HS256 by this secret are accepted
+    pub admin_jwt_hs256_secret: Option<String>,
+    // NOTE: admin JWTs signed with EdDSA by this key (raw 32 bytes, base64) are accepted
+    pub admin_jwt_ed25519_public_key: Option<String>,
+    #[serde(default = "admin_jwt_leeway_seconds_default")]
+    pub admin_jwt_leeway_seconds: u32,
+    // NOTE: recent submissions and delivery failures kept for the admin API
+    #[serde(default = "admin_history_size_default")]
+    pub admin_history_size: u32,
+
+    // NOTE: proxies whose forwarding headers are believed; empty = use the peer address
+    #[serde(default, deserialize_with = "csv_to_ip_nets")]
+    pub trusted_proxies: Vec<IpNet>,
+    // NOTE: forwarded | x-forwarded-for | x-real-ip; the only header read from trusted proxies
+    #[serde(default)]
+    pub client_ip_header: ProxyHeader,
 }
 
 pub fn load() -> Result<Settings, ConfigError> {
@@ -80,6 +285,174 @@ impl Settings {
         if self.smtp_password.is_empty() {
             return Err(ConfigError::Invalid("smtp_password is empty".into()));
         }
+        if self.rate_limit_timeframe_seconds == 0 {
+            return Err(ConfigError::Invalid("rate_limit_timeframe_seconds must be > 0".into()));
+        }
+        if let Some(secret) = &self.form_token_secret
+            && secret.len() < 32
+        {
+            return Err(ConfigError::Invalid("form_token_secret must be at least 32 characters".into()));
+        }
+        if self.form_token_min_fill_seconds >= self.form_token_max_age_seconds {
+            return Err(ConfigError::Invalid(
+                "form_token_min_fill_seconds must be below form_token_max_age_seconds".into(),
+            ));
+        }
+        for url in [&self.form_success_url, &self.form_error_url].into_iter().flatten() {
+            let absolute = url.starts_with("https://") || url.starts_with("http://");
+            if !(absolute || url.starts_with('/')) || http::HeaderValue::from_str(url).is_err() {
+                return Err(ConfigError::Invalid(format!(
+                    "form redirect URLs must be absolute http(s) URLs or paths: {url}"
+                )));
+            }
+        }
+        if let Some(dir) = &self.locales_dir
+            && !std::path::Path::new(dir).is_dir()
+        {
+            return Err(ConfigError::Invalid(format!("locales_dir is not a directory: {dir}")));
+        }
+        if !(LeadContextSettings::MIN_LENGTH..=LeadContextSettings::MAX_LENGTH).contains(&self.lead_context.max_length) {
+            return Err(ConfigError::Invalid(format!(
+                "lead_context.max_length must be {}..={}",
+                LeadContextSettings::MIN_LENGTH,
+                LeadContextSettings::MAX_LENGTH
+            )));
+        }
+        if self.captcha_provider.is_some()
+            && self.captcha_secret.as_deref().is_none_or(|s| s.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid("captcha_secret is required when captcha_provider is set".into()));
+        }
+        if !(0.0..=1.0).contains(&self.captcha_min_score) {
+            return Err(ConfigError::Invalid("captcha_min_score must be within 0.0..=1.0".into()));
+        }
+        if let Some(secret) = &self.pow_secret
+            && secret.len() < 32
+        {
+            return Err(ConfigError::Invalid("pow_secret must be at least 32 characters".into()));
+        }
+        if !(1..=32).contains(&self.pow_difficulty) || !(1..=32).contains(&self.pow_max_difficulty) {
+            return Err(ConfigError::Invalid("pow difficulties must be within 1..=32".into()));
+        }
+        if self.pow_difficulty > self.pow_max_difficulty {
+            return Err(ConfigError::Invalid("pow_difficulty must not exceed pow_max_difficulty".into()));
+        }
+        if self.use_bans && (self.ban_violations == 0 || self.ban_durations.is_empty()) {
+            return Err(ConfigError::Invalid("ban_violations and ban_durations must be set when use_bans is on".into()));
+        }
+        for (i, key) in self.api_keys.iter().enumerate() {
+            if key.id.trim().is_empty() || self.api_keys[..i].iter().any(|k| k.id == key.id) {
+                return Err(ConfigError::Invalid(format!("api key ids must be unique and non-empty: {:?}", key.id)));
+            }
+            if key.key_sha256.is_none() && key.hmac_secret.is_none() {
+                return Err(ConfigError::Invalid(format!("api key {} needs key_sha256 or hmac_secret", key.id)));
+            }
+            if let Some(hash) = &key.key_sha256
+                && (hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()))
+            {
+                return Err(ConfigError::Invalid(format!("api key {}: key_sha256 must be 64 hex characters", key.id)));
+            }
+            if let Some(bad) = key.allowed_recipients.iter().find(|e| !looks_like_email(e)) {
+                return Err(ConfigError::Invalid(format!("api key {}: invalid allowed recipient: {bad}", key.id)));
+            }
+        }
+        for (i, adapter) in self.inbound.iter().enumerate() {
+            let id = adapter.id();
+            // NOTE: ids share the API key quota map
+            if id.trim().is_empty()
+                || self.inbound[..i].iter().any(|a| a.id() == id)
+                || self.api_keys.iter().any(|k| k.id == id)
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "inbound ids must be unique, non-empty and not an api key id: {id:?}"
+                )));
+            }
+            if adapter.caller.key_sha256.is_some() || adapter.caller.hmac_secret.is_some() {
+                return Err(ConfigError::Invalid(format!("inbound {id}: takes secret, not key_sha256 or hmac_secret")));
+            }
+            if adapter.secret.len() < 16 {
+                return Err(ConfigError::Invalid(format!("inbound {id}: secret must be at least 16 characters")));
+            }
+            if let Some(bad) = adapter.caller.allowed_recipients.iter().find(|e| !looks_like_email(e)) {
+                return Err(ConfigError::Invalid(format!("inbound {id}: invalid allowed recipient: {bad}")));
+            }
+        }
+        if let Some(secret) = &self.admin_jwt_hs256_secret
+            && secret.len() < 32
+        {
+            return Err(ConfigError::Invalid("admin_jwt_hs256_secret must be at least 32 characters".into()));
+        }
+        if let Some(key) = &self.admin_jwt_ed25519_public_key
+            && parse_ed25519_key(key).is_none()
+        {
+            return Err(ConfigError::Invalid(
+                "admin_jwt_ed25519_public_key must be a base64 raw 32-byte Ed25519 public key".into(),
+            ));
+        }
+        for (i, channel) in self.channels.iter().enumerate() {
+            if channel.id.trim().is_empty() || self.channels[..i].iter().any(|c| c.id == channel.id) {
+                return Err(ConfigError::Invalid(format!("channel ids must be unique and non-empty: {:?}", channel.id)));
+            }
+            let http_url = |url: &str| url.starts_with("https://") || url.starts_with("http://");
+            let valid = match &channel.kind {
+                ChannelKind::Email { to } => !to.is_empty() && to.iter().all(|e| looks_like_email(e)),
+                ChannelKind::Telegram {
+                    bot_token,
+                    chat_id,
+                    base_url,
+                } => !bot_token.trim().is_empty() && !chat_id.trim().is_empty() && http_url(base_url),
+                ChannelKind::Slack { webhook_url } => http_url(webhook_url),
+                ChannelKind::Http { url } => http_url(url),
+            };
+            if !valid {
+                return Err(ConfigError::Invalid(format!(
+                    "channel {}: incomplete {} settings",
+                    channel.id,
+                    channel.kind.as_str()
+                )));
+            }
+        }
+        if let Some(crm) = &self.crm {
+            if !(crm.url.starts_with("https://") || crm.url.starts_with("http://")) {
+                return Err(ConfigError::Invalid("crm.url must be http(s)".into()));
+            }
+            if crm.lead.is_empty() {
+                return Err(ConfigError::Invalid("crm.lead must map at least one field".into()));
+            }
+            if !crm.lead_id_pointer().is_empty() && !crm.lead_id_pointer().starts_with('/') {
+                return Err(ConfigError::Invalid("crm.lead_id_pointer must be a JSON pointer like /id".into()));
+            }
+            if crm.max_attempts == 0 {
+                return Err(ConfigError::Invalid("crm.max_attempts must be > 0".into()));
+            }
+        }
+        for (i, hook) in self.webhooks.iter().enumerate() {
+            if hook.id.trim().is_empty() || self.webhooks[..i].iter().any(|h| h.id == hook.id) {
+                return Err(ConfigError::Invalid(format!("webhook ids must be unique and non-empty: {:?}", hook.id)));
+            }
+            if !(hook.url.starts_with("https://") || hook.url.starts_with("http://")) {
+                return Err(ConfigError::Invalid(format!("webhook {}: url must be http(s)", hook.id)));
+            }
+            if hook.secret.len() < 16 {
+                return Err(ConfigError::Invalid(format!("webhook {}: secret must be at least 16 characters", hook.id)));
+            }
+        }
+        if self.webhook_max_attempts == 0 {
+            return Err(ConfigError::Invalid("webhook_max_attempts must be > 0".into()));
+        }
+        if self.quarantine_max_items == 0 {
+            return Err(ConfigError::Invalid("quarantine_max_items must be > 0".into()));
+        }
+        if let Some(list) = &self.quarantine_digest_recipients {
+            for e in list {
+                if !looks_like_email(e) {
+                    return Err(ConfigError::Invalid(format!("invalid quarantine digest recipient: {e}")));
+                }
+            }
+        }
+        if self.idempotency_window_seconds == 0 {
+            return Err(ConfigError::Invalid("idempotency_window_seconds must be > 0".into()));
+        }
         if let Some(list) = &self.emails {
             for e in list {
                 if !looks_like_email(e) {
@@ -123,7 +496,8 @@ impl Settings {
     }
 }
 
-struct Redacted<'a>(&'a Settings);
+/// Settings with secrets masked, for logs and the admin config endpoint.
+pub struct Redacted<'a>(pub &'a Settings);
 
 impl<'a> fmt::Debug for Redacted<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -139,6 +513,9 @@ impl<'a> fmt::Debug for Redacted<'a> {
             .field("use_rate_limit", &s.use_rate_limit)
             .field("rate_limit_timeframe", &s.rate_limit_timeframe_seconds)
             .field("rate_limit_max", &s.rate_limit_max)
+            .field("rate_limit_email_max", &s.rate_limit_email_max)
+            .field("rate_limit_recipient_max", &s.rate_limit_recipient_max)
+            .field("rate_limit_global_daily_max", &s.rate_limit_global_daily_max)
             .field(
                 "emails",
                 &s.emails
@@ -147,6 +524,82 @@ impl<'a> fmt::Debug for Redacted<'a> {
             )
             .field("duplicate_emails_to_deafult_recipients_everytime", &s.duplicate_emails_to_deafult_recipients_everytime)
             .field("cors_origins", &s.cors_origins)
+            .field("trusted_proxies", &s.trusted_proxies)
+            .field("client_ip_header", &s.client_ip_header)
+            .field("ip_allowlist", &s.ip_allowlist)
+            .field("ip_denylist", &s.ip_denylist)
+            .field("ip_lists_file", &s.ip_lists_file)
+            .field("ip_lists_reload_seconds", &s.ip_lists_reload_seconds)
+            .field("use_bans", &s.use_bans)
+            .field("ban_violations", &s.ban_violations)
+            .field("ban_violation_window_seconds", &s.ban_violation_window_seconds)
+            .field("ban_durations", &s.ban_durations)
+            .field("ban_strike_ttl_seconds", &s.ban_strike_ttl_seconds)
+            .field("ban_store_file", &s.ban_store_file)
+            .field("quarantine_store_file", &s.quarantine_store_file)
+            .field("quarantine_max_items", &s.quarantine_max_items)
+            .field("quarantine_blocked_domains", &s.quarantine_blocked_domains)
+            .field("quarantine_new_domains", &s.quarantine_new_domains)
+            .field("quarantine_first_time_ips", &s.quarantine_first_time_ips)
+            .field("quarantine_digest_seconds", &s.quarantine_digest_seconds)
+            .field(
+                "quarantine_digest_recipients",
+                &s.quarantine_digest_recipients
+                    .as_ref()
+                    .map(|v| v.iter().map(|e| mask_email(e)).collect::<Vec<_>>()),
+            )
+            .field("idempotency_window_seconds", &s.idempotency_window_seconds)
+            .field("duplicate_window_seconds", &s.duplicate_window_seconds)
+            .field("api_keys", &s.api_keys)
+            .field("signature_tolerance_seconds", &s.signature_tolerance_seconds)
+            .field("inbound", &s.inbound)
+            .field("channels", &s.channels)
+            .field("crm", &s.crm)
+            .field("webhooks", &s.webhooks)
+            .field("webhook_max_attempts", &s.webhook_max_attempts)
+            .field("webhook_retry_base_seconds", &s.webhook_retry_base_seconds)
+            .field(
+                "admin_tokens",
+                &s.admin_tokens
+                    .as_ref()
+                    .map(|v| v.iter().map(|t| mask_secret(t, 2)).collect::<Vec<_>>()),
+            )
+            .field(
+                "admin_jwt_hs256_secret",
+                &s.admin_jwt_hs256_secret.as_ref().map(|v| mask_secret(v, 0)),
+            )
+            .field("admin_jwt_ed25519_public_key", &s.admin_jwt_ed25519_public_key)
+            .field("admin_jwt_leeway_seconds", &s.admin_jwt_leeway_seconds)
+            .field("admin_history_size", &s.admin_history_size)
+            .field("honeypot_field", &s.honeypot_field)
+            .field(
+                "form_token_secret",
+                &s.form_token_secret.as_ref().map(|v| mask_secret(v, 0)),
+            )
+            .field("form_token_min_fill_seconds", &s.form_token_min_fill_seconds)
+            .field("form_token_max_age_seconds", &s.form_token_max_age_seconds)
+            .field("form_success_url", &s.form_success_url)
+            .field("form_error_url", &s.form_error_url)
+            .field("default_locale", &s.default_locale)
+            .field("locales_dir", &s.locales_dir)
+            .field("auto_reply", &s.auto_reply)
+            .field("lead_context", &s.lead_context)
+            .field("captcha_provider", &s.captcha_provider)
+            .field(
+                "captcha_secret",
+                &s.captcha_secret.as_ref().map(|v| mask_secret(v, 2)),
+            )
+            .field("captcha_min_score", &s.captcha_min_score)
+            .field("captcha_verify_url", &s.captcha_verify_url)
+            .field("pow_secret", &s.pow_secret.as_ref().map(|v| mask_secret(v, 0)))
+            .field("pow_difficulty", &s.pow_difficulty)
+            .field("pow_adaptive_difficulty", &s.pow_adaptive_difficulty)
+            .field("pow_difficulty_step", &s.pow_difficulty_step)
+            .field("pow_max_difficulty", &s.pow_max_difficulty)
+            .field("pow_max_age_seconds", &s.pow_max_age_seconds)
+            .field("spam", &s.spam)
+            .field("shadow", &s.shadow)
+            .field("audit_log_file", &s.audit_log_file)
             .finish()
     }
 }
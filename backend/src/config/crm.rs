@@ -0,0 +1,81 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fmt;
+
+use crate::utils::{
+    crm_defaults::{crm_max_attempts_default, crm_retry_base_seconds_default},
+    mask_string::{mask_secret, mask_url},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrmStyle {
+    /// `crm.lead.add` through an incoming webhook URL: `{"fields": <lead>}`.
+    Bitrix24,
+    /// `POST /api/v4/leads` with a long-lived token: `[<lead>]`.
+    #[serde(alias = "amo")]
+    AmoCrm,
+    /// The lead object posted as is.
+    Generic,
+}
+
+impl CrmStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bitrix24 => "bitrix24",
+            Self::AmoCrm => "amocrm",
+            Self::Generic => "generic",
+        }
+    }
+
+    /// Where the created lead's id is in the CRM's answer.
+    pub fn lead_id_pointer(self) -> &'static str {
+        match self {
+            Self::Bitrix24 => "/result",
+            Self::AmoCrm => "/_embedded/leads/0/id",
+            Self::Generic => "/id",
+        }
+    }
+}
+
+/// Lead export, declared as `[crm]` in `Settings.toml`.
+#[derive(Clone, Deserialize)]
+pub struct CrmSettings {
+    pub style: CrmStyle,
+    pub url: String,
+    // NOTE: sent as a bearer token; Bitrix24 webhooks carry theirs in the url instead
+    #[serde(default)]
+    pub token: Option<String>,
+    // NOTE: the lead object; string values take placeholders like {email} or {phone}
+    pub lead: Map<String, Value>,
+    // NOTE: JSON pointer to the id in the answer, defaults per style
+    #[serde(default)]
+    pub lead_id_pointer: Option<String>,
+    // NOTE: tries per lead; the wait doubles from retry_base_seconds
+    #[serde(default = "crm_max_attempts_default")]
+    pub max_attempts: u32,
+    #[serde(default = "crm_retry_base_seconds_default")]
+    pub retry_base_seconds: u32,
+}
+
+impl CrmSettings {
+    pub fn lead_id_pointer(&self) -> &str {
+        self.lead_id_pointer
+            .as_deref()
+            .unwrap_or_else(|| self.style.lead_id_pointer())
+    }
+}
+
+impl fmt::Debug for CrmSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrmSettings")
+            .field("style", &self.style)
+            .field("url", &mask_url(&self.url))
+            .field("token", &self.token.as_deref().map(|t| mask_secret(t, 0)))
+            .field("lead", &self.lead.keys().collect::<Vec<_>>())
+            .field("lead_id_pointer", &self.lead_id_pointer())
+            .field("max_attempts", &self.max_attempts)
+            .field("retry_base_seconds", &self.retry_base_seconds)
+            .finish()
+    }
+}
@@ -0,0 +1,62 @@
+use serde::Deserialize;
+use std::fmt;
+
+use super::ApiKeySettings;
+use crate::utils::{inbound_defaults::{inbound_secret_field_default, inbound_subject_default}, mask_string::mask_secret};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InboundProvider {
+    /// Tilda form webhook, form-encoded or JSON, with an API key field.
+    Tilda,
+    /// An Apps Script `onFormSubmit` trigger posting the responses as JSON.
+    GoogleForms,
+    /// Typeform `form_response` webhook.
+    Typeform,
+}
+
+impl InboundProvider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tilda => "tilda",
+            Self::GoogleForms => "google-forms",
+            Self::Typeform => "typeform",
+        }
+    }
+}
+
+/// A site builder posting its form submissions to `/api/v1/inbound/{id}`,
+/// declared as `[[inbound]]` in `Settings.toml`. Verified posts go through
+/// the pipeline as an API caller with this `id`, quota and recipients.
+#[derive(Clone, Deserialize)]
+pub struct InboundSettings {
+    pub provider: InboundProvider,
+    // NOTE: Tilda API key value, or the HMAC secret of Typeform and Google Forms signatures
+    pub secret: String,
+    // NOTE: Tilda only: header or form field carrying the API key
+    #[serde(default = "inbound_secret_field_default")]
+    pub secret_field: String,
+    // NOTE: placeholders {form} {provider}
+    #[serde(default = "inbound_subject_default")]
+    pub subject: String,
+    #[serde(flatten)]
+    pub caller: ApiKeySettings,
+}
+
+impl InboundSettings {
+    pub fn id(&self) -> &str {
+        &self.caller.id
+    }
+}
+
+impl fmt::Debug for InboundSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InboundSettings")
+            .field("provider", &self.provider)
+            .field("secret", &mask_secret(&self.secret, 0))
+            .field("secret_field", &self.secret_field)
+            .field("subject", &self.subject)
+            .field("caller", &self.caller)
+            .finish()
+    }
+}
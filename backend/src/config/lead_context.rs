@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+/// Which parts of a lead's origin are captured, read from `APP__LEAD_CONTEXT__*`.
+/// Turning one off drops it before it reaches emails, stores or webhooks.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct LeadContextSettings {
+    // NOTE: the `Referer` header, kept only when it is an http(s) URL
+    pub referer: bool,
+    pub user_agent: bool,
+    // NOTE: the page URL the client sends, `metadata.page_url` or a `page_url` form field
+    pub page_url: bool,
+    // NOTE: client-sent UTM tags, else the ones in the page URL or referer query
+    pub utm: bool,
+    // NOTE: longer values are cut to this many characters
+    pub max_length: usize,
+}
+
+impl LeadContextSettings {
+    pub const MIN_LENGTH: usize = 16;
+    pub const MAX_LENGTH: usize = 2048;
+}
+
+impl Default for LeadContextSettings {
+    fn default() -> Self {
+        Self {
+            referer: true,
+            user_agent: true,
+            page_url: true,
+            utm: true,
+            max_length: 512,
+        }
+    }
+}
@@ -3,4 +3,6 @@ pub struct MailConfig;
 impl MailConfig {
     pub const MAX_SUBJECT: usize = 200;
     pub const MAX_MESSAGE: usize = 10_000;
+    // NOTE: each contact entry, typed field name, label, value and choice
+    pub const MAX_FIELD: usize = 500;
 }
@@ -1,6 +1,23 @@
+#[allow(clippy::module_inception)]
 mod config;
-pub use config::CONFIG;
+pub use config::{CONFIG, Redacted};
 mod paths;
 pub use paths::ApiPaths;
 mod mail;
 pub use mail::MailConfig;
+mod spam;
+pub use spam::SpamSettings;
+mod shadow;
+pub use shadow::{ShadowMode, ShadowSettings};
+mod lead_context;
+pub use lead_context::LeadContextSettings;
+mod api_keys;
+pub use api_keys::ApiKeySettings;
+mod webhooks;
+pub use webhooks::{WebhookEvent, WebhookSettings};
+mod channels;
+pub use channels::{ChannelKind, ChannelSettings, OnFailure};
+mod crm;
+mod inbound;
+pub use inbound::{InboundProvider, InboundSettings};
+pub use crm::{CrmSettings, CrmStyle};
@@ -2,9 +2,26 @@ pub struct ApiPaths;
 
 impl ApiPaths {
     pub const V1_PREFIX: &'static str = "/api/v1";
+    pub const V2_PREFIX: &'static str = "/api/v2";
 
     pub const REQUEST: &'static str =  "/request";
     pub const HEALTH: &'static str = "/health";
+    pub const FORM_TOKEN: &'static str = "/form-token";
+    pub const POW_CHALLENGE: &'static str = "/pow-challenge";
+    pub const INBOUND: &'static str = "/inbound/{id}";
+
+    pub const ADMIN_PREFIX: &'static str = "/admin";
+    pub const ADMIN_BANS: &'static str = "/bans";
+    pub const ADMIN_BAN: &'static str = "/bans/{ip}";
+    pub const ADMIN_QUARANTINE: &'static str = "/quarantine";
+    pub const ADMIN_QUARANTINE_APPROVE: &'static str = "/quarantine/{id}/approve";
+    pub const ADMIN_QUARANTINE_REJECT: &'static str = "/quarantine/{id}/reject";
+    pub const ADMIN_QUARANTINE_REJECT_AND_BAN: &'static str = "/quarantine/{id}/reject-and-ban";
+    pub const ADMIN_SUBMISSIONS: &'static str = "/submissions";
+    pub const ADMIN_DELIVERY_FAILURES: &'static str = "/delivery-failures";
+    pub const ADMIN_RATE_LIMITS: &'static str = "/rate-limits";
+    pub const ADMIN_CONFIG: &'static str = "/config";
+    pub const ADMIN_WEBHOOK_DELIVERIES: &'static str = "/webhook-deliveries";
 
     pub const SWAGGER_UI: &'static str = "/docs";
     pub const OPENAPI_JSON: &'static str = "/openapi.json";
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+/// What a shadow-banned rule does with a submission it would reject. The
+/// client gets the same `200 ok` as a real success either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShadowMode {
+    /// Answer with the rule's error as usual.
+    #[default]
+    Off,
+    Discard,
+    Quarantine,
+}
+
+/// Per-rule shadow-ban modes, read from `APP__SHADOW__*`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ShadowSettings {
+    // NOTE: submissions over the spam reject threshold
+    pub spam: ShadowMode,
+    // NOTE: missing, forged, expired or reused form tokens
+    pub form_token: ShadowMode,
+    pub pow: ShadowMode,
+    // NOTE: failed CAPTCHA checks; an unreachable verifier still answers 503
+    pub captcha: ShadowMode,
+}
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+
+use crate::utils::csv_to_vec;
+
+/// Rule weights and thresholds of the spam scorer, read from `APP__SPAM__*`.
+/// A weight or threshold of `0` disables that rule or action.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SpamSettings {
+    // NOTE: opt-in, scoring can quarantine or reject leads
+    pub enabled: bool,
+
+    // NOTE: links beyond max_links score link_weight each
+    pub max_links: u32,
+    pub link_weight: f64,
+
+    // NOTE: matched against link hosts and the submitter's email domain
+    #[serde(deserialize_with = "csv_to_vec")]
+    pub blocked_domains: Option<Vec<String>>,
+    pub blocked_domain_weight: f64,
+
+    // NOTE: case-insensitive; entries with spaces match as phrases
+    #[serde(deserialize_with = "csv_to_vec")]
+    pub blocked_words: Option<Vec<String>>,
+    pub blocked_word_weight: f64,
+
+    // NOTE: words mixing Latin and Cyrillic letters (homoglyph tricks)
+    pub mixed_script_weight: f64,
+    // NOTE: long words without vowels or with long consonant runs
+    pub gibberish_weight: f64,
+
+    pub caps_ratio: f64,
+    pub caps_weight: f64,
+
+    pub max_repeated_chars: u32,
+    pub repeated_chars_weight: f64,
+
+    pub tag_threshold: f64,
+    pub quarantine_threshold: f64,
+    pub reject_threshold: f64,
+}
+
+impl Default for SpamSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_links: 2,
+            link_weight: 1.0,
+            blocked_domains: None,
+            blocked_domain_weight: 5.0,
+            blocked_words: None,
+            blocked_word_weight: 3.0,
+            mixed_script_weight: 2.0,
+            gibberish_weight: 1.5,
+            caps_ratio: 0.7,
+            caps_weight: 2.0,
+            max_repeated_chars: 5,
+            repeated_chars_weight: 1.0,
+            tag_threshold: 3.0,
+            quarantine_threshold: 6.0,
+            reject_threshold: 10.0,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use serde::Deserialize;
+use std::fmt;
+
+use crate::utils::mask_string::{mask_secret, mask_url};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "submission.received")]
+    SubmissionReceived,
+    #[serde(rename = "email.sent")]
+    EmailSent,
+    #[serde(rename = "email.failed")]
+    EmailFailed,
+}
+
+impl WebhookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SubmissionReceived => "submission.received",
+            Self::EmailSent => "email.sent",
+            Self::EmailFailed => "email.failed",
+        }
+    }
+}
+
+/// An outbound webhook subscription, declared as `[[webhooks]]` in `Settings.toml`.
+#[derive(Clone, Deserialize)]
+pub struct WebhookSettings {
+    pub id: String,
+    pub url: String,
+    // NOTE: signs every delivery, see X-Webhook-Signature
+    pub secret: String,
+    // NOTE: empty = every event
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhookSettings {
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+impl fmt::Debug for WebhookSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookSettings")
+            .field("id", &self.id)
+            .field("url", &mask_url(&self.url))
+            .field("secret", &mask_secret(&self.secret, 0))
+            .field("events", &self.events)
+            .finish()
+    }
+}
@@ -0,0 +1,254 @@
+use axum::{
+    Json,
+    http::{HeaderValue, Method, StatusCode, header::{RETRY_AFTER, WWW_AUTHENTICATE}},
+    response::{IntoResponse, Response},
+};
+use fluent_bundle::FluentValue;
+use std::fmt;
+use thiserror::Error;
+
+use crate::api::rate_limit;
+use crate::services::{
+    api_auth::AuthError,
+    captcha::CaptchaError,
+    flood_control::{QuotaExceeded, QuotaStatus},
+    form_token::FormTokenError,
+    inbound::InboundError,
+    i18n,
+    pow::PowError,
+};
+use crate::types::{ApiError, ErrorCode, FieldError, locale::Locale};
+
+/// Why a JSON body could not be read; serde's own wording stays internal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonProblem {
+    ContentType,
+    /// The body ends mid-value.
+    Eof,
+    Syntax { line: usize, column: usize },
+    Unreadable,
+}
+
+impl JsonProblem {
+    fn args(self) -> Vec<(&'static str, FluentValue<'static>)> {
+        match self {
+            Self::ContentType => vec![("reason", "content-type".into())],
+            Self::Eof => vec![("reason", "eof".into())],
+            Self::Syntax { line, column } => {
+                vec![("reason", "syntax".into()), ("line", line.into()), ("column", column.into())]
+            }
+            Self::Unreadable => vec![("reason", "other".into())],
+        }
+    }
+}
+
+impl fmt::Display for JsonProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ContentType => f.write_str("expected `Content-Type: application/json`"),
+            Self::Eof => f.write_str("request body ends before the JSON value does"),
+            Self::Syntax { line, column } => {
+                write!(f, "request body is not valid JSON (line {line}, column {column})")
+            }
+            Self::Unreadable => f.write_str("request body could not be read"),
+        }
+    }
+}
+
+/// Every way an API request can fail. Clients see `message()` in their
+/// locale next to the stable `code()`; the `Display` text is its English
+/// form for logs and messages missing from the catalog.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{0}")]
+    InvalidJson(JsonProblem),
+    #[error("{0}")]
+    InvalidForm(String),
+    /// Every invalid field of the payload.
+    #[error("{} field(s) failed validation", .0.len())]
+    Validation(Vec<FieldError>),
+    #[error("Idempotency-Key must be 1-255 visible ASCII characters")]
+    InvalidIdempotencyKey,
+    #[error("An identical request is still being processed")]
+    RequestInProgress,
+    #[error("Idempotency-Key was already used with a different payload")]
+    IdempotencyKeyReused,
+    #[error("{}", .0.message())]
+    ApiAuth(AuthError),
+    #[error("{}", .0.message())]
+    FormToken(FormTokenError),
+    #[error("{}", .0.message())]
+    Pow(PowError),
+    #[error("{}", .0.message())]
+    Captcha(CaptchaError),
+    #[error("{}", .0.message())]
+    Quota(QuotaExceeded),
+    #[error("submission was classified as spam")]
+    SpamRejected,
+    #[error("{0} is not an allowed recipient for this API key")]
+    RecipientNotAllowed(String),
+    #[error("No recipient configured")]
+    NoRecipient,
+    /// `failure_message` of the recipients that could not be reached.
+    #[error("{0}")]
+    EmailSendFailed(String),
+    #[error("{0}")]
+    NotificationFailed(String),
+    #[error("{}", .0.message())]
+    Inbound(InboundError),
+    #[error("Access from your network is blocked")]
+    IpBlocked,
+    /// Seconds the ban still lasts.
+    #[error("Too many violations, banned for another {0}s")]
+    IpBanned(i64),
+    #[error("No route for {method} {path}")]
+    RouteNotFound { method: Method, path: String },
+    #[error("no inbound adapter {0}")]
+    InboundAdapterNotFound(String),
+    #[error("A valid admin bearer token or JWT is required")]
+    AdminUnauthorized,
+    #[error("form tokens are not enabled")]
+    FormTokenDisabled,
+    #[error("proof of work is not enabled")]
+    PowDisabled,
+    #[error("{0} is not banned")]
+    BanNotFound(String),
+    #[error("no quarantined submission {0}")]
+    QuarantineItemNotFound(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidJson(_) => ErrorCode::InvalidJson,
+            Self::InvalidForm(_) => ErrorCode::InvalidForm,
+            Self::Validation(_) => ErrorCode::ValidationFailed,
+            Self::InvalidIdempotencyKey => ErrorCode::InvalidIdempotencyKey,
+            Self::RequestInProgress => ErrorCode::RequestInProgress,
+            Self::IdempotencyKeyReused => ErrorCode::IdempotencyKeyReused,
+            Self::ApiAuth(e) => e.error_code(),
+            Self::FormToken(e) => e.error_code(),
+            Self::Pow(e) => e.error_code(),
+            Self::Captcha(e) => e.error_code(),
+            Self::Quota(e) => e.kind.error_code(),
+            Self::SpamRejected => ErrorCode::SpamRejected,
+            Self::RecipientNotAllowed(_) => ErrorCode::RecipientNotAllowed,
+            Self::NoRecipient => ErrorCode::NoRecipient,
+            Self::EmailSendFailed(_) => ErrorCode::EmailSendFailed,
+            Self::NotificationFailed(_) => ErrorCode::NotificationFailed,
+            Self::Inbound(e) => e.error_code(),
+            Self::IpBlocked => ErrorCode::IpBlocked,
+            Self::IpBanned(_) => ErrorCode::IpBanned,
+            Self::RouteNotFound { .. } | Self::InboundAdapterNotFound(_) => ErrorCode::NotFound,
+            Self::AdminUnauthorized => ErrorCode::Unauthorized,
+            Self::FormTokenDisabled => ErrorCode::FormTokenDisabled,
+            Self::PowDisabled => ErrorCode::PowDisabled,
+            Self::BanNotFound(_) => ErrorCode::BanNotFound,
+            Self::QuarantineItemNotFound(_) => ErrorCode::QuarantineItemNotFound,
+        }
+    }
+
+    /// Client-facing message from the `locale` catalog.
+    pub fn message(&self, locale: Locale) -> String {
+        i18n::text(locale, self.code().as_str(), &self.message_args()).unwrap_or_else(|| self.to_string())
+    }
+
+    fn message_args(&self) -> Vec<(&'static str, FluentValue<'static>)> {
+        match self {
+            Self::InvalidJson(problem) => problem.args(),
+            Self::InvalidForm(detail)
+            | Self::EmailSendFailed(detail)
+            | Self::NotificationFailed(detail)
+            | Self::Inbound(InboundError::Malformed(detail)) => vec![("detail", detail.clone().into())],
+            Self::Validation(errors) => vec![("count", errors.len().into())],
+            Self::Captcha(CaptchaError::Failed(codes)) if codes.is_empty() => vec![("codes", "none".into())],
+            Self::Captcha(CaptchaError::Failed(codes)) => vec![("codes", codes.join(", ").into())],
+            Self::Quota(exceeded) => vec![
+                ("limit", exceeded.limit.into()),
+                ("window", exceeded.window.into()),
+                ("retry", exceeded.retry_after.map_or_else(|| "never".into(), Into::into)),
+                ("requested", exceeded.requested.into()),
+            ],
+            Self::RecipientNotAllowed(recipient) => vec![("recipient", recipient.clone().into())],
+            Self::IpBanned(seconds) => vec![("seconds", (*seconds).into())],
+            Self::RouteNotFound { method, path } => {
+                vec![("method", method.to_string().into()), ("path", path.clone().into())]
+            }
+            Self::InboundAdapterNotFound(id) => vec![("path", "none".into()), ("id", id.clone().into())],
+            Self::BanNotFound(ip) => vec![("ip", ip.clone().into())],
+            Self::QuarantineItemNotFound(id) => vec![("id", id.clone().into())],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidJson(_)
+            | Self::InvalidForm(_)
+            | Self::Validation(_)
+            | Self::InvalidIdempotencyKey
+            | Self::FormToken(_)
+            | Self::Pow(_)
+            | Self::SpamRejected
+            | Self::NoRecipient
+            | Self::Inbound(InboundError::Malformed(_)) => StatusCode::BAD_REQUEST,
+            Self::Captcha(CaptchaError::Unavailable(_)) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Captcha(_) => StatusCode::BAD_REQUEST,
+            Self::ApiAuth(_) | Self::Inbound(InboundError::Unauthorized) | Self::AdminUnauthorized => {
+                StatusCode::UNAUTHORIZED
+            }
+            Self::RecipientNotAllowed(_) | Self::IpBlocked | Self::IpBanned(_) => StatusCode::FORBIDDEN,
+            Self::RouteNotFound { .. }
+            | Self::InboundAdapterNotFound(_)
+            | Self::FormTokenDisabled
+            | Self::PowDisabled
+            | Self::BanNotFound(_)
+            | Self::QuarantineItemNotFound(_) => StatusCode::NOT_FOUND,
+            Self::RequestInProgress => StatusCode::CONFLICT,
+            Self::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Quota(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::EmailSendFailed(_) | Self::NotificationFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let body = ApiError {
+            error: self.code(),
+            message: self.message(i18n::current()),
+            errors: match &self {
+                Self::Validation(errors) => errors.clone(),
+                _ => Vec::new(),
+            },
+        };
+        let mut response = (self.status(), Json(body)).into_response();
+        let headers = response.headers_mut();
+        match &self {
+            Self::ApiAuth(_) | Self::AdminUnauthorized => {
+                headers.insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            }
+            // NOTE: no retry hints for a request that can never fit
+            Self::Quota(QuotaExceeded {
+                limit,
+                retry_after: Some(retry_after),
+                ..
+            }) => {
+                rate_limit::apply_headers(
+                    headers,
+                    &QuotaStatus {
+                        limit: *limit,
+                        remaining: 0,
+                        reset: *retry_after,
+                    },
+                );
+                headers.insert(RETRY_AFTER, HeaderValue::from(*retry_after));
+            }
+            Self::IpBanned(retry_after) => {
+                headers.insert(RETRY_AFTER, HeaderValue::from(*retry_after));
+            }
+            _ => {}
+        }
+        response
+    }
+}
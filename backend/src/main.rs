@@ -1,6 +1,6 @@
 use http::{
     HeaderValue, Method,
-    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
 };
 use std::time::Duration;
 use tower_http::cors::CorsLayer;
@@ -10,6 +10,7 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use std::{net::SocketAddr, sync::Arc};
+use tokio::sync::{Mutex, RwLock};
 
 mod api;
 mod config;
@@ -22,6 +23,7 @@ mod middlewares;
 mod shutdown;
 
 use config::{ApiPaths, CONFIG};
+use services::{bans::BanList, ip_filter::IpFilter, quarantine::QuarantineQueue, webhooks::WebhookLog};
 use state::AppState;
 use shutdown::shutdown_signal;
 
@@ -30,18 +32,41 @@ use shutdown::shutdown_signal;
 async fn main() {
     CONFIG.init_tracing();
     CONFIG.log_effective();
+    services::i18n::init(CONFIG.locales_dir.as_deref());
 
     let state = Arc::new(AppState {
         flood_control: Default::default(),
+        form_tokens: Default::default(),
+        pow_challenges: Default::default(),
+        signatures: Default::default(),
+        http: reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client"),
+        ip_filter: RwLock::new(IpFilter::load().expect("Failed to load IP lists")),
+        bans: Mutex::new(BanList::load()),
+        quarantine: Mutex::new(QuarantineQueue::load()),
+        idempotency: Default::default(),
+        submissions: Default::default(),
+        webhooks: Arc::new(Mutex::new(WebhookLog::new(CONFIG.admin_history_size as usize))),
     });
+    services::ip_filter::spawn_reloader(state.clone());
+    services::quarantine::spawn_digest(state.clone());
 
     let app = api::app()
         .merge(
             SwaggerUi::new(ApiPaths::SWAGGER_UI)
                 .url(ApiPaths::OPENAPI_JSON, api::openapi::ApiDoc::openapi()),
         )
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(middlewares::request_span))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middlewares::ip_filter_layer,
+        ))
         .route_layer(axum::middleware::from_fn(middlewares::real_ip_layer))
+        .layer(axum::middleware::from_fn(middlewares::problem_details_layer))
+        .layer(axum::middleware::from_fn(middlewares::locale_layer))
+        .layer(axum::middleware::from_fn(middlewares::request_id_layer))
         .with_state(state.clone());
 
     let allowed_origins: Vec<HeaderValue> = CONFIG
@@ -65,8 +90,22 @@ async fn main() {
             Method::DELETE,
             Method::OPTIONS,
         ])
-        .allow_headers([CONTENT_TYPE, AUTHORIZATION, ACCEPT])
-        .expose_headers([AUTHORIZATION])
+        .allow_headers([
+            CONTENT_TYPE,
+            AUTHORIZATION,
+            ACCEPT,
+            api::idempotency::IDEMPOTENCY_KEY,
+            api::request_id::X_REQUEST_ID,
+        ])
+        .expose_headers([
+            AUTHORIZATION,
+            RETRY_AFTER,
+            api::idempotency::IDEMPOTENT_REPLAYED,
+            api::rate_limit::RATELIMIT_LIMIT,
+            api::rate_limit::RATELIMIT_REMAINING,
+            api::rate_limit::RATELIMIT_RESET,
+            api::request_id::X_REQUEST_ID,
+        ])
         .max_age(Duration::from_secs(60 * 30));
 
     let app = app.layer(cors);
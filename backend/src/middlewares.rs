@@ -1,53 +1,185 @@
 use axum::{
     body::Body,
-    extract::connect_info::ConnectInfo,
-    http::Request,
+    extract::{OriginalUri, State, connect_info::ConnectInfo},
+    http::{HeaderValue, Request, header::{ACCEPT, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_LANGUAGE, VARY}},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+use chrono::Utc;
+use tracing::{Span, info, info_span};
+
+use crate::api::problem;
+use crate::api::request_id::{self, RequestId, X_REQUEST_ID};
+use crate::services::i18n;
+use crate::types::locale::Locale;
+use crate::config::{ApiPaths, CONFIG};
+use crate::services::{admin_auth, ip_filter::IpDecision};
+use crate::state::AppState;
+use crate::errors::app::AppError;
+use crate::utils::client_ip::resolve_client_ip;
+
+/// Set by `ip_filter_layer`; allowlisted clients skip rate limiting.
+#[derive(Debug, Clone, Copy)]
+pub struct Allowlisted(pub bool);
 
 pub async fn real_ip_layer(
     mut req: Request<Body>,
     next: Next,
 ) -> Response {
-    let ip_from_headers: Option<IpAddr> = {
-        let headers = req.headers();
-
-        if let Some(v) = headers.get("x-forwarded-for") {
-            if let Ok(s) = v.to_str() {
-                if let Some(first) = s.split(',').next() {
-                    if let Ok(ip) = first.trim().parse::<IpAddr>() {
-                        Some(ip)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else if let Some(v) = headers.get("x-real-ip") {
-            if let Ok(s) = v.to_str() {
-                s.parse::<IpAddr>().ok()
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    };
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|conn_info| conn_info.0.ip())
+        .unwrap_or(IpAddr::from([127, 0, 0, 1]));
 
-    let real_ip = if let Some(ip) = ip_from_headers {
-        ip
-    } else if let Some(conn_info) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
-        conn_info.0.ip()
-    } else {
-        IpAddr::from([127, 0, 0, 1])
-    };
+    let real_ip = resolve_client_ip(peer, req.headers(), &CONFIG.trusted_proxies, CONFIG.client_ip_header);
 
     req.extensions_mut().insert(real_ip);
 
     next.run(req).await
 }
+
+/// Rejects denylisted and banned clients. Must run after `real_ip_layer`,
+/// which provides the client `IpAddr`.
+pub async fn ip_filter_layer(
+    State(state): State<Arc<AppState>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let ip = req
+        .extensions()
+        .get::<IpAddr>()
+        .copied()
+        .unwrap_or(IpAddr::from([127, 0, 0, 1]));
+
+    let decision = state.ip_filter.read().await.decide(&ip);
+    if decision == IpDecision::Denied {
+        info!(ip = %ip, "blocked by IP denylist");
+        return AppError::IpBlocked.into_response();
+    }
+
+    // NOTE: admin routes stay reachable so a banned operator can lift the ban
+    let admin_route = req
+        .uri()
+        .path()
+        .strip_prefix(ApiPaths::V1_PREFIX)
+        .is_some_and(|p| p.starts_with(ApiPaths::ADMIN_PREFIX));
+    if decision != IpDecision::Allowlisted && !admin_route {
+        let now = Utc::now().timestamp();
+        let banned_until = state
+            .bans
+            .lock()
+            .await
+            .active(&ip.to_string(), now)
+            .map(|ban| ban.until);
+        if let Some(until) = banned_until {
+            return AppError::IpBanned((until - now).max(1)).into_response();
+        }
+    }
+
+    req.extensions_mut()
+        .insert(Allowlisted(decision == IpDecision::Allowlisted));
+
+    next.run(req).await
+}
+
+/// Takes the client's `X-Request-Id` or makes one, hands it to the handlers
+/// and the trace span, and echoes it on the response.
+pub async fn request_id_layer(mut req: Request<Body>, next: Next) -> Response {
+    let id = RequestId::accept_or_generate(req.headers().get(&X_REQUEST_ID));
+    let value = HeaderValue::from_str(&id.0).expect("request ids are visible ASCII");
+    req.headers_mut().insert(X_REQUEST_ID, value.clone());
+    req.extensions_mut().insert(id.clone());
+    let mut response = request_id::scope(id, next.run(req)).await;
+    response.headers_mut().insert(X_REQUEST_ID, value);
+    response
+}
+
+/// The `TraceLayer` span of a request, carrying its id.
+pub fn request_span(req: &Request<Body>) -> Span {
+    let id = req.extensions().get::<RequestId>().map(|id| id.0.as_str()).unwrap_or_default();
+    info_span!("request", method = %req.method(), uri = %req.uri(), request_id = %id)
+}
+
+/// Answers in the language `Accept-Language` prefers, `APP__DEFAULT_LOCALE`
+/// otherwise; a payload `locale` may still switch it.
+pub async fn locale_layer(req: Request<Body>, next: Next) -> Response {
+    let locale = req
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(Locale::negotiate)
+        .unwrap_or(CONFIG.default_locale);
+    i18n::scope(locale, async move {
+        let mut response = next.run(req).await;
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_LANGUAGE, HeaderValue::from_static(i18n::current().as_str()));
+        headers.append(VARY, HeaderValue::from_static("accept-language"));
+        response
+    })
+    .await
+}
+
+/// Sends error answers as `application/problem+json` to clients whose
+/// `Accept` prefers it.
+pub async fn problem_details_layer(req: Request<Body>, next: Next) -> Response {
+    let wants_problem = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(problem::wants_problem);
+    let instance = req.uri().path().to_string();
+    let response = next.run(req).await;
+    let failed = response.status().is_client_error() || response.status().is_server_error();
+    if !(wants_problem && failed) {
+        return response;
+    }
+    problem::into_problem(response, &instance).await
+}
+
+/// Guards the admin router with `Authorization: Bearer <admin token or JWT>`.
+/// The routes pretend not to exist while no admin credential is configured.
+pub async fn require_admin(req: Request<Body>, next: Next) -> Response {
+    if !admin_auth::enabled() {
+        let path = req
+            .extensions()
+            .get::<OriginalUri>()
+            .map_or(req.uri().path(), |uri| uri.path());
+        return AppError::RouteNotFound {
+            method: req.method().clone(),
+            path: path.to_string(),
+        }
+        .into_response();
+    }
+
+    let presented = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    let authorized = match presented {
+        Some(token) if admin_auth::is_static_token(token) => true,
+        // NOTE: static tokens never contain dots, a JWT always has two
+        Some(token) if token.contains('.') => match admin_auth::verify_jwt(token, Utc::now().timestamp()) {
+            Ok(sub) => {
+                info!(sub = sub.as_deref().unwrap_or("-"), "admin JWT accepted");
+                true
+            }
+            Err(e) => {
+                info!(reason = e.as_str(), "admin JWT rejected");
+                false
+            }
+        },
+        _ => false,
+    };
+    if !authorized {
+        return AppError::AdminUnauthorized.into_response();
+    }
+
+    next.run(req).await
+}
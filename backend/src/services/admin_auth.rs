@@ -0,0 +1,201 @@
+use ed25519_dalek::Signature;
+use serde::Deserialize;
+
+use crate::config::CONFIG;
+use crate::utils::crypto::{base64url_decode, constant_time_eq, parse_ed25519_key, verify_hmac_sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtError {
+    Malformed,
+    /// `alg` is not one with a key configured; `none` never is.
+    UnsupportedAlgorithm,
+    BadSignature,
+    Expired,
+    NotYetValid,
+}
+
+impl JwtError {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Malformed => "malformed",
+            Self::UnsupportedAlgorithm => "unsupported_algorithm",
+            Self::BadSignature => "bad_signature",
+            Self::Expired => "expired",
+            Self::NotYetValid => "not_yet_valid",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    exp: i64,
+    nbf: Option<i64>,
+    sub: Option<String>,
+}
+
+/// Whether any admin credential is configured; without one the admin API
+/// does not exist.
+pub fn enabled() -> bool {
+    CONFIG.admin_tokens.as_ref().is_some_and(|t| !t.is_empty())
+        || CONFIG.admin_jwt_hs256_secret.is_some()
+        || CONFIG.admin_jwt_ed25519_public_key.is_some()
+}
+
+pub fn is_static_token(presented: &str) -> bool {
+    CONFIG
+        .admin_tokens
+        .iter()
+        .flatten()
+        .any(|t| constant_time_eq(t.as_bytes(), presented.as_bytes()))
+}
+
+/// Verifies an HS256 or EdDSA (Ed25519) JWT against the configured keys and
+/// returns its `sub`. `exp` is required; `exp` and `nbf` allow
+/// `admin_jwt_leeway_seconds` of clock skew.
+pub fn verify_jwt(token: &str, now: i64) -> Result<Option<String>, JwtError> {
+    let keys = Keys {
+        hs256_secret: CONFIG.admin_jwt_hs256_secret.as_deref(),
+        ed25519_public_key: CONFIG.admin_jwt_ed25519_public_key.as_deref(),
+        leeway: CONFIG.admin_jwt_leeway_seconds as i64,
+    };
+    verify_with(token, &keys, now)
+}
+
+/// What `verify_jwt` checks against, taken apart from `CONFIG`.
+struct Keys<'a> {
+    hs256_secret: Option<&'a str>,
+    ed25519_public_key: Option<&'a str>,
+    leeway: i64,
+}
+
+fn verify_with(token: &str, keys: &Keys, now: i64) -> Result<Option<String>, JwtError> {
+    let mut parts = token.split('.');
+    let (Some(header), Some(payload), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(JwtError::Malformed);
+    };
+    let decode = |part: &str| base64url_decode(part).ok_or(JwtError::Malformed);
+    let alg: Header = serde_json::from_slice(&decode(header)?).map_err(|_| JwtError::Malformed)?;
+    let signature = decode(signature)?;
+    let signed = &token[..header.len() + 1 + payload.len()];
+
+    let valid = match alg.alg.as_str() {
+        "HS256" => {
+            let secret = keys.hs256_secret.ok_or(JwtError::UnsupportedAlgorithm)?;
+            verify_hmac_sha256(secret.as_bytes(), signed.as_bytes(), &signature)
+        }
+        "EdDSA" => {
+            let key = keys.ed25519_public_key.and_then(parse_ed25519_key).ok_or(JwtError::UnsupportedAlgorithm)?;
+            let signature = Signature::from_slice(&signature).map_err(|_| JwtError::BadSignature)?;
+            key.verify_strict(signed.as_bytes(), &signature).is_ok()
+        }
+        _ => return Err(JwtError::UnsupportedAlgorithm),
+    };
+    if !valid {
+        return Err(JwtError::BadSignature);
+    }
+
+    let claims: Claims = serde_json::from_slice(&decode(payload)?).map_err(|_| JwtError::Malformed)?;
+    let leeway = keys.leeway;
+    if now > claims.exp + leeway {
+        return Err(JwtError::Expired);
+    }
+    if claims.nbf.is_some_and(|nbf| now + leeway < nbf) {
+        return Err(JwtError::NotYetValid);
+    }
+    Ok(claims.sub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::{base64url_encode, hmac_sha256};
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::{Value, json};
+
+    const SECRET: &str = "admin-jwt-secret";
+    const NOW: i64 = 1_000_000;
+
+    fn unsigned(alg: &str, claims: Value) -> String {
+        let header = base64url_encode(json!({"alg": alg, "typ": "JWT"}).to_string().as_bytes());
+        format!("{header}.{}", base64url_encode(claims.to_string().as_bytes()))
+    }
+
+    fn hs256(secret: &str, claims: Value) -> String {
+        let signed = unsigned("HS256", claims);
+        let signature = hmac_sha256(secret.as_bytes(), signed.as_bytes());
+        format!("{signed}.{}", base64url_encode(&signature))
+    }
+
+    fn keys(public_key: Option<&str>) -> Keys<'_> {
+        Keys {
+            hs256_secret: Some(SECRET),
+            ed25519_public_key: public_key,
+            leeway: 30,
+        }
+    }
+
+    fn claims(exp: i64) -> Value {
+        json!({"sub": "alice", "exp": exp})
+    }
+
+    #[test]
+    fn accepts_a_valid_hs256_token() {
+        let token = hs256(SECRET, claims(NOW + 60));
+        assert_eq!(verify_with(&token, &keys(None), NOW), Ok(Some("alice".into())));
+    }
+
+    #[test]
+    fn rejects_foreign_and_altered_signatures() {
+        let forged = hs256("guessed-secret", claims(NOW + 60));
+        assert_eq!(verify_with(&forged, &keys(None), NOW), Err(JwtError::BadSignature));
+
+        let token = hs256(SECRET, claims(NOW + 60));
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        let promoted = format!("{}.{signature}", unsigned("HS256", json!({"sub": "root", "exp": NOW + 60})));
+        assert_eq!(verify_with(&promoted, &keys(None), NOW), Err(JwtError::BadSignature));
+    }
+
+    #[test]
+    fn exp_is_required_and_enforced_with_leeway() {
+        assert_eq!(verify_with(&hs256(SECRET, claims(NOW - 30)), &keys(None), NOW), Ok(Some("alice".into())));
+        assert_eq!(verify_with(&hs256(SECRET, claims(NOW - 31)), &keys(None), NOW), Err(JwtError::Expired));
+
+        let forever = hs256(SECRET, json!({"sub": "alice"}));
+        assert_eq!(verify_with(&forever, &keys(None), NOW), Err(JwtError::Malformed));
+
+        let early = hs256(SECRET, json!({"sub": "alice", "exp": NOW + 600, "nbf": NOW + 31}));
+        assert_eq!(verify_with(&early, &keys(None), NOW), Err(JwtError::NotYetValid));
+    }
+
+    #[test]
+    fn rejects_algorithms_without_a_configured_key() {
+        let none = format!("{}.", unsigned("none", claims(NOW + 60)));
+        assert_eq!(verify_with(&none, &keys(None), NOW), Err(JwtError::UnsupportedAlgorithm));
+
+        let rs256 = format!("{}.c2ln", unsigned("RS256", claims(NOW + 60)));
+        assert_eq!(verify_with(&rs256, &keys(None), NOW), Err(JwtError::UnsupportedAlgorithm));
+
+        let signed = unsigned("EdDSA", claims(NOW + 60));
+        let signing = SigningKey::from_bytes(&[7; 32]);
+        let token = format!("{signed}.{}", base64url_encode(&signing.sign(signed.as_bytes()).to_bytes()));
+        assert_eq!(verify_with(&token, &keys(None), NOW), Err(JwtError::UnsupportedAlgorithm));
+
+        let public_key = base64url_encode(signing.verifying_key().as_bytes());
+        assert_eq!(verify_with(&token, &keys(Some(&public_key)), NOW), Ok(Some("alice".into())));
+
+        // NOTE: key confusion, an HS256 token keyed with the Ed25519 public key
+        let confused = hs256(&public_key, claims(NOW + 60));
+        let only_ed25519 = Keys {
+            hs256_secret: None,
+            ..keys(Some(&public_key))
+        };
+        assert_eq!(verify_with(&confused, &only_ed25519, NOW), Err(JwtError::UnsupportedAlgorithm));
+    }
+}
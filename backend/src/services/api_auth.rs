@@ -0,0 +1,201 @@
+use axum::http::{HeaderMap, HeaderName, header::AUTHORIZATION};
+
+use crate::config::{ApiKeySettings, CONFIG};
+use crate::services::nonce_store::NonceStore;
+use crate::types::ErrorCode;
+use crate::utils::crypto::{constant_time_eq, hex_decode, sha256_hex, verify_hmac_sha256};
+
+pub const API_KEY_ID: HeaderName = HeaderName::from_static("x-api-key-id");
+pub const SIGNATURE_TIMESTAMP: HeaderName = HeaderName::from_static("x-signature-timestamp");
+pub const SIGNATURE: HeaderName = HeaderName::from_static("x-signature");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    InvalidKey,
+    /// Signature headers are incomplete or the key id is unknown.
+    SignatureMalformed,
+    SignatureInvalid,
+    SignatureExpired,
+    SignatureReplayed,
+}
+
+impl AuthError {
+    pub fn error_code(self) -> ErrorCode {
+        match self {
+            Self::InvalidKey => ErrorCode::InvalidApiKey,
+            Self::SignatureMalformed => ErrorCode::SignatureMalformed,
+            Self::SignatureInvalid => ErrorCode::SignatureInvalid,
+            Self::SignatureExpired => ErrorCode::SignatureExpired,
+            Self::SignatureReplayed => ErrorCode::SignatureReplayed,
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::InvalidKey => "API key is not valid",
+            Self::SignatureMalformed => {
+                "X-Api-Key-Id, X-Signature-Timestamp and X-Signature must name a signing key and carry a hex signature"
+            }
+            Self::SignatureInvalid => "request signature does not match",
+            Self::SignatureExpired => "X-Signature-Timestamp is outside the accepted clock skew",
+            Self::SignatureReplayed => "this signed request was already received",
+        }
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &HeaderName) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+fn by_key(key: &str) -> Result<&'static ApiKeySettings, AuthError> {
+    let hash = sha256_hex(key.as_bytes());
+    CONFIG
+        .api_keys
+        .iter()
+        .find(|k| {
+            k.key_sha256
+                .as_deref()
+                .is_some_and(|h| constant_time_eq(h.to_ascii_lowercase().as_bytes(), hash.as_bytes()))
+        })
+        .ok_or(AuthError::InvalidKey)
+}
+
+/// Signature is the hex HMAC-SHA256 of `<timestamp>.<raw body>` with the
+/// key's `hmac_secret`; each one is accepted once.
+fn by_signature<'a>(
+    headers: &HeaderMap,
+    body: &[u8],
+    keys: &'a [ApiKeySettings],
+    tolerance: i64,
+    now: i64,
+    seen: &mut NonceStore,
+) -> Result<&'a ApiKeySettings, AuthError> {
+    let id = header(headers, &API_KEY_ID).ok_or(AuthError::SignatureMalformed)?;
+    let timestamp = header(headers, &SIGNATURE_TIMESTAMP).ok_or(AuthError::SignatureMalformed)?;
+    let signature = header(headers, &SIGNATURE)
+        .map(|s| s.trim_start_matches("sha256="))
+        .and_then(hex_decode)
+        .ok_or(AuthError::SignatureMalformed)?;
+    let (key, secret) = keys
+        .iter()
+        .find_map(|k| Some((k, k.hmac_secret.as_deref()?)).filter(|(k, _)| k.id == id))
+        .ok_or(AuthError::SignatureMalformed)?;
+
+    let mut signed = format!("{timestamp}.").into_bytes();
+    signed.extend_from_slice(body);
+    if !verify_hmac_sha256(secret.as_bytes(), &signed, &signature) {
+        return Err(AuthError::SignatureInvalid);
+    }
+    let sent_at: i64 = timestamp.parse().map_err(|_| AuthError::SignatureMalformed)?;
+    if (now - sent_at).abs() > tolerance {
+        return Err(AuthError::SignatureExpired);
+    }
+    let nonce = format!("{id}:{}", sha256_hex(&signature));
+    if !seen.consume(&nonce, sent_at + tolerance, now) {
+        return Err(AuthError::SignatureReplayed);
+    }
+    Ok(key)
+}
+
+/// Identifies a server-to-server caller by signature headers or a bearer API
+/// key; `None` for anonymous requests.
+pub fn authenticate(
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+    seen: &mut NonceStore,
+) -> Result<Option<&'static ApiKeySettings>, AuthError> {
+    if headers.contains_key(SIGNATURE) {
+        let tolerance = CONFIG.signature_tolerance_seconds as i64;
+        return by_signature(headers, body, &CONFIG.api_keys, tolerance, now, seen).map(Some);
+    }
+    let Some(authorization) = header(headers, &AUTHORIZATION) else {
+        return Ok(None);
+    };
+    let key = authorization
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .ok_or(AuthError::InvalidKey)?;
+    by_key(key).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crypto::hmac_sha256;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    const SECRET: &str = "crm-signing-secret";
+    const BODY: &[u8] = br#"{"subject":"Hi"}"#;
+    const SENT: i64 = 1_000_000;
+
+    fn keys() -> Vec<ApiKeySettings> {
+        vec![serde_json::from_value(json!({"id": "crm", "hmac_secret": SECRET})).unwrap()]
+    }
+
+    fn signed(id: &str, timestamp: i64, secret: &str, body: &[u8]) -> HeaderMap {
+        let mut data = format!("{timestamp}.").into_bytes();
+        data.extend_from_slice(body);
+        let hex: String = hmac_sha256(secret.as_bytes(), &data).iter().map(|b| format!("{b:02x}")).collect();
+        let mut headers = HeaderMap::new();
+        headers.insert(API_KEY_ID, HeaderValue::from_str(id).unwrap());
+        headers.insert(SIGNATURE_TIMESTAMP, HeaderValue::from(timestamp));
+        headers.insert(SIGNATURE, HeaderValue::from_str(&format!("sha256={hex}")).unwrap());
+        headers
+    }
+
+    fn check(headers: &HeaderMap, body: &[u8], now: i64, seen: &mut NonceStore) -> Result<String, AuthError> {
+        by_signature(headers, body, &keys(), 300, now, seen).map(|key| key.id.clone())
+    }
+
+    #[test]
+    fn a_signature_is_accepted_once() {
+        let headers = signed("crm", SENT, SECRET, BODY);
+        let mut seen = NonceStore::default();
+        assert_eq!(check(&headers, BODY, SENT + 10, &mut seen), Ok("crm".into()));
+        assert_eq!(check(&headers, BODY, SENT + 20, &mut seen), Err(AuthError::SignatureReplayed));
+    }
+
+    #[test]
+    fn timestamps_outside_the_skew_are_refused() {
+        let mut seen = NonceStore::default();
+        for now in [SENT + 301, SENT - 301] {
+            let headers = signed("crm", SENT, SECRET, BODY);
+            assert_eq!(check(&headers, BODY, now, &mut seen), Err(AuthError::SignatureExpired));
+        }
+        for now in [SENT + 300, SENT - 300] {
+            let headers = signed("crm", SENT, SECRET, BODY);
+            assert_eq!(check(&headers, BODY, now, &mut NonceStore::default()), Ok("crm".into()));
+        }
+    }
+
+    #[test]
+    fn the_timestamp_and_body_are_covered_by_the_signature() {
+        let mut seen = NonceStore::default();
+        let mut moved = signed("crm", SENT, SECRET, BODY);
+        moved.insert(SIGNATURE_TIMESTAMP, HeaderValue::from(SENT + 60));
+        assert_eq!(check(&moved, BODY, SENT, &mut seen), Err(AuthError::SignatureInvalid));
+
+        let headers = signed("crm", SENT, SECRET, BODY);
+        assert_eq!(check(&headers, br#"{"subject":"Bye"}"#, SENT, &mut seen), Err(AuthError::SignatureInvalid));
+
+        let forged = signed("crm", SENT, "guessed-secret", BODY);
+        assert_eq!(check(&forged, BODY, SENT, &mut seen), Err(AuthError::SignatureInvalid));
+    }
+
+    #[test]
+    fn unknown_keys_and_broken_headers_are_malformed() {
+        let mut seen = NonceStore::default();
+        let unknown = signed("erp", SENT, SECRET, BODY);
+        assert_eq!(check(&unknown, BODY, SENT, &mut seen), Err(AuthError::SignatureMalformed));
+
+        let mut not_hex = signed("crm", SENT, SECRET, BODY);
+        not_hex.insert(SIGNATURE, HeaderValue::from_static("sha256=zz"));
+        assert_eq!(check(&not_hex, BODY, SENT, &mut seen), Err(AuthError::SignatureMalformed));
+
+        let mut missing = signed("crm", SENT, SECRET, BODY);
+        missing.remove(SIGNATURE_TIMESTAMP);
+        assert_eq!(check(&missing, BODY, SENT, &mut seen), Err(AuthError::SignatureMalformed));
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::Local;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use tracing::error;
+
+use crate::api::request_id;
+use crate::config::CONFIG;
+use crate::services::quarantine::QuarantinedSubmission;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditDecision {
+    /// Answered `200 ok`, nothing stored or sent.
+    Discarded,
+    /// Answered `200 ok`, held for moderation.
+    Quarantined,
+    /// A moderator sent a quarantined submission after all.
+    Approved,
+    /// A moderator dropped a quarantined submission.
+    Rejected,
+}
+
+/// One line of the audit trail: a submission the client was told succeeded
+/// although it was not sent, or a moderator's later decision on it.
+#[derive(Debug, Serialize)]
+pub struct AuditEntry<'a> {
+    pub ip: &'a str,
+    pub decision: AuditDecision,
+    /// Check that fired, e.g. `spam` or `honeypot`.
+    pub rule: &'a str,
+    /// Error code the client would otherwise have seen.
+    pub code: &'a str,
+    pub email: Option<&'a str>,
+    pub subject: &'a str,
+    pub quarantine_id: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    timestamp: String,
+    /// `X-Request-Id` of the request that made the decision.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(flatten)]
+    entry: &'a AuditEntry<'a>,
+}
+
+pub fn record(entry: &AuditEntry) {
+    let line = match serde_json::to_string(&Line {
+        timestamp: Local::now().to_rfc3339(),
+        request_id: request_id::current(),
+        entry,
+    }) {
+        Ok(line) => line,
+        Err(e) => return error!("Warning: Failed to serialize audit entry: {}", e),
+    };
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&CONFIG.audit_log_file)
+        .and_then(|mut file| writeln!(file, "{line}"));
+    if let Err(e) = result {
+        error!("Warning: Failed to write to audit log: {}", e);
+    }
+}
+
+/// Records a moderator's decision on a quarantined submission.
+pub fn record_moderation(item: &QuarantinedSubmission, decision: AuditDecision, code: &str) {
+    let rule = item.shadow_rule.clone().unwrap_or_else(|| {
+        item.reasons.iter().map(|r| r.as_str()).collect::<Vec<_>>().join(",")
+    });
+    record(&AuditEntry {
+        ip: &item.ip,
+        decision,
+        rule: &rule,
+        code,
+        email: item.email.as_deref(),
+        subject: &item.subject,
+        quarantine_id: Some(&item.id),
+    });
+}
@@ -0,0 +1,91 @@
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::api::request_id;
+use crate::config::CONFIG;
+use crate::services::{
+    deliver::{REQUEST_ID_HEADER, deliver},
+    i18n,
+};
+use crate::state::AppState;
+use crate::types::locale::Locale;
+use crate::utils::mask_string::mask_email;
+
+// NOTE: characters of the submitter's message quoted back to them
+const EXCERPT_CHARS: usize = 100;
+
+/// Confirms receipt to the submitter with the fixed `AutoReplySubject` /
+/// `AutoReplyBody` texts of `locale`. The address is whatever the sender
+/// typed, so only `excerpt` of their `message` is repeated, or nothing.
+/// Runs in the background; a failure is only logged, the submission itself
+/// went through. Skipped once the global daily cap is reached.
+pub async fn send(state: &AppState, email: &str, message: &str, locale: Locale) {
+    if !CONFIG.auto_reply {
+        return;
+    }
+    if let Err(exceeded) = state.flood_control.lock().await.hit_global(1, Utc::now().timestamp()) {
+        info!(email = mask_email(email), quota = exceeded.kind.as_str(), "auto-reply skipped");
+        return;
+    }
+    let text = |id| i18n::text(locale, id, &[]);
+    let (Some(subject), Some(body), Some(footer)) = (text("AutoReplySubject"), text("AutoReplyBody"), text("AutoReplyFooter"))
+    else {
+        return;
+    };
+    let quote = excerpt(message).and_then(|excerpt| Some(format!("{}\n> {excerpt}", text("AutoReplyExcerpt")?)));
+    let body = [Some(body), quote, Some(footer)].into_iter().flatten().collect::<Vec<_>>().join("\n\n");
+    let email = email.trim().to_string();
+    let request_id = request_id::current();
+    tokio::spawn(async move {
+        // NOTE: RFC 3834, keeps the submitter's own auto-responder from answering
+        let mut headers = vec![("Auto-Submitted", "auto-replied".to_string())];
+        headers.extend(request_id.map(|id| (REQUEST_ID_HEADER, id)));
+        let failures = deliver(std::slice::from_ref(&email), &subject, &body, &headers).await;
+        match failures.first() {
+            None => info!(email = mask_email(&email), locale = locale.as_str(), "auto-reply sent"),
+            Some((_, error)) => warn!(email = mask_email(&email), error = %error, "auto-reply failed"),
+        }
+    });
+}
+
+/// The start of `message` on one line, or `None` when it holds anything that
+/// could turn the reply into a relay: a link, a domain or an address.
+fn excerpt(message: &str) -> Option<String> {
+    let words: Vec<&str> = message.split_whitespace().collect();
+    if words.is_empty() || words.iter().any(|w| looks_like_link(w)) {
+        return None;
+    }
+    let line = words.join(" ");
+    let mut excerpt: String = line.chars().filter(|c| !c.is_control()).take(EXCERPT_CHARS).collect();
+    if line.chars().count() > EXCERPT_CHARS {
+        excerpt.push('…');
+    }
+    Some(excerpt)
+}
+
+fn looks_like_link(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+    let tld = word.rsplit_once('.').map(|(_, tld)| tld);
+    word.contains("://")
+        || word.contains('@')
+        || tld.is_some_and(|tld| tld.chars().count() >= 2 && tld.chars().all(char::is_alphabetic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excerpt_is_short_and_never_carries_links() {
+        assert_eq!(excerpt("Hello,\n\nplease call me back."), Some("Hello, please call me back.".into()));
+        let long = "word ".repeat(40);
+        let cut = excerpt(&long).unwrap();
+        assert_eq!(cut.chars().count(), EXCERPT_CHARS + 1);
+        assert!(cut.ends_with('…'));
+        for relay in ["Claim at https://evil.example", "visit evil.com now", "write to a@b.ru", "see WWW.EVIL.RU."] {
+            assert_eq!(excerpt(relay), None, "{relay}");
+        }
+        assert_eq!(excerpt("Version 1.5, e.g. today"), Some("Version 1.5, e.g. today".into()));
+        assert_eq!(excerpt("  "), None);
+    }
+}
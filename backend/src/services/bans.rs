@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::config::CONFIG;
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    RateLimited,
+    Spam,
+}
+
+impl Violation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RateLimited => "rate_limited",
+            Self::Spam => "spam",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ban {
+    pub reason: String,
+    /// 1-based escalation level; picks the entry of `ban_durations`.
+    pub strike: u32,
+    pub banned_at: i64,
+    pub until: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Strikes {
+    count: u32,
+    last_until: i64,
+}
+
+/// What survives a restart: active bans and escalation levels.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Persisted {
+    bans: HashMap<String, Ban>,
+    strikes: HashMap<String, Strikes>,
+}
+
+/// The escalation settings, taken apart from `CONFIG` so tests can pick
+/// their own.
+struct Policy<'a> {
+    violations: usize,
+    window: i64,
+    durations: &'a [u64],
+    strike_ttl: i64,
+}
+
+fn policy() -> Policy<'static> {
+    Policy {
+        violations: CONFIG.ban_violations as usize,
+        window: CONFIG.ban_violation_window_seconds as i64,
+        durations: &CONFIG.ban_durations,
+        strike_ttl: CONFIG.ban_strike_ttl_seconds as i64,
+    }
+}
+
+/// fail2ban-style escalation: `ban_violations` within
+/// `ban_violation_window_seconds` bans the IP for the next duration in
+/// `ban_durations`. Strikes are forgotten `ban_strike_ttl_seconds` after the
+/// last ban ends.
+#[derive(Debug, Default)]
+pub struct BanList {
+    bans: HashMap<String, Ban>,
+    strikes: HashMap<String, Strikes>,
+    // NOTE: IP -> violation timestamps, in memory only
+    violations: HashMap<String, Vec<i64>>,
+}
+
+impl BanList {
+    pub fn load() -> Self {
+        let path = &CONFIG.ban_store_file;
+        let persisted: Persisted = match std::fs::read_to_string(path) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+                warn!(path = %path, error = %e, "ban store unreadable, starting empty");
+                Persisted::default()
+            }),
+            Err(_) => Persisted::default(),
+        };
+        Self {
+            bans: persisted.bans,
+            strikes: persisted.strikes,
+            violations: HashMap::new(),
+        }
+    }
+
+    pub fn save(&self) {
+        let persisted = Persisted {
+            bans: self.bans.clone(),
+            strikes: self.strikes.clone(),
+        };
+        let path = &CONFIG.ban_store_file;
+        let tmp = format!("{path}.tmp");
+        let result = serde_json::to_vec_pretty(&persisted)
+            .map_err(std::io::Error::other)
+            .and_then(|bytes| std::fs::write(&tmp, bytes))
+            .and_then(|_| std::fs::rename(&tmp, path));
+        if let Err(e) = result {
+            warn!(path = %path, error = %e, "Failed to persist bans");
+        }
+    }
+
+    fn prune(&mut self, now: i64, ttl: i64) {
+        self.bans.retain(|_, ban| ban.until > now);
+        self.strikes.retain(|_, s| now - s.last_until < ttl);
+    }
+
+    pub fn active(&mut self, ip: &str, now: i64) -> Option<&Ban> {
+        if self.bans.get(ip).is_some_and(|ban| ban.until <= now) {
+            self.bans.remove(ip);
+        }
+        self.bans.get(ip)
+    }
+
+    /// Counts a violation and returns the new ban if it tipped the IP over
+    /// the threshold.
+    pub fn record(&mut self, ip: &str, violation: Violation, now: i64) -> Option<Ban> {
+        if !CONFIG.use_bans {
+            return None;
+        }
+        self.record_under(&policy(), ip, violation, now)
+    }
+
+    fn record_under(&mut self, policy: &Policy, ip: &str, violation: Violation, now: i64) -> Option<Ban> {
+        if policy.durations.is_empty() {
+            return None;
+        }
+        self.prune(now, policy.strike_ttl);
+        if self.bans.contains_key(ip) {
+            return None;
+        }
+
+        let hits = self.violations.entry(ip.to_string()).or_default();
+        hits.retain(|&t| now - t < policy.window);
+        hits.push(now);
+        if hits.len() < policy.violations {
+            return None;
+        }
+        self.violations.remove(ip);
+        self.ban_under(policy, ip, violation.as_str(), now)
+    }
+
+    /// Bans `ip` at its next escalation level right away, e.g. on a
+    /// moderator's decision; `None` if `ban_durations` is empty.
+    pub fn ban(&mut self, ip: &str, reason: &str, now: i64) -> Option<Ban> {
+        self.ban_under(&policy(), ip, reason, now)
+    }
+
+    fn ban_under(&mut self, policy: &Policy, ip: &str, reason: &str, now: i64) -> Option<Ban> {
+        let last = policy.durations.len().checked_sub(1)?;
+        self.prune(now, policy.strike_ttl);
+        let strikes = self.strikes.entry(ip.to_string()).or_insert(Strikes {
+            count: 0,
+            last_until: now,
+        });
+        let level = (strikes.count as usize).min(last);
+        let until = now + policy.durations[level] as i64;
+        strikes.count += 1;
+        strikes.last_until = until;
+
+        let ban = Ban {
+            reason: reason.into(),
+            strike: strikes.count,
+            banned_at: now,
+            until,
+        };
+        self.bans.insert(ip.to_string(), ban.clone());
+        Some(ban)
+    }
+
+    pub fn list(&mut self, now: i64) -> Vec<(String, Ban)> {
+        self.prune(now, policy().strike_ttl);
+        let mut bans: Vec<(String, Ban)> = self
+            .bans
+            .iter()
+            .map(|(ip, ban)| (ip.clone(), ban.clone()))
+            .collect();
+        bans.sort_by_key(|(_, ban)| ban.until);
+        bans
+    }
+
+    /// Lifts the ban and forgets the IP's strikes; `false` if it wasn't banned.
+    pub fn lift(&mut self, ip: &str) -> bool {
+        self.strikes.remove(ip);
+        self.violations.remove(ip);
+        self.bans.remove(ip).is_some()
+    }
+}
+
+/// Records `violation` for `ip` and persists the ban list if it led to a ban.
+pub async fn record_violation(state: &AppState, ip: &str, violation: Violation, now: i64) {
+    let mut bans = state.bans.lock().await;
+    if let Some(ban) = bans.record(ip, violation, now) {
+        warn!(
+            ip,
+            reason = violation.as_str(),
+            strike = ban.strike,
+            seconds = ban.until - now,
+            "IP banned"
+        );
+        bans.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IP: &str = "203.0.113.7";
+    const DURATIONS: [u64; 3] = [60, 600, 3600];
+
+    fn policy() -> Policy<'static> {
+        Policy {
+            violations: 3,
+            window: 100,
+            durations: &DURATIONS,
+            strike_ttl: 1000,
+        }
+    }
+
+    /// Records violations one second apart until one of them bans `IP`.
+    fn offend(bans: &mut BanList, from: i64) -> Ban {
+        (from..from + 3)
+            .find_map(|now| bans.record_under(&policy(), IP, Violation::Spam, now))
+            .expect("three violations within the window ban")
+    }
+
+    #[test]
+    fn violations_outside_the_window_do_not_add_up() {
+        let mut bans = BanList::default();
+        for now in [0, 60, 120, 180] {
+            assert!(bans.record_under(&policy(), IP, Violation::RateLimited, now).is_none());
+        }
+        let ban = bans.record_under(&policy(), IP, Violation::RateLimited, 181).unwrap();
+        assert_eq!((ban.reason.as_str(), ban.strike, ban.until), ("rate_limited", 1, 181 + 60));
+    }
+
+    #[test]
+    fn repeat_offenders_climb_the_durations_and_stay_at_the_last() {
+        let mut bans = BanList::default();
+        let mut now = 0;
+        for (strike, seconds) in [(1, 60), (2, 600), (3, 3600), (4, 3600)] {
+            let ban = offend(&mut bans, now);
+            assert_eq!((ban.strike, ban.until - ban.banned_at), (strike, seconds));
+            // NOTE: further violations while banned are not counted
+            assert!(bans.record_under(&policy(), IP, Violation::Spam, ban.until - 1).is_none());
+            now = ban.until;
+        }
+    }
+
+    #[test]
+    fn bans_expire_and_strikes_are_forgotten_after_the_ttl() {
+        let mut bans = BanList::default();
+        let first = offend(&mut bans, 0);
+        assert!(bans.active(IP, first.until - 1).is_some());
+        assert!(bans.active(IP, first.until).is_none());
+
+        let soon = offend(&mut bans, first.until + 999 - 2);
+        assert_eq!(soon.strike, 2);
+
+        let late = offend(&mut bans, soon.until + 1000);
+        assert_eq!((late.strike, late.until - late.banned_at), (1, 60));
+    }
+
+    #[test]
+    fn lifting_resets_the_escalation() {
+        let mut bans = BanList::default();
+        offend(&mut bans, 0);
+        assert!(bans.lift(IP));
+        assert!(!bans.lift(IP));
+        assert_eq!(offend(&mut bans, 10).strike, 1);
+    }
+
+    #[test]
+    fn no_durations_means_no_bans() {
+        let mut bans = BanList::default();
+        let policy = Policy { durations: &[], ..policy() };
+        assert!((0..10).all(|now| bans.record_under(&policy, IP, Violation::Spam, now).is_none()));
+        assert!(bans.ban_under(&policy, IP, "moderator", 0).is_none());
+    }
+}
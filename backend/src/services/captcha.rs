@@ -0,0 +1,240 @@
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::config::CONFIG;
+use crate::types::captcha::CaptchaProvider;
+use crate::types::ErrorCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptchaError {
+    Missing,
+    Failed(Vec<String>),
+    LowScore(f64),
+    /// The siteverify endpoint could not be reached or answered garbage.
+    Unavailable(String),
+}
+
+impl CaptchaError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::Missing => ErrorCode::CaptchaMissing,
+            Self::Failed(_) => ErrorCode::CaptchaFailed,
+            Self::LowScore(_) => ErrorCode::CaptchaScoreTooLow,
+            Self::Unavailable(_) => ErrorCode::CaptchaUnavailable,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::Missing => "captcha_token is required".into(),
+            Self::Failed(codes) if codes.is_empty() => "captcha verification failed".into(),
+            Self::Failed(codes) => format!("captcha verification failed: {}", codes.join(", ")),
+            Self::LowScore(_) => "captcha score is below the accepted threshold".into(),
+            Self::Unavailable(_) => "captcha verification is temporarily unavailable".into(),
+        }
+    }
+}
+
+/// Union of the siteverify answers: Turnstile, hCaptcha and reCAPTCHA send
+/// `success`, SmartCaptcha sends `status`.
+#[derive(Debug, Deserialize)]
+struct SiteverifyResponse {
+    #[serde(default)]
+    success: bool,
+    status: Option<String>,
+    score: Option<f64>,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+    message: Option<String>,
+}
+
+pub struct CaptchaCheck<'a> {
+    pub provider: CaptchaProvider,
+    pub verify_url: &'a str,
+    pub secret: &'a str,
+    /// Minimum human-likeness in 0.0..=1.0.
+    pub min_score: f64,
+}
+
+impl CaptchaCheck<'_> {
+    pub async fn verify(
+        &self,
+        client: &reqwest::Client,
+        token: Option<&str>,
+        ip: &str,
+    ) -> Result<(), CaptchaError> {
+        let token = token
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or(CaptchaError::Missing)?;
+
+        let form: [(&str, &str); 3] = match self.provider {
+            CaptchaProvider::Smartcaptcha => [("secret", self.secret), ("token", token), ("ip", ip)],
+            _ => [("secret", self.secret), ("response", token), ("remoteip", ip)],
+        };
+
+        let answer: SiteverifyResponse = client
+            .post(self.verify_url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| CaptchaError::Unavailable(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| CaptchaError::Unavailable(e.to_string()))?;
+
+        let passed = match self.provider {
+            CaptchaProvider::Smartcaptcha => answer.status.as_deref() == Some("ok"),
+            _ => answer.success,
+        };
+        if !passed {
+            let mut codes = answer.error_codes;
+            codes.extend(answer.message);
+            return Err(CaptchaError::Failed(codes));
+        }
+
+        // NOTE: reCAPTCHA v3 scores humans high, hCaptcha Enterprise scores risk
+        let human = match (self.provider, answer.score) {
+            (CaptchaProvider::Recaptcha, Some(score)) => Some(score),
+            (CaptchaProvider::Hcaptcha, Some(score)) => Some(1.0 - score),
+            _ => None,
+        };
+        match human {
+            Some(h) if h < self.min_score => Err(CaptchaError::LowScore(h)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Verifies `token` with the configured provider; a no-op when CAPTCHA is
+/// disabled.
+pub async fn verify(client: &reqwest::Client, token: Option<&str>, ip: &str) -> Result<(), CaptchaError> {
+    let Some(provider) = CONFIG.captcha_provider else {
+        return Ok(());
+    };
+    let check = CaptchaCheck {
+        provider,
+        verify_url: CONFIG
+            .captcha_verify_url
+            .as_deref()
+            .unwrap_or(provider.default_verify_url()),
+        secret: CONFIG.captcha_secret.as_deref().unwrap_or_default(),
+        min_score: CONFIG.captcha_min_score,
+    };
+    let result = check.verify(client, token, ip).await;
+    if let Err(CaptchaError::Unavailable(reason)) = &result {
+        warn!(provider = provider.as_str(), %reason, "captcha verification unavailable");
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Form, Json, Router, routing::post};
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+
+    const SECRET: &str = "test-secret";
+
+    /// Local stand-in for the providers' siteverify endpoints. The token
+    /// picks the answer; SmartCaptcha's field names are checked on its route.
+    async fn stand_in() -> String {
+        async fn generic(Form(form): Form<HashMap<String, String>>) -> Json<Value> {
+            assert_eq!(form.get("secret").map(String::as_str), Some(SECRET));
+            assert_eq!(form.get("remoteip").map(String::as_str), Some("203.0.113.7"));
+            Json(match form.get("response").map(String::as_str) {
+                Some("high") => json!({ "success": true, "score": 0.9 }),
+                Some("low") => json!({ "success": true, "score": 0.1 }),
+                Some("no-score") => json!({ "success": true }),
+                _ => json!({ "success": false, "error-codes": ["invalid-input-response"] }),
+            })
+        }
+        async fn smart(Form(form): Form<HashMap<String, String>>) -> Json<Value> {
+            assert_eq!(form.get("secret").map(String::as_str), Some(SECRET));
+            assert_eq!(form.get("ip").map(String::as_str), Some("203.0.113.7"));
+            Json(match form.get("token").map(String::as_str) {
+                Some("human") => json!({ "status": "ok", "message": "" }),
+                _ => json!({ "status": "failed", "message": "Token invalid or expired." }),
+            })
+        }
+
+        let app = Router::new()
+            .route("/siteverify", post(generic))
+            .route("/validate", post(smart))
+            .route("/broken", post(|| async { "not json" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    async fn run(provider: CaptchaProvider, url: &str, token: Option<&str>) -> Result<(), CaptchaError> {
+        CaptchaCheck {
+            provider,
+            verify_url: url,
+            secret: SECRET,
+            min_score: 0.5,
+        }
+        .verify(&reqwest::Client::new(), token, "203.0.113.7")
+        .await
+    }
+
+    #[tokio::test]
+    async fn turnstile_pass_and_fail() {
+        let base = stand_in().await;
+        let url = format!("{base}/siteverify");
+        assert_eq!(run(CaptchaProvider::Turnstile, &url, Some("no-score")).await, Ok(()));
+        assert_eq!(
+            run(CaptchaProvider::Turnstile, &url, Some("forged")).await,
+            Err(CaptchaError::Failed(vec!["invalid-input-response".into()]))
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected_without_a_request() {
+        let result = run(CaptchaProvider::Turnstile, "http://127.0.0.1:9/unused", Some("  ")).await;
+        assert_eq!(result, Err(CaptchaError::Missing));
+    }
+
+    #[tokio::test]
+    async fn recaptcha_score_threshold() {
+        let url = format!("{}/siteverify", stand_in().await);
+        assert_eq!(run(CaptchaProvider::Recaptcha, &url, Some("high")).await, Ok(()));
+        assert_eq!(
+            run(CaptchaProvider::Recaptcha, &url, Some("low")).await,
+            Err(CaptchaError::LowScore(0.1))
+        );
+    }
+
+    #[tokio::test]
+    async fn hcaptcha_score_is_treated_as_risk() {
+        let url = format!("{}/siteverify", stand_in().await);
+        // NOTE: risk 0.9 -> human 0.1, risk 0.1 -> human 0.9
+        assert!(matches!(
+            run(CaptchaProvider::Hcaptcha, &url, Some("high")).await,
+            Err(CaptchaError::LowScore(_))
+        ));
+        assert_eq!(run(CaptchaProvider::Hcaptcha, &url, Some("low")).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn smartcaptcha_uses_status_field() {
+        let url = format!("{}/validate", stand_in().await);
+        assert_eq!(run(CaptchaProvider::Smartcaptcha, &url, Some("human")).await, Ok(()));
+        assert_eq!(
+            run(CaptchaProvider::Smartcaptcha, &url, Some("stale")).await,
+            Err(CaptchaError::Failed(vec!["Token invalid or expired.".into()]))
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_or_garbled_verifier_is_unavailable() {
+        let base = stand_in().await;
+        let broken = run(CaptchaProvider::Turnstile, &format!("{base}/broken"), Some("high")).await;
+        assert!(matches!(broken, Err(CaptchaError::Unavailable(_))));
+        let missing = run(CaptchaProvider::Turnstile, &format!("{base}/nope"), Some("high")).await;
+        assert!(matches!(missing, Err(CaptchaError::Unavailable(_))));
+    }
+}
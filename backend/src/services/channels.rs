@@ -0,0 +1,313 @@
+use futures_util::future::join_all;
+use serde::Deserialize;
+use chrono::Utc;
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::config::{CONFIG, ChannelKind, ChannelSettings, OnFailure};
+use crate::services::{
+    FloodControl,
+    deliver::{deliver, failure_message},
+};
+use crate::state::AppState;
+use crate::utils::template::fill;
+
+const DEFAULT_TEMPLATE: &str = "New submission {submission_id}\nFrom: {email}\nSubject: {subject}\n\n{message}";
+
+/// What a channel is told about a submission.
+#[derive(Debug, Clone)]
+pub struct Notification<'a> {
+    pub submission_id: &'a str,
+    pub subject: &'a str,
+    pub message: &'a str,
+    pub email: Option<&'a str>,
+    pub recipients: &'a [String],
+}
+
+impl Notification<'_> {
+    /// The value of a template placeholder, `None` for unknown names.
+    pub fn placeholder(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "submission_id" => self.submission_id.to_string(),
+            "subject" => self.subject.to_string(),
+            "message" => self.message.to_string(),
+            "email" => self.email.unwrap_or("-").to_string(),
+            "recipients" => self.recipients.join(", "),
+            _ => return None,
+        })
+    }
+
+    /// Fills `{name}` placeholders; braces inside the submitted text are
+    /// never expanded and unknown names stay as written.
+    pub fn render(&self, template: &str) -> String {
+        fill(template, |name| self.placeholder(name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelFailure {
+    pub channel: String,
+    pub error: String,
+    /// The channel is configured with `on_failure = "fail"`.
+    pub required: bool,
+}
+
+#[derive(Deserialize)]
+struct TelegramAnswer {
+    ok: bool,
+    description: Option<String>,
+}
+
+async fn post_json(client: &reqwest::Client, url: &str, body: &serde_json::Value) -> Result<reqwest::Response, String> {
+    let answer = client.post(url).json(body).send().await.map_err(|e| e.to_string())?;
+    if !answer.status().is_success() {
+        return Err(format!("receiver answered {}", answer.status()));
+    }
+    Ok(answer)
+}
+
+/// Sends one notification through `channel`. Email channels count against
+/// the global daily cap of `daily_max` messages and are skipped once it is
+/// reached.
+async fn send(
+    client: &reqwest::Client,
+    flood_control: &Mutex<FloodControl>,
+    daily_max: u32,
+    channel: &ChannelSettings,
+    n: &Notification<'_>,
+) -> Result<(), String> {
+    let text = n.render(channel.template.as_deref().unwrap_or(DEFAULT_TEMPLATE));
+    match &channel.kind {
+        ChannelKind::Email { to } => {
+            let hit = flood_control.lock().await.hit_global_max(to.len(), daily_max, Utc::now().timestamp());
+            if let Err(exceeded) = hit {
+                return Err(format!("skipped: {}", exceeded.message()));
+            }
+            let failures = deliver(to, n.subject, &text, &[]).await;
+            if !failures.is_empty() {
+                return Err(failure_message(&failures, to.len()));
+            }
+        }
+        ChannelKind::Telegram {
+            bot_token,
+            chat_id,
+            base_url,
+        } => {
+            let url = format!("{}/bot{bot_token}/sendMessage", base_url.trim_end_matches('/'));
+            let body = json!({ "chat_id": chat_id, "text": text, "disable_web_page_preview": true });
+            // NOTE: errors come back as 4xx with {"ok":false,"description":...}
+            let answer = client.post(&url).json(&body).send().await.map_err(|e| e.to_string())?;
+            let status = answer.status();
+            let answer: TelegramAnswer = answer
+                .json()
+                .await
+                .map_err(|_| format!("Telegram answered {status}"))?;
+            if !answer.ok {
+                return Err(answer.description.unwrap_or_else(|| format!("Telegram answered {status}")));
+            }
+        }
+        ChannelKind::Slack { webhook_url } => {
+            post_json(client, webhook_url, &json!({ "text": text })).await?;
+        }
+        ChannelKind::Http { url } => {
+            let body = json!({
+                "channel": channel.id,
+                "text": text,
+                "submission_id": n.submission_id,
+                "subject": n.subject,
+                "message": n.message,
+                "email": n.email,
+                "recipients": n.recipients,
+            });
+            post_json(client, url, &body).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Fans the notification out to every channel at once and returns the
+/// ones that failed.
+pub async fn notify(state: &AppState, channels: &[ChannelSettings], n: &Notification<'_>) -> Vec<ChannelFailure> {
+    let daily_max = CONFIG.rate_limit_global_daily_max;
+    notify_within(&state.http, &state.flood_control, daily_max, channels, n).await
+}
+
+async fn notify_within(
+    client: &reqwest::Client,
+    flood_control: &Mutex<FloodControl>,
+    daily_max: u32,
+    channels: &[ChannelSettings],
+    n: &Notification<'_>,
+) -> Vec<ChannelFailure> {
+    let results = join_all(channels.iter().map(|c| send(client, flood_control, daily_max, c, n))).await;
+    channels
+        .iter()
+        .zip(results)
+        .filter_map(|(channel, result)| match result {
+            Ok(()) => {
+                debug!(channel = %channel.id, kind = channel.kind.as_str(), "notification sent");
+                None
+            }
+            Err(error) => {
+                warn!(channel = %channel.id, kind = channel.kind.as_str(), %error, "notification failed");
+                Some(ChannelFailure {
+                    channel: channel.id.clone(),
+                    error,
+                    required: channel.on_failure == OnFailure::Fail,
+                })
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json, Router,
+        extract::Path,
+        http::StatusCode,
+        routing::post,
+    };
+    use serde_json::Value;
+
+    /// Local stand-in for the Telegram Bot API, a Slack/Mattermost incoming
+    /// webhook and a generic JSON receiver.
+    async fn stand_in() -> String {
+        async fn telegram(Path(token): Path<String>, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+            assert_eq!(token, "botT0KEN");
+            if body["chat_id"] == "-100" {
+                assert!(body["text"].as_str().unwrap().contains("Subject: Hello"));
+                (StatusCode::OK, Json(json!({ "ok": true, "result": {} })))
+            } else {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "ok": false, "description": "Bad Request: chat not found" })),
+                )
+            }
+        }
+        async fn slack(Json(body): Json<Value>) -> &'static str {
+            assert_eq!(body["text"], "lead 42: Hello");
+            "ok"
+        }
+        async fn http(Json(body): Json<Value>) -> StatusCode {
+            assert_eq!(body["channel"], "crm");
+            assert_eq!(body["submission_id"], "42");
+            assert_eq!(body["recipients"], json!(["team@example.com"]));
+            StatusCode::CREATED
+        }
+
+        let app = Router::new()
+            .route("/{token}/sendMessage", post(telegram))
+            .route("/slack", post(slack))
+            .route("/http", post(http))
+            .route("/down", post(|| async { StatusCode::SERVICE_UNAVAILABLE }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn channel(id: &str, kind: ChannelKind, template: Option<&str>, on_failure: OnFailure) -> ChannelSettings {
+        ChannelSettings {
+            id: id.into(),
+            kind,
+            template: template.map(str::to_string),
+            on_failure,
+        }
+    }
+
+    fn telegram(base: &str, chat_id: &str) -> ChannelKind {
+        ChannelKind::Telegram {
+            bot_token: "T0KEN".into(),
+            chat_id: chat_id.into(),
+            base_url: base.into(),
+        }
+    }
+
+    const RECIPIENTS: [String; 0] = [];
+
+    fn notification(recipients: &[String]) -> Notification<'_> {
+        Notification {
+            submission_id: "42",
+            subject: "Hello",
+            message: "Call me {email}",
+            email: Some("client@example.com"),
+            recipients,
+        }
+    }
+
+    async fn notify_unlimited(channels: &[ChannelSettings], n: &Notification<'_>) -> Vec<ChannelFailure> {
+        notify_within(&reqwest::Client::new(), &Mutex::default(), 0, channels, n).await
+    }
+
+    #[test]
+    fn render_fills_placeholders_once() {
+        let n = notification(&RECIPIENTS);
+        assert_eq!(
+            n.render("{subject} from {email}: {message} {unknown} {"),
+            "Hello from client@example.com: Call me {email} {unknown} {"
+        );
+    }
+
+    #[tokio::test]
+    async fn every_channel_kind_reaches_its_receiver() {
+        let base = stand_in().await;
+        let recipients = vec!["team@example.com".to_string()];
+        let channels = [
+            channel("tg", telegram(&base, "-100"), None, OnFailure::Fail),
+            channel(
+                "chat",
+                ChannelKind::Slack { webhook_url: format!("{base}/slack") },
+                Some("lead {submission_id}: {subject}"),
+                OnFailure::Fail,
+            ),
+            channel("crm", ChannelKind::Http { url: format!("{base}/http") }, None, OnFailure::Fail),
+        ];
+        let failures = notify_unlimited(&channels, &notification(&recipients)).await;
+        assert_eq!(failures, []);
+    }
+
+    #[tokio::test]
+    async fn failures_carry_the_reason_and_policy() {
+        let base = stand_in().await;
+        let channels = [
+            channel("tg", telegram(&base, "404"), None, OnFailure::Log),
+            channel("hook", ChannelKind::Http { url: format!("{base}/down") }, None, OnFailure::Fail),
+        ];
+        let failures = notify_unlimited(&channels, &notification(&RECIPIENTS)).await;
+        assert_eq!(
+            failures,
+            [
+                ChannelFailure {
+                    channel: "tg".into(),
+                    error: "Bad Request: chat not found".into(),
+                    required: false,
+                },
+                ChannelFailure {
+                    channel: "hook".into(),
+                    error: "receiver answered 503 Service Unavailable".into(),
+                    required: true,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn email_channels_stop_at_the_global_cap() {
+        let email = ChannelKind::Email {
+            to: vec!["ops@example.com".into(), "sales@example.com".into()],
+        };
+        let channels = [channel("mail", email, None, OnFailure::Log)];
+        let flood_control = Mutex::new(FloodControl::default());
+        let now = Utc::now().timestamp();
+        assert!(flood_control.lock().await.hit_global_max(9, 10, now).is_ok());
+
+        let n = notification(&RECIPIENTS);
+        let failures = notify_within(&reqwest::Client::new(), &flood_control, 10, &channels, &n).await;
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].error.starts_with("skipped: Quota 'global_daily' exceeded"), "{}", failures[0].error);
+        assert_eq!(flood_control.lock().await.global.len(), 9);
+    }
+}
@@ -0,0 +1,317 @@
+use axum::http::StatusCode;
+use serde_json::{Map, Value, json};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, CrmStyle};
+use crate::services::{channels::Notification, submissions::SubmissionLog};
+use crate::state::AppState;
+use crate::utils::template::fill;
+
+// NOTE: recipient column of the delivery failures an export leaves behind
+pub const FAILURE_RECIPIENT: &str = "crm";
+
+/// What a lead template can draw from: the notification placeholders, the
+/// sender's `{ip}` and any other submitted form field by name.
+pub struct LeadSource<'a> {
+    pub notification: &'a Notification<'a>,
+    pub ip: &'a str,
+    pub extra: &'a HashMap<String, Value>,
+}
+
+impl LeadSource<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        match name {
+            "email" => self.notification.email.map(str::to_string),
+            "ip" => Some(self.ip.to_string()),
+            _ => self.notification.placeholder(name).or_else(|| match self.extra.get(name)? {
+                Value::String(s) if !s.trim().is_empty() => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                Value::Bool(b) => Some(b.to_string()),
+                _ => None,
+            }),
+        }
+    }
+
+    /// A string that is a single placeholder becomes `null` when the value
+    /// is missing, so the field can be left out; placeholders inside longer
+    /// text become empty.
+    fn render(&self, template: &Value) -> Value {
+        match template {
+            Value::String(s) => {
+                if let Some(name) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}'))
+                    && !name.contains(['{', '}'])
+                {
+                    return self.lookup(name).map_or(Value::Null, Value::String);
+                }
+                Value::String(fill(s, |name| Some(self.lookup(name).unwrap_or_default())))
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|v| self.render(v))
+                    .filter(|v| !v.is_null())
+                    .collect(),
+            ),
+            Value::Object(fields) => Value::Object(self.lead(fields)),
+            other => other.clone(),
+        }
+    }
+
+    /// The configured lead object with the placeholders filled and the
+    /// fields that came out empty removed.
+    pub fn lead(&self, template: &Map<String, Value>) -> Map<String, Value> {
+        template
+            .iter()
+            .map(|(k, v)| (k.clone(), self.render(v)))
+            .filter(|(_, v)| !v.is_null())
+            .collect()
+    }
+}
+
+/// The request body each CRM expects around a lead.
+pub fn request_body(style: CrmStyle, lead: Map<String, Value>) -> Value {
+    match style {
+        CrmStyle::Bitrix24 => json!({ "fields": lead, "params": { "REGISTER_SONET_EVENT": "Y" } }),
+        CrmStyle::AmoCrm => json!([lead]),
+        CrmStyle::Generic => Value::Object(lead),
+    }
+}
+
+pub struct CrmExport<'a> {
+    pub url: &'a str,
+    pub token: Option<&'a str>,
+    pub lead_id_pointer: &'a str,
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubles after each failure.
+    pub retry_base: Duration,
+}
+
+impl CrmExport<'_> {
+    /// Posts `body` until the CRM creates the lead and returns its id.
+    /// Connection errors, 429 and 5xx are retried; any other answer is final,
+    /// since repeating it could create the lead twice or fail the same way.
+    pub async fn run(&self, client: &reqwest::Client, body: &Value) -> Result<String, String> {
+        let mut wait = self.retry_base;
+        let mut last_error = String::new();
+        for n in 1..=self.max_attempts {
+            let mut request = client.post(self.url).json(body);
+            if let Some(token) = self.token {
+                request = request.bearer_auth(token);
+            }
+            match request.send().await {
+                Ok(answer) if answer.status().is_success() => {
+                    let answer: Value = answer
+                        .json()
+                        .await
+                        .map_err(|e| format!("CRM answer is not JSON: {e}"))?;
+                    return lead_id(&answer, self.lead_id_pointer)
+                        .ok_or_else(|| format!("no lead id at {} in the CRM answer", self.lead_id_pointer));
+                }
+                Ok(answer) => {
+                    let status = answer.status();
+                    let detail = answer.text().await.unwrap_or_default();
+                    let detail: String = detail.chars().take(200).collect();
+                    last_error = format!("CRM answered {status}: {detail}");
+                    if !(status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS) {
+                        return Err(last_error);
+                    }
+                }
+                Err(e) => last_error = e.to_string(),
+            }
+            warn!(attempt = n, error = %last_error, "CRM export attempt failed");
+            if n < self.max_attempts {
+                tokio::time::sleep(wait).await;
+                wait = wait.saturating_mul(2);
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// The id at `pointer`, whether the CRM sends it as a number or a string.
+pub fn lead_id(answer: &Value, pointer: &str) -> Option<String> {
+    match answer.pointer(pointer)? {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Turns the submission into a CRM lead in the background. The lead id, or
+/// the reason there is none, ends up on the submission in the admin API.
+pub fn export(state: &AppState, submission_id: &str, source: &LeadSource<'_>) {
+    let Some(crm) = &CONFIG.crm else {
+        return;
+    };
+    let body = request_body(crm.style, source.lead(&crm.lead));
+    let client = state.http.clone();
+    let submissions: Arc<Mutex<SubmissionLog>> = state.submissions.clone();
+    let id = submission_id.to_string();
+    tokio::spawn(async move {
+        let export = CrmExport {
+            url: &crm.url,
+            token: crm.token.as_deref(),
+            lead_id_pointer: crm.lead_id_pointer(),
+            max_attempts: crm.max_attempts,
+            retry_base: Duration::from_secs(crm.retry_base_seconds as u64),
+        };
+        let result = export.run(&client, &body).await;
+        let mut submissions = submissions.lock().await;
+        match result {
+            Ok(lead) => {
+                info!(submission = %id, lead = %lead, crm = crm.style.as_str(), "lead exported");
+                submissions.set_crm_lead(&id, lead);
+            }
+            Err(error) => {
+                warn!(submission = %id, crm = crm.style.as_str(), %error, "lead export failed");
+                let now = chrono::Utc::now().timestamp();
+                submissions.record_failures(Some(&id), &[(FAILURE_RECIPIENT.to_string(), error)], now);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json, Router,
+        extract::State,
+        http::{HeaderMap, header::AUTHORIZATION},
+        routing::post,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const RECIPIENTS: [String; 0] = [];
+
+    fn notification(email: Option<&'static str>) -> Notification<'static> {
+        Notification {
+            submission_id: "42",
+            subject: "Pricing",
+            message: "Call me {back}",
+            email,
+            recipients: &RECIPIENTS,
+        }
+    }
+
+    fn template() -> Map<String, Value> {
+        json!({
+            "name": "Website: {subject}",
+            "price": 0,
+            "email": "{email}",
+            "phone": "{phone}",
+            "comment": "{message} ({ip}, {company})",
+            "custom_fields_values": [
+                { "field_code": "PHONE", "values": [{ "value": "{phone}" }] }
+            ],
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    #[test]
+    fn lead_fills_fields_and_drops_missing_ones() {
+        let n = notification(None);
+        let extra = HashMap::from([("phone".to_string(), json!("+7 900 000-00-00"))]);
+        let source = LeadSource {
+            notification: &n,
+            ip: "203.0.113.7",
+            extra: &extra,
+        };
+        assert_eq!(
+            Value::Object(source.lead(&template())),
+            json!({
+                "name": "Website: Pricing",
+                "price": 0,
+                "phone": "+7 900 000-00-00",
+                "comment": "Call me {back} (203.0.113.7, )",
+                "custom_fields_values": [
+                    { "field_code": "PHONE", "values": [{ "value": "+7 900 000-00-00" }] }
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn body_and_id_follow_the_crm_style() {
+        let lead = json!({ "TITLE": "x" }).as_object().cloned().unwrap();
+        assert_eq!(request_body(CrmStyle::AmoCrm, lead.clone()), json!([{ "TITLE": "x" }]));
+        assert_eq!(request_body(CrmStyle::Bitrix24, lead)["fields"]["TITLE"], "x");
+
+        let amo = json!({ "_embedded": { "leads": [{ "id": 1234567 }] } });
+        assert_eq!(lead_id(&amo, CrmStyle::AmoCrm.lead_id_pointer()).as_deref(), Some("1234567"));
+        assert_eq!(lead_id(&json!({ "result": 89 }), CrmStyle::Bitrix24.lead_id_pointer()).as_deref(), Some("89"));
+        assert_eq!(lead_id(&json!({ "id": "L-7" }), CrmStyle::Generic.lead_id_pointer()).as_deref(), Some("L-7"));
+        assert_eq!(lead_id(&json!({ "result": null }), "/result"), None);
+    }
+
+    /// Local stand-in for the CRM: answers 503 to the first `fail_first`
+    /// requests, then creates lead 1001, or 400 for a lead without a name.
+    async fn stand_in(fail_first: usize) -> (String, Arc<AtomicUsize>) {
+        async fn create(
+            State((fail_first, hits)): State<(usize, Arc<AtomicUsize>)>,
+            headers: HeaderMap,
+            Json(body): Json<Value>,
+        ) -> (StatusCode, Json<Value>) {
+            assert_eq!(headers[AUTHORIZATION], "Bearer amo-token");
+            if hits.fetch_add(1, Ordering::SeqCst) < fail_first {
+                return (StatusCode::SERVICE_UNAVAILABLE, Json(json!({})));
+            }
+            if body[0]["name"].is_null() {
+                return (StatusCode::BAD_REQUEST, Json(json!({ "title": "name is required" })));
+            }
+            (
+                StatusCode::OK,
+                Json(json!({ "_embedded": { "leads": [{ "id": 1001, "request_id": "0" }] } })),
+            )
+        }
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/api/v4/leads", post(create))
+            .with_state((fail_first, hits.clone()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/api/v4/leads"), hits)
+    }
+
+    async fn run(url: &str, body: Value) -> Result<String, String> {
+        CrmExport {
+            url,
+            token: Some("amo-token"),
+            lead_id_pointer: CrmStyle::AmoCrm.lead_id_pointer(),
+            max_attempts: 3,
+            retry_base: Duration::from_millis(10),
+        }
+        .run(&reqwest::Client::new(), &body)
+        .await
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_lead_is_created() {
+        let (url, hits) = stand_in(2).await;
+        assert_eq!(run(&url, json!([{ "name": "x" }])).await.as_deref(), Ok("1001"));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let (url, hits) = stand_in(0).await;
+        let error = run(&url, json!([{}])).await.unwrap_err();
+        assert!(error.starts_with("CRM answered 400 Bad Request"), "{error}");
+        assert!(error.contains("name is required"));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (url, hits) = stand_in(usize::MAX).await;
+        let error = run(&url, json!([{ "name": "x" }])).await.unwrap_err();
+        assert!(error.starts_with("CRM answered 503"), "{error}");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,68 @@
+use tracing::{debug, warn};
+
+use crate::services::send_email;
+use crate::utils::mask_string::mask_email;
+
+/// Mail header carrying the `X-Request-Id` of the submission.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Sends the message to every recipient in parallel on the blocking pool and
+/// returns the `(recipient, error)` pairs that failed.
+pub async fn deliver(
+    recipients: &[String],
+    subject: &str,
+    message: &str,
+    extra_headers: &[(&'static str, String)],
+) -> Vec<(String, String)> {
+    let mut failures: Vec<(String, String)> = Vec::new();
+    let mut handles = Vec::with_capacity(recipients.len());
+
+    for r in recipients {
+        debug!(recipient = mask_email(r), "sending email");
+        let r = r.clone();
+        let subject = subject.to_string();
+        let message = message.to_string();
+        let extra_headers = extra_headers.to_vec();
+
+        handles.push(tokio::task::spawn_blocking(move || {
+            (r.clone(), send_email(&r, &subject, &message, &extra_headers))
+        }));
+    }
+
+    for h in handles {
+        match h.await {
+            Ok((rcpt, res)) => match res {
+                Ok(_) => debug!(recipient = mask_email(&rcpt), "email sent"),
+                Err(e) => {
+                    warn!(recipient = mask_email(&rcpt), error=%e, "send failed");
+                    failures.push((rcpt, e.to_string()));
+                }
+            },
+            Err(join_err) => {
+                warn!(error=%join_err, "send task join failed");
+            }
+        }
+    }
+
+    failures
+}
+
+/// Summary for an `EmailSendFailed` answer, listing at most three errors.
+pub fn failure_message(failures: &[(String, String)], total: usize) -> String {
+    let failed_count = failures.len();
+    let detail = failures
+        .iter()
+        .take(3)
+        .map(|(addr, err)| format!("{addr}: {err}"))
+        .collect::<Vec<_>>()
+        .join("; ");
+    format!(
+        "Failed to send {failed_count} of {total} email(s). {}{}",
+        if failed_count > 3 {
+            "Some errors omitted. "
+        } else {
+            ""
+        },
+        detail
+    )
+}
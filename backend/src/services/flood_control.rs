@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+
+use crate::config::{ApiKeySettings, CONFIG};
+use crate::types::ErrorCode;
+
+const DAY_SECONDS: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    Ip,
+    Email,
+    Recipient,
+    GlobalDaily,
+    ApiKey,
+}
+
+impl QuotaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ip => "ip",
+            Self::Email => "email",
+            Self::Recipient => "recipient",
+            Self::GlobalDaily => "global_daily",
+            Self::ApiKey => "api_key",
+        }
+    }
+
+    pub fn error_code(self) -> ErrorCode {
+        match self {
+            Self::Ip => ErrorCode::IpQuotaExceeded,
+            Self::Email => ErrorCode::EmailQuotaExceeded,
+            Self::Recipient => ErrorCode::RecipientQuotaExceeded,
+            Self::GlobalDaily => ErrorCode::DailyQuotaExceeded,
+            Self::ApiKey => ErrorCode::ApiKeyQuotaExceeded,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaExceeded {
+    pub kind: QuotaKind,
+    pub limit: u32,
+    pub window: i64,
+    /// Seconds until the oldest counted hit leaves the window; `None` when
+    /// the request alone needs more than `limit`, so waiting cannot help.
+    pub retry_after: Option<i64>,
+    /// Hits the refused request would have added, e.g. one per recipient.
+    pub requested: usize,
+}
+
+impl QuotaExceeded {
+    pub fn message(&self) -> String {
+        let scope = match self.kind {
+            QuotaKind::Ip => "requests from your address",
+            QuotaKind::Email => "requests from this email",
+            QuotaKind::Recipient => "messages to this recipient",
+            QuotaKind::GlobalDaily => "messages the service may send",
+            QuotaKind::ApiKey => "requests with this API key",
+        };
+        let advice = match self.retry_after {
+            Some(seconds) => format!("Please try again in {seconds}s."),
+            None => format!("This request alone needs {} messages and can never fit.", self.requested),
+        };
+        format!(
+            "Quota '{}' exceeded: at most {} {} per {}s. {advice}",
+            self.kind.as_str(),
+            self.limit,
+            scope,
+            self.window,
+        )
+    }
+}
+
+/// Snapshot of the quota a successful hit was counted against, used for the
+/// `RateLimit-*` response headers.
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaStatus {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the oldest counted hit leaves the window.
+    pub reset: i64,
+}
+
+/// Sliding-window counters for every quota layer. Each map holds the
+/// timestamps of the hits still inside its window.
+#[derive(Debug, Default)]
+pub struct FloodControl {
+    pub ip: HashMap<String, Vec<i64>>,
+    pub email: HashMap<String, Vec<i64>>,
+    pub recipient: HashMap<String, Vec<i64>>,
+    pub global: Vec<i64>,
+    // NOTE: one entry per configured API key, each with its own window
+    pub api_key: HashMap<String, Vec<i64>>,
+}
+
+/// Hits a key currently has on one quota layer.
+#[derive(Debug, Clone)]
+pub struct KeyUsage {
+    pub key: String,
+    pub hits: usize,
+    /// `0` means the quota is disabled.
+    pub limit: u32,
+    pub window: i64,
+    /// Seconds until the oldest counted hit leaves the window.
+    pub reset: i64,
+}
+
+fn usage(key: &str, hits: &[i64], limit: u32, window: i64, now: i64) -> Option<KeyUsage> {
+    let live: Vec<i64> = hits.iter().copied().filter(|&t| now - t < window).collect();
+    let oldest = live.iter().min()?;
+    Some(KeyUsage {
+        key: key.to_string(),
+        hits: live.len(),
+        limit,
+        window,
+        reset: (oldest + window - now).max(1),
+    })
+}
+
+fn prune(hits: &mut Vec<i64>, window: i64, now: i64) {
+    hits.retain(|&t| now - t < window);
+}
+
+/// `max == 0` disables the quota.
+fn check(
+    hits: &[i64],
+    incoming: usize,
+    kind: QuotaKind,
+    max: u32,
+    window: i64,
+    now: i64,
+) -> Result<(), QuotaExceeded> {
+    if max == 0 || hits.len() + incoming <= max as usize {
+        return Ok(());
+    }
+    let oldest = hits.iter().min().copied().unwrap_or(now);
+    Err(QuotaExceeded {
+        kind,
+        limit: max,
+        window,
+        retry_after: (incoming <= max as usize).then(|| (oldest + window - now).max(1)),
+        requested: incoming,
+    })
+}
+
+/// The anonymous quotas from `CONFIG`, taken apart so tests can pick their own.
+struct Limits {
+    window: i64,
+    ip: u32,
+    email: u32,
+    recipient: u32,
+    global: u32,
+}
+
+fn limits() -> Limits {
+    Limits {
+        window: CONFIG.rate_limit_timeframe_seconds as i64,
+        ip: CONFIG.rate_limit_max,
+        email: CONFIG.rate_limit_email_max,
+        recipient: CONFIG.rate_limit_recipient_max,
+        global: CONFIG.rate_limit_global_daily_max,
+    }
+}
+
+fn normalize(key: &str) -> String {
+    key.trim().to_ascii_lowercase()
+}
+
+impl FloodControl {
+    /// Counts an attempt from `ip`. Runs before the payload is parsed, so
+    /// malformed requests still consume the caller's quota. Returns `None`
+    /// when the per-IP quota is disabled.
+    pub fn hit_ip(&mut self, ip: &str, now: i64) -> Result<Option<QuotaStatus>, QuotaExceeded> {
+        self.hit_ip_under(&limits(), ip, now)
+    }
+
+    fn hit_ip_under(&mut self, limits: &Limits, ip: &str, now: i64) -> Result<Option<QuotaStatus>, QuotaExceeded> {
+        let (window, max) = (limits.window, limits.ip);
+        self.sweep(window, now);
+        let hits = self.ip.entry(ip.to_string()).or_default();
+        check(hits, 1, QuotaKind::Ip, max, window, now)?;
+        hits.push(now);
+        if max == 0 {
+            return Ok(None);
+        }
+        let oldest = hits.iter().min().copied().unwrap_or(now);
+        Ok(Some(QuotaStatus {
+            limit: max,
+            remaining: max.saturating_sub(hits.len() as u32),
+            reset: (oldest + window - now).max(1),
+        }))
+    }
+
+    /// Counts a request authenticated with an API key against that key's
+    /// own quota, which replaces the anonymous quotas.
+    pub fn hit_api_key(&mut self, key: &ApiKeySettings, now: i64) -> Result<Option<QuotaStatus>, QuotaExceeded> {
+        let window = key.rate_limit_timeframe_seconds as i64;
+        let max = key.rate_limit_max;
+        let hits = self.api_key.entry(key.id.clone()).or_default();
+        prune(hits, window, now);
+        check(hits, 1, QuotaKind::ApiKey, max, window, now)?;
+        hits.push(now);
+        if max == 0 {
+            return Ok(None);
+        }
+        let oldest = hits.iter().min().copied().unwrap_or(now);
+        Ok(Some(QuotaStatus {
+            limit: max,
+            remaining: max.saturating_sub(hits.len() as u32),
+            reset: (oldest + window - now).max(1),
+        }))
+    }
+
+    /// Hits currently counted for `ip`, without recording a new one.
+    pub fn ip_hits(&self, ip: &str, now: i64) -> usize {
+        let window = CONFIG.rate_limit_timeframe_seconds as i64;
+        self.ip
+            .get(ip)
+            .map_or(0, |hits| hits.iter().filter(|&&t| now - t < window).count())
+    }
+
+    /// Checks the submitter, recipient and global quotas together and only
+    /// records the submission when none of them is exceeded.
+    pub fn hit_submission(
+        &mut self,
+        email: Option<&str>,
+        recipients: &[String],
+        now: i64,
+    ) -> Result<(), QuotaExceeded> {
+        self.hit_submission_under(&limits(), email, recipients, now)
+    }
+
+    fn hit_submission_under(
+        &mut self,
+        limits: &Limits,
+        email: Option<&str>,
+        recipients: &[String],
+        now: i64,
+    ) -> Result<(), QuotaExceeded> {
+        let window = limits.window;
+        let email = email.map(normalize);
+        let recipients: Vec<String> = recipients.iter().map(|r| normalize(r)).collect();
+
+        if let Some(email) = &email {
+            let hits = self.email.entry(email.clone()).or_default();
+            prune(hits, window, now);
+            check(hits, 1, QuotaKind::Email, limits.email, window, now)?;
+        }
+        for r in &recipients {
+            let hits = self.recipient.entry(r.clone()).or_default();
+            prune(hits, window, now);
+            check(hits, 1, QuotaKind::Recipient, limits.recipient, window, now)?;
+        }
+        prune(&mut self.global, DAY_SECONDS, now);
+        check(
+            &self.global,
+            recipients.len(),
+            QuotaKind::GlobalDaily,
+            limits.global,
+            DAY_SECONDS,
+            now,
+        )?;
+
+        if let Some(email) = email {
+            self.email.entry(email).or_default().push(now);
+        }
+        for r in recipients {
+            self.recipient.entry(r).or_default().push(now);
+            self.global.push(now);
+        }
+        Ok(())
+    }
+
+    /// Counts `messages` outgoing emails against the service-wide daily cap.
+    /// Mail that skips `hit_submission` still goes through here: API keys,
+    /// inbound adapters, allowlisted IPs, approvals, auto-replies, email
+    /// channels and quarantine digests.
+    pub fn hit_global(&mut self, messages: usize, now: i64) -> Result<(), QuotaExceeded> {
+        self.hit_global_max(messages, CONFIG.rate_limit_global_daily_max, now)
+    }
+
+    /// `hit_global` against an explicit cap of `max` messages.
+    pub fn hit_global_max(&mut self, messages: usize, max: u32, now: i64) -> Result<(), QuotaExceeded> {
+        prune(&mut self.global, DAY_SECONDS, now);
+        check(&self.global, messages, QuotaKind::GlobalDaily, max, DAY_SECONDS, now)?;
+        self.global.extend(std::iter::repeat_n(now, messages));
+        Ok(())
+    }
+
+    /// Every key with hits inside its window on `kind`, busiest first.
+    pub fn usage(&self, kind: QuotaKind, now: i64) -> Vec<KeyUsage> {
+        let window = CONFIG.rate_limit_timeframe_seconds as i64;
+        let keyed = |map: &HashMap<String, Vec<i64>>, max: u32| -> Vec<KeyUsage> {
+            map.iter()
+                .filter_map(|(key, hits)| usage(key, hits, max, window, now))
+                .collect()
+        };
+        let mut usages = match kind {
+            QuotaKind::Ip => keyed(&self.ip, CONFIG.rate_limit_max),
+            QuotaKind::Email => keyed(&self.email, CONFIG.rate_limit_email_max),
+            QuotaKind::Recipient => keyed(&self.recipient, CONFIG.rate_limit_recipient_max),
+            QuotaKind::ApiKey => CONFIG
+                .api_keys
+                .iter()
+                .filter_map(|k| {
+                    let hits = self.api_key.get(&k.id)?;
+                    usage(&k.id, hits, k.rate_limit_max, k.rate_limit_timeframe_seconds as i64, now)
+                })
+                .collect(),
+            QuotaKind::GlobalDaily => {
+                usage("global", &self.global, CONFIG.rate_limit_global_daily_max, DAY_SECONDS, now)
+                    .into_iter()
+                    .collect()
+            }
+        };
+        usages.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.key.cmp(&b.key)));
+        usages
+    }
+
+    /// Drops keys whose windows have fully expired.
+    fn sweep(&mut self, window: i64, now: i64) {
+        for map in [&mut self.ip, &mut self.email, &mut self.recipient] {
+            map.retain(|_, hits| {
+                prune(hits, window, now);
+                !hits.is_empty()
+            });
+        }
+        prune(&mut self.global, DAY_SECONDS, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_000_000;
+
+    fn limits() -> Limits {
+        Limits {
+            window: 60,
+            ip: 2,
+            email: 2,
+            recipient: 3,
+            global: 5,
+        }
+    }
+
+    fn to(recipients: &[&str]) -> Vec<String> {
+        recipients.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn the_ip_quota_slides_with_its_window() {
+        let mut flood = FloodControl::default();
+        let first = flood.hit_ip_under(&limits(), "203.0.113.7", NOW).unwrap().unwrap();
+        assert_eq!((first.remaining, first.reset), (1, 60));
+        assert!(flood.hit_ip_under(&limits(), "203.0.113.7", NOW + 10).is_ok());
+
+        let exceeded = flood.hit_ip_under(&limits(), "203.0.113.7", NOW + 20).unwrap_err();
+        assert_eq!((exceeded.kind, exceeded.retry_after), (QuotaKind::Ip, Some(40)));
+        assert!(flood.hit_ip_under(&limits(), "198.51.100.1", NOW + 20).is_ok());
+        assert!(flood.hit_ip_under(&limits(), "203.0.113.7", NOW + 60).is_ok());
+
+        let off = Limits { ip: 0, ..limits() };
+        assert!((0..10).all(|_| matches!(flood.hit_ip_under(&off, "203.0.113.7", NOW + 60), Ok(None))));
+    }
+
+    #[test]
+    fn the_email_quota_ignores_case_and_spaces() {
+        let mut flood = FloodControl::default();
+        let team = to(&["team@example.com"]);
+        assert!(flood.hit_submission_under(&limits(), Some("Ann@Example.com"), &team, NOW).is_ok());
+        assert!(flood.hit_submission_under(&limits(), Some(" ann@example.com "), &team, NOW).is_ok());
+        let exceeded = flood.hit_submission_under(&limits(), Some("ANN@example.com"), &[], NOW).unwrap_err();
+        assert_eq!(exceeded.kind, QuotaKind::Email);
+        assert!(flood.hit_submission_under(&limits(), None, &[], NOW).is_ok());
+    }
+
+    #[test]
+    fn the_recipient_quota_counts_every_sender() {
+        let mut flood = FloodControl::default();
+        for sender in ["a@example.com", "b@example.com", "c@example.com"] {
+            assert!(flood.hit_submission_under(&limits(), Some(sender), &to(&["team@example.com"]), NOW).is_ok());
+        }
+        let exceeded = flood
+            .hit_submission_under(&limits(), Some("d@example.com"), &to(&["Team@example.com"]), NOW)
+            .unwrap_err();
+        assert_eq!(exceeded.kind, QuotaKind::Recipient);
+    }
+
+    #[test]
+    fn a_refused_submission_records_nothing() {
+        let mut flood = FloodControl::default();
+        let team = to(&["team@example.com", "sales@example.com"]);
+        for _ in 0..2 {
+            assert!(flood.hit_submission_under(&limits(), Some("ann@example.com"), &team, NOW).is_ok());
+        }
+        let exceeded = flood.hit_submission_under(&limits(), Some("ann@example.com"), &team, NOW).unwrap_err();
+        assert_eq!(exceeded.kind, QuotaKind::Email);
+        assert_eq!(flood.recipient["team@example.com"].len(), 2);
+        assert_eq!(flood.global.len(), 4);
+
+        // NOTE: the last recipient is over its quota, the first must not be charged either
+        let busy = to(&["new@example.com", "team@example.com"]);
+        let limits = Limits {
+            recipient: 2,
+            ..limits()
+        };
+        assert!(flood.hit_submission_under(&limits, Some("bob@example.com"), &busy, NOW).is_err());
+        assert!(flood.recipient["new@example.com"].is_empty());
+        assert!(flood.email["bob@example.com"].is_empty());
+        assert_eq!(flood.global.len(), 4);
+    }
+
+    #[test]
+    fn every_sender_counts_against_the_global_cap() {
+        let mut flood = FloodControl::default();
+        for sender in ["a@example.com", "b@example.com"] {
+            let team = to(&["team@example.com", "sales@example.com"]);
+            assert!(flood.hit_submission_under(&limits(), Some(sender), &team, NOW).is_ok());
+        }
+        // NOTE: API keys, inbound adapters, approvals and auto-replies go through `hit_global_max`
+        assert!(flood.hit_global_max(1, 5, NOW + 10).is_ok());
+        let exceeded = flood.hit_global_max(1, 5, NOW + 20).unwrap_err();
+        assert_eq!((exceeded.kind, exceeded.retry_after), (QuotaKind::GlobalDaily, Some(DAY_SECONDS - 20)));
+        let team = to(&["team@example.com"]);
+        let exceeded = flood.hit_submission_under(&limits(), None, &team, NOW + 20).unwrap_err();
+        assert_eq!(exceeded.kind, QuotaKind::GlobalDaily);
+        assert!(flood.hit_global_max(1, 5, NOW + DAY_SECONDS).is_ok());
+    }
+
+    #[test]
+    fn more_recipients_than_the_cap_can_never_fit() {
+        let mut flood = FloodControl::default();
+        let exceeded = flood.hit_global_max(6, 5, NOW).unwrap_err();
+        assert_eq!((exceeded.retry_after, exceeded.requested), (None, 6));
+        assert!(!exceeded.message().contains("try again"));
+        assert!(flood.global.is_empty());
+
+        assert!(flood.hit_global_max(5, 5, NOW).is_ok());
+        assert_eq!(flood.hit_global_max(1, 5, NOW).unwrap_err().retry_after, Some(DAY_SECONDS));
+    }
+
+    #[test]
+    fn sweep_drops_keys_whose_window_has_passed() {
+        let mut flood = FloodControl::default();
+        assert!(flood.hit_ip_under(&limits(), "203.0.113.7", NOW).is_ok());
+        let team = to(&["team@example.com"]);
+        assert!(flood.hit_submission_under(&limits(), Some("ann@example.com"), &team, NOW).is_ok());
+        assert!(flood.hit_ip_under(&limits(), "198.51.100.1", NOW + 30).is_ok());
+
+        flood.sweep(60, NOW + 60);
+        assert_eq!(flood.ip.keys().collect::<Vec<_>>(), ["198.51.100.1"]);
+        assert!(flood.email.is_empty() && flood.recipient.is_empty());
+        assert_eq!(flood.global.len(), 1);
+
+        flood.sweep(60, NOW + DAY_SECONDS);
+        assert!(flood.ip.is_empty() && flood.global.is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::config::CONFIG;
+use crate::services::nonce_store::NonceStore;
+use crate::types::ErrorCode;
+use crate::utils::crypto::{base64url_decode, base64url_encode, hmac_sha256, random_hex, verify_hmac_sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormTokenError {
+    Missing,
+    Invalid,
+    Expired,
+    Reused,
+    /// Submitted faster than a human could fill the form.
+    TooFast,
+}
+
+impl FormTokenError {
+    pub fn error_code(self) -> ErrorCode {
+        match self {
+            Self::Missing => ErrorCode::FormTokenMissing,
+            Self::Invalid => ErrorCode::FormTokenInvalid,
+            Self::Expired => ErrorCode::FormTokenExpired,
+            Self::Reused => ErrorCode::FormTokenReused,
+            Self::TooFast => ErrorCode::FormTokenTooFast,
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Missing => "form_token is required",
+            Self::Invalid => "form_token is invalid",
+            Self::Expired => "form_token has expired, reload the form",
+            Self::Reused => "form_token has already been used",
+            Self::TooFast => "form was submitted too quickly",
+        }
+    }
+}
+
+/// Issues `<issued_at>.<nonce>.<signature>`; `None` when no secret is
+/// configured.
+pub fn issue(now: i64) -> Option<String> {
+    Some(sign(CONFIG.form_token_secret.as_deref()?, now))
+}
+
+fn sign(secret: &str, now: i64) -> String {
+    let body = format!("{now}.{}", random_hex(16));
+    let signature = hmac_sha256(secret.as_bytes(), body.as_bytes());
+    format!("{body}.{}", base64url_encode(&signature))
+}
+
+/// Checks signature, age and single use. The nonce is consumed even when the
+/// form was filled too fast, so a bot cannot simply wait and resubmit.
+pub fn verify(token: Option<&str>, now: i64, seen: &mut NonceStore) -> Result<(), FormTokenError> {
+    let Some(secret) = CONFIG.form_token_secret.as_deref() else {
+        return Ok(());
+    };
+    let max_age = CONFIG.form_token_max_age_seconds as i64;
+    let min_fill = CONFIG.form_token_min_fill_seconds as i64;
+    check(token, secret, max_age, min_fill, now, seen)
+}
+
+fn check(
+    token: Option<&str>,
+    secret: &str,
+    max_age: i64,
+    min_fill: i64,
+    now: i64,
+    seen: &mut NonceStore,
+) -> Result<(), FormTokenError> {
+    let token = token.map(str::trim).filter(|t| !t.is_empty()).ok_or(FormTokenError::Missing)?;
+
+    let (body, signature) = token.rsplit_once('.').ok_or(FormTokenError::Invalid)?;
+    let signature = base64url_decode(signature).ok_or(FormTokenError::Invalid)?;
+    if !verify_hmac_sha256(secret.as_bytes(), body.as_bytes(), &signature) {
+        return Err(FormTokenError::Invalid);
+    }
+    let (issued_at, nonce) = body.split_once('.').ok_or(FormTokenError::Invalid)?;
+    let issued_at: i64 = issued_at.parse().map_err(|_| FormTokenError::Invalid)?;
+
+    let age = now - issued_at;
+    if age < 0 {
+        return Err(FormTokenError::Invalid);
+    }
+    if age > max_age {
+        return Err(FormTokenError::Expired);
+    }
+    if !seen.consume(nonce, issued_at + max_age, now) {
+        return Err(FormTokenError::Reused);
+    }
+    if age < min_fill {
+        return Err(FormTokenError::TooFast);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+    const ISSUED: i64 = 1_000_000;
+
+    fn verify_at(token: &str, now: i64, seen: &mut NonceStore) -> Result<(), FormTokenError> {
+        check(Some(token), SECRET, 3600, 3, now, seen)
+    }
+
+    #[test]
+    fn fresh_token_passes_once() {
+        let token = sign(SECRET, ISSUED);
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&token, ISSUED + 10, &mut seen), Ok(()));
+        assert_eq!(verify_at(&token, ISSUED + 11, &mut seen), Err(FormTokenError::Reused));
+    }
+
+    #[test]
+    fn too_fast_still_burns_the_token() {
+        let token = sign(SECRET, ISSUED);
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&token, ISSUED + 1, &mut seen), Err(FormTokenError::TooFast));
+        assert_eq!(verify_at(&token, ISSUED + 10, &mut seen), Err(FormTokenError::Reused));
+    }
+
+    #[test]
+    fn expired_and_future_tokens_are_refused() {
+        let token = sign(SECRET, ISSUED);
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&token, ISSUED + 3601, &mut seen), Err(FormTokenError::Expired));
+        assert_eq!(verify_at(&token, ISSUED - 1, &mut seen), Err(FormTokenError::Invalid));
+    }
+
+    #[test]
+    fn tampered_or_foreign_tokens_are_invalid() {
+        let token = sign(SECRET, ISSUED);
+        let (body, signature) = token.rsplit_once('.').unwrap();
+        let (_, nonce) = body.split_once('.').unwrap();
+        let mut seen = NonceStore::default();
+
+        // NOTE: backdating the timestamp to dodge the fill time breaks the signature
+        let backdated = format!("{}.{nonce}.{signature}", ISSUED - 60);
+        assert_eq!(verify_at(&backdated, ISSUED + 10, &mut seen), Err(FormTokenError::Invalid));
+
+        let other_secret = sign("another-secret-of-sufficient-size", ISSUED);
+        assert_eq!(verify_at(&other_secret, ISSUED + 10, &mut seen), Err(FormTokenError::Invalid));
+
+        for garbage in ["nodots", "a.b", "x.y.!!!"] {
+            assert_eq!(verify_at(garbage, ISSUED + 10, &mut seen), Err(FormTokenError::Invalid));
+        }
+        assert_eq!(check(Some("  "), SECRET, 3600, 3, ISSUED, &mut seen), Err(FormTokenError::Missing));
+        assert_eq!(check(None, SECRET, 3600, 3, ISSUED, &mut seen), Err(FormTokenError::Missing));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{cell::Cell, fs, future::Future, path::Path, sync::OnceLock};
+
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue, concurrent::FluentBundle};
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+use crate::types::locale::Locale;
+
+fn builtin(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => include_str!("../../locales/en.ftl"),
+        Locale::Ru => include_str!("../../locales/ru.ftl"),
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: Cell<Locale>;
+}
+
+/// Locale of the request being handled, `en` outside of one.
+pub fn current() -> Locale {
+    CURRENT.try_with(Cell::get).unwrap_or_default()
+}
+
+/// Switches the current request to `locale`, e.g. from its payload.
+pub fn set_current(locale: Locale) {
+    let _ = CURRENT.try_with(|c| c.set(locale));
+}
+
+/// Runs `f` as a request answered in `locale`.
+pub async fn scope<F: Future>(locale: Locale, f: F) -> F::Output {
+    CURRENT.scope(Cell::new(locale), f).await
+}
+
+pub struct Catalog {
+    bundles: Vec<(Locale, FluentBundle<FluentResource>)>,
+}
+
+impl Catalog {
+    /// The built-in catalogs, with `<dir>/<locale>.ftl` overriding single
+    /// messages. Broken override entries are skipped with a warning.
+    fn load(dir: Option<&str>) -> Self {
+        let bundles = Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let id: LanguageIdentifier = locale.as_str().parse().expect("valid language tag");
+                let mut bundle = FluentBundle::new_concurrent(vec![id]);
+                // NOTE: no Unicode isolation marks, messages end up in JSON and plain-text mail
+                bundle.set_use_isolating(false);
+                let resource = FluentResource::try_new(builtin(locale).to_string()).expect("built-in catalog parses");
+                bundle.add_resource(resource).expect("built-in catalog has unique ids");
+                if let Some(dir) = dir {
+                    let path = Path::new(dir).join(format!("{}.ftl", locale.as_str()));
+                    if let Ok(source) = fs::read_to_string(&path) {
+                        let resource = FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+                            warn!(path = %path.display(), errors = errors.len(), "skipping broken catalog entries");
+                            resource
+                        });
+                        bundle.add_resource_overriding(resource);
+                    }
+                }
+                (locale, bundle)
+            })
+            .collect();
+        Self { bundles }
+    }
+
+    fn format(&self, locale: Locale, id: &str, args: &FluentArgs) -> Option<String> {
+        let (_, bundle) = self.bundles.iter().find(|(l, _)| *l == locale)?;
+        let pattern = bundle.get_message(id)?.value()?;
+        let mut errors = Vec::new();
+        let text = bundle.format_pattern(pattern, Some(args), &mut errors);
+        if !errors.is_empty() {
+            warn!(id, locale = locale.as_str(), errors = ?errors, "catalog message formatted with errors");
+        }
+        Some(text.into_owned())
+    }
+}
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Loads the catalogs with the overrides from `dir`; without a call the
+/// built-in ones are used.
+pub fn init(dir: Option<&str>) {
+    let _ = CATALOG.set(Catalog::load(dir));
+}
+
+/// Message `id` in `locale`, falling back to `en`; `None` when neither
+/// catalog has it.
+pub fn text(locale: Locale, id: &str, args: &[(&str, FluentValue<'static>)]) -> Option<String> {
+    let catalog = CATALOG.get_or_init(|| Catalog::load(None));
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    catalog
+        .format(locale, id, &fluent_args)
+        .or_else(|| catalog.format(Locale::En, id, &fluent_args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ErrorCode;
+
+    #[test]
+    fn every_error_code_has_a_message_in_every_locale() {
+        let catalog = Catalog::load(None);
+        let args = FluentArgs::new();
+        let schema = serde_json::to_value(<ErrorCode as utoipa::PartialSchema>::schema()).unwrap();
+        let codes = schema["enum"].as_array().unwrap().iter().filter_map(|c| c.as_str()).collect::<Vec<_>>();
+        assert!(codes.contains(&ErrorCode::SubjectTooLong.as_str()));
+        for locale in Locale::ALL {
+            for code in &codes {
+                assert!(catalog.format(locale, code, &args).is_some(), "{code} missing in {}", locale.as_str());
+            }
+        }
+    }
+
+    #[test]
+    fn plurals_and_selectors_follow_the_locale() {
+        let count = |n: i64| [("count", FluentValue::from(n))];
+        assert_eq!(text(Locale::En, "ValidationFailed", &count(1)).unwrap(), "1 field failed validation");
+        assert_eq!(text(Locale::Ru, "ValidationFailed", &count(3)).unwrap(), "3 поля не прошли проверку");
+        assert_eq!(text(Locale::Ru, "ValidationFailed", &count(5)).unwrap(), "5 полей не прошли проверку");
+        let args = [("field", FluentValue::from("subject")), ("expected", FluentValue::from("list"))];
+        assert_eq!(text(Locale::Ru, "FieldInvalidType", &args).unwrap(), "поле subject должно быть массивом строк");
+        let ip = [("ip", FluentValue::from("203.0.113.7"))];
+        assert_eq!(text(Locale::Ru, "BanNotFound", &ip).unwrap(), "203.0.113.7 не заблокирован");
+        let quota = |retry: FluentValue<'static>| {
+            [("limit", 5.into()), ("window", 86_400.into()), ("retry", retry), ("requested", 6.into())]
+        };
+        let never = text(Locale::En, "DailyQuotaExceeded", &quota("never".into())).unwrap();
+        assert!(never.ends_with("This request alone needs 6 messages and can never fit."), "{never}");
+        let later = text(Locale::En, "DailyQuotaExceeded", &quota(30.into())).unwrap();
+        assert!(later.ends_with("Please try again in 30s."), "{later}");
+        assert_eq!(text(Locale::Ru, "NoSuchMessage", &[]), None);
+    }
+
+    #[test]
+    fn accept_language_picks_the_best_supported_locale() {
+        assert_eq!(Locale::negotiate("ru-RU,ru;q=0.9,en-US;q=0.8"), Some(Locale::Ru));
+        assert_eq!(Locale::negotiate("de-DE, en;q=0.5, ru;q=0.7"), Some(Locale::Ru));
+        assert_eq!(Locale::negotiate("de, fr"), None);
+        assert_eq!(Locale::parse("EN_gb"), Some(Locale::En));
+    }
+}
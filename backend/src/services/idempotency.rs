@@ -0,0 +1,245 @@
+use axum::{
+    body::Bytes,
+    http::{HeaderValue, StatusCode},
+};
+use std::collections::HashMap;
+
+use crate::config::CONFIG;
+use crate::types::Submission;
+use crate::utils::crypto::sha256_hex;
+
+pub const MAX_KEY_LEN: usize = 255;
+// NOTE: frees a key whose request never finished, e.g. the client disconnected
+const IN_FLIGHT_SECONDS: i64 = 300;
+
+/// A response kept for replay.
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub status: StatusCode,
+    pub content_type: Option<HeaderValue>,
+    pub body: Bytes,
+}
+
+#[derive(Debug, Clone)]
+enum Slot {
+    InFlight,
+    Done(StoredResponse),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    fingerprint: String,
+    slot: Slot,
+    expires_at: i64,
+}
+
+/// Identifies a submission, either by `Idempotency-Key` or, when
+/// the client sent none, by a hash of its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupKey {
+    key: String,
+    fingerprint: String,
+    explicit: bool,
+    window: i64,
+}
+
+impl DedupKey {
+    /// `None` when no key was sent and content deduplication is disabled.
+    pub fn new(ip: &str, idempotency_key: Option<&str>, payload: &Submission) -> Option<Self> {
+        let key_window = CONFIG.idempotency_window_seconds as i64;
+        Self::within(ip, idempotency_key, payload, key_window, CONFIG.duplicate_window_seconds as i64)
+    }
+
+    fn within(
+        ip: &str,
+        idempotency_key: Option<&str>,
+        payload: &Submission,
+        key_window: i64,
+        content_window: i64,
+    ) -> Option<Self> {
+        let fingerprint = fingerprint(payload);
+        match idempotency_key {
+            Some(key) => Some(Self {
+                key: format!("key:{ip}:{key}"),
+                fingerprint,
+                explicit: true,
+                window: key_window,
+            }),
+            None if content_window > 0 => Some(Self {
+                key: format!("content:{ip}:{fingerprint}"),
+                fingerprint,
+                explicit: false,
+                window: content_window,
+            }),
+            None => None,
+        }
+    }
+
+    pub fn is_explicit(&self) -> bool {
+        self.explicit
+    }
+
+    /// Explicit keys keep every final answer except retryable ones; content
+    /// hashes only remember accepted submissions.
+    fn keeps(&self, status: StatusCode) -> bool {
+        if self.explicit {
+            !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS
+        } else {
+            status.is_success()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Begin {
+    /// First sighting: process it, then call `finish`.
+    New,
+    Replay(StoredResponse),
+    /// The original request is still being processed.
+    InProgress,
+    /// The key was used before with a different payload.
+    Mismatch,
+}
+
+/// Hash of what ends up in the email. Form token, CAPTCHA and proof-of-work
+/// answers are left out so a retry with fresh ones still matches.
+fn fingerprint(payload: &Submission) -> String {
+    let mut recipients: Vec<String> = payload
+        .recipients
+        .iter()
+        .flatten()
+        .map(|r| r.trim().to_lowercase())
+        .collect();
+    recipients.sort();
+    let email = payload.email().unwrap_or_default().trim().to_lowercase();
+    let canonical = serde_json::json!([
+        payload.subject.trim(),
+        payload.body(str::to_string).trim(),
+        email,
+        recipients,
+    ]);
+    sha256_hex(canonical.to_string().as_bytes())
+}
+
+/// Answers of recent submissions, for `Idempotency-Key` replays and for
+/// dropping identical double submits.
+#[derive(Debug, Default)]
+pub struct IdempotencyStore {
+    entries: HashMap<String, Entry>,
+}
+
+impl IdempotencyStore {
+    pub fn begin(&mut self, key: &DedupKey, now: i64) -> Begin {
+        self.entries.retain(|_, e| e.expires_at > now);
+        if let Some(entry) = self.entries.get(&key.key) {
+            if entry.fingerprint != key.fingerprint {
+                return Begin::Mismatch;
+            }
+            return match &entry.slot {
+                Slot::InFlight => Begin::InProgress,
+                Slot::Done(stored) => Begin::Replay(stored.clone()),
+            };
+        }
+        self.entries.insert(
+            key.key.clone(),
+            Entry {
+                fingerprint: key.fingerprint.clone(),
+                slot: Slot::InFlight,
+                expires_at: now + key.window.min(IN_FLIGHT_SECONDS),
+            },
+        );
+        Begin::New
+    }
+
+    /// Stores the final answer, or forgets the key so the client may retry.
+    pub fn finish(&mut self, key: &DedupKey, response: StoredResponse, now: i64) {
+        if !key.keeps(response.status) {
+            self.entries.remove(&key.key);
+            return;
+        }
+        if let Some(entry) = self.entries.get_mut(&key.key) {
+            entry.slot = Slot::Done(response);
+            entry.expires_at = now + key.window;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const IP: &str = "203.0.113.7";
+
+    fn submission(message: &str) -> Submission {
+        serde_json::from_value(json!({"subject": "Hi", "message": message})).unwrap()
+    }
+
+    fn keyed(key: &str, message: &str) -> DedupKey {
+        DedupKey::within(IP, Some(key), &submission(message), 3600, 0).unwrap()
+    }
+
+    fn answer(status: StatusCode) -> StoredResponse {
+        StoredResponse {
+            status,
+            content_type: None,
+            body: Bytes::from_static(b"{}"),
+        }
+    }
+
+    #[test]
+    fn a_reused_key_with_another_body_is_a_mismatch() {
+        let mut store = IdempotencyStore::default();
+        let original = keyed("abc", "Hello");
+        assert!(matches!(store.begin(&original, 0), Begin::New));
+        assert!(matches!(store.begin(&keyed("abc", "Hello again"), 1), Begin::Mismatch));
+        assert!(matches!(store.begin(&original, 1), Begin::InProgress));
+
+        store.finish(&original, answer(StatusCode::OK), 2);
+        assert!(matches!(store.begin(&keyed("abc", "Hello again"), 3), Begin::Mismatch));
+        // NOTE: a fresh form token or CAPTCHA answer is not a different body
+        let mut retry = submission("Hello");
+        retry.form_token = Some("fresh".into());
+        let retry = DedupKey::within(IP, Some("abc"), &retry, 3600, 0).unwrap();
+        assert!(matches!(store.begin(&retry, 3), Begin::Replay(r) if r.status == StatusCode::OK));
+    }
+
+    #[test]
+    fn keys_are_scoped_to_the_client_and_expire() {
+        let mut store = IdempotencyStore::default();
+        let original = keyed("abc", "Hello");
+        assert!(matches!(store.begin(&original, 0), Begin::New));
+        store.finish(&original, answer(StatusCode::OK), 0);
+
+        let elsewhere = DedupKey::within("198.51.100.1", Some("abc"), &submission("Other"), 3600, 0).unwrap();
+        assert!(matches!(store.begin(&elsewhere, 1), Begin::New));
+        assert!(matches!(store.begin(&keyed("abc", "Other"), 3600), Begin::New));
+    }
+
+    #[test]
+    fn retryable_answers_free_the_key() {
+        let mut store = IdempotencyStore::default();
+        let original = keyed("abc", "Hello");
+        for status in [StatusCode::INTERNAL_SERVER_ERROR, StatusCode::TOO_MANY_REQUESTS] {
+            assert!(matches!(store.begin(&original, 0), Begin::New));
+            store.finish(&original, answer(status), 0);
+        }
+        assert!(matches!(store.begin(&original, 0), Begin::New));
+        store.finish(&original, answer(StatusCode::UNPROCESSABLE_ENTITY), 0);
+        assert!(matches!(store.begin(&original, 1), Begin::Replay(_)));
+    }
+
+    #[test]
+    fn content_deduplication_only_remembers_accepted_submissions() {
+        assert!(DedupKey::within(IP, None, &submission("Hello"), 3600, 0).is_none());
+
+        let mut store = IdempotencyStore::default();
+        let key = DedupKey::within(IP, None, &submission("Hello"), 3600, 60).unwrap();
+        assert!(matches!(store.begin(&key, 0), Begin::New));
+        store.finish(&key, answer(StatusCode::UNPROCESSABLE_ENTITY), 0);
+        assert!(matches!(store.begin(&key, 1), Begin::New));
+        store.finish(&key, answer(StatusCode::OK), 1);
+        assert!(matches!(store.begin(&key, 60), Begin::Replay(_)));
+        assert!(matches!(store.begin(&key, 61), Begin::New));
+    }
+}
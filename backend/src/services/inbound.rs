@@ -0,0 +1,450 @@
+use axum::http::{HeaderMap, HeaderName};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::config::{InboundProvider, InboundSettings};
+use crate::types::{ErrorCode, RequestPayload};
+use crate::utils::{
+    crypto::{base64_decode, constant_time_eq, verify_hmac_sha256},
+    template::fill,
+};
+
+pub const TYPEFORM_SIGNATURE: HeaderName = HeaderName::from_static("typeform-signature");
+pub const GOOGLE_FORMS_SIGNATURE: HeaderName = HeaderName::from_static("x-google-forms-signature");
+
+// NOTE: Tilda bookkeeping fields, not part of what the visitor typed
+const TILDA_SERVICE_FIELDS: [&str; 5] = ["tranid", "formid", "formname", "COOKIES", "test"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InboundError {
+    /// Missing or wrong API key or signature.
+    Unauthorized,
+    Malformed(String),
+}
+
+impl InboundError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::InboundUnauthorized,
+            Self::Malformed(_) => ErrorCode::InboundPayloadInvalid,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::Unauthorized => "the provider's API key or signature does not match".into(),
+            Self::Malformed(reason) => format!("payload not understood: {reason}"),
+        }
+    }
+}
+
+/// A provider's form post in provider-neutral terms.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct InboundForm {
+    /// Form title or id, for the subject.
+    pub form: String,
+    pub email: Option<String>,
+    /// Question title and answer, in form order.
+    pub answers: Vec<(String, String)>,
+    /// Answers and hidden fields by machine name, for templates.
+    pub fields: HashMap<String, String>,
+}
+
+impl InboundForm {
+    fn answer(&mut self, key: &str, title: &str, value: String) {
+        if value.trim().is_empty() {
+            return;
+        }
+        let key = field_key(key);
+        if self.email.is_none() && key == "email" {
+            self.email = Some(value.trim().to_string());
+        }
+        self.fields.insert(key, value.clone());
+        self.answers.push((title.to_string(), value));
+    }
+
+    /// The submission the pipeline runs on: every answer in the message and
+    /// the machine names in `extra`, where CRM templates can pick them up.
+    pub fn into_payload(self, settings: &InboundSettings) -> RequestPayload {
+        let subject = fill(&settings.subject, |name| match name {
+            "form" => Some(self.form.clone()),
+            "provider" => Some(settings.provider.as_str().to_string()),
+            _ => None,
+        });
+        let message = self
+            .answers
+            .iter()
+            .map(|(title, value)| format!("{title}: {value}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        RequestPayload {
+            subject,
+            message,
+            email: self.email,
+            recipients: None,
+            form_token: None,
+            captcha_token: None,
+            pow: None,
+            locale: None,
+            extra: self
+                .fields
+                .into_iter()
+                .map(|(k, v)| (k, Value::String(v)))
+                .collect(),
+        }
+    }
+}
+
+/// `Your Name` -> `your_name`.
+fn field_key(name: &str) -> String {
+    let key: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    key.trim_matches('_').to_string()
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(text).collect::<Vec<_>>().join(", "),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// `sha256=<base64 HMAC-SHA256 of the body>`, as Typeform signs and the
+/// Apps Script for Google Forms is told to.
+fn verify_base64_hmac(headers: &HeaderMap, header: &HeaderName, secret: &str, body: &[u8]) -> bool {
+    headers
+        .get(header)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("sha256="))
+        .and_then(base64_decode)
+        .is_some_and(|mac| verify_hmac_sha256(secret.as_bytes(), body, &mac))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim_start().to_ascii_lowercase().starts_with("application/json"))
+}
+
+/// Tilda fields in order, from either of the encodings it can send.
+fn tilda_fields(headers: &HeaderMap, body: &[u8]) -> Result<Vec<(String, String)>, InboundError> {
+    if !is_json(headers) {
+        return Ok(form_urlencoded::parse(body).into_owned().collect());
+    }
+    let object: Map<String, Value> =
+        serde_json::from_slice(body).map_err(|e| InboundError::Malformed(e.to_string()))?;
+    Ok(object.into_iter().map(|(k, v)| (k, text(&v))).collect())
+}
+
+/// Tilda's "test" ping when the webhook is connected. It carries no key, so
+/// it is answered before verification and never processed.
+pub fn is_tilda_test(headers: &HeaderMap, body: &[u8]) -> bool {
+    tilda_fields(headers, body).is_ok_and(|fields| fields.iter().any(|(k, v)| k == "test" && v == "test"))
+}
+
+fn tilda(settings: &InboundSettings, headers: &HeaderMap, body: &[u8]) -> Result<InboundForm, InboundError> {
+    let fields = tilda_fields(headers, body)?;
+    let header_key = headers.get(settings.secret_field.as_str()).and_then(|v| v.to_str().ok());
+    let field_key = fields
+        .iter()
+        .find(|(k, _)| *k == settings.secret_field)
+        .map(|(_, v)| v.as_str());
+    if !header_key
+        .or(field_key)
+        .is_some_and(|key| constant_time_eq(key.as_bytes(), settings.secret.as_bytes()))
+    {
+        return Err(InboundError::Unauthorized);
+    }
+
+    let mut form = InboundForm::default();
+    let named = |name: &str| fields.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+    form.form = named("formname")
+        .or_else(|| named("formid"))
+        .unwrap_or_else(|| "Tilda form".into());
+    for (name, value) in &fields {
+        if *name == settings.secret_field || TILDA_SERVICE_FIELDS.contains(&name.as_str()) {
+            continue;
+        }
+        form.answer(name, name, value.clone());
+    }
+    Ok(form)
+}
+
+#[derive(Deserialize)]
+struct GoogleFormsPost {
+    #[serde(default)]
+    form_title: Option<String>,
+    #[serde(default)]
+    respondent_email: Option<String>,
+    answers: Vec<GoogleFormsAnswer>,
+}
+
+#[derive(Deserialize)]
+struct GoogleFormsAnswer {
+    title: String,
+    /// A string, or a list for checkboxes and grids.
+    answer: Value,
+}
+
+fn google_forms(body: &[u8]) -> Result<InboundForm, InboundError> {
+    let post: GoogleFormsPost = serde_json::from_slice(body).map_err(|e| InboundError::Malformed(e.to_string()))?;
+    let mut form = InboundForm {
+        form: post.form_title.unwrap_or_else(|| "Google Form".into()),
+        email: post.respondent_email.filter(|e| !e.trim().is_empty()),
+        ..Default::default()
+    };
+    for answer in &post.answers {
+        form.answer(&answer.title, &answer.title, text(&answer.answer));
+    }
+    Ok(form)
+}
+
+#[derive(Deserialize)]
+struct TypeformPost {
+    event_type: String,
+    form_response: TypeformResponse,
+}
+
+#[derive(Deserialize)]
+struct TypeformResponse {
+    form_id: String,
+    #[serde(default)]
+    definition: Option<TypeformDefinition>,
+    #[serde(default)]
+    answers: Vec<Map<String, Value>>,
+    #[serde(default)]
+    hidden: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct TypeformDefinition {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    fields: Vec<TypeformField>,
+}
+
+#[derive(Deserialize)]
+struct TypeformField {
+    id: String,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+/// The value of one Typeform answer, stored under the key its `type` names.
+fn typeform_value(answer: &Map<String, Value>) -> String {
+    let kind = answer.get("type").and_then(Value::as_str).unwrap_or_default();
+    match (kind, answer.get(kind)) {
+        ("choice", Some(choice)) => text(choice.get("label").or_else(|| choice.get("other")).unwrap_or(&Value::Null)),
+        ("choices", Some(choices)) => {
+            let mut labels: Vec<String> = choices
+                .get("labels")
+                .and_then(Value::as_array)
+                .map(|l| l.iter().map(text).collect())
+                .unwrap_or_default();
+            labels.extend(choices.get("other").map(text));
+            labels.join(", ")
+        }
+        ("payment", Some(payment)) => text(payment.get("amount").unwrap_or(&Value::Null)),
+        (_, Some(value)) => text(value),
+        (_, None) => String::new(),
+    }
+}
+
+fn typeform(body: &[u8]) -> Result<InboundForm, InboundError> {
+    let post: TypeformPost = serde_json::from_slice(body).map_err(|e| InboundError::Malformed(e.to_string()))?;
+    if post.event_type != "form_response" {
+        return Err(InboundError::Malformed(format!("unsupported event_type {:?}", post.event_type)));
+    }
+    let response = post.form_response;
+    let definition = response.definition.unwrap_or(TypeformDefinition {
+        title: None,
+        fields: Vec::new(),
+    });
+    let mut form = InboundForm {
+        form: definition.title.unwrap_or(response.form_id),
+        ..Default::default()
+    };
+    for answer in &response.answers {
+        let field = answer.get("field");
+        let id = field.and_then(|f| f.get("id")).and_then(Value::as_str).unwrap_or_default();
+        let key = field
+            .and_then(|f| f.get("ref"))
+            .and_then(Value::as_str)
+            .filter(|r| !r.is_empty())
+            .unwrap_or(id);
+        let title = definition
+            .fields
+            .iter()
+            .find(|f| f.id == id)
+            .and_then(|f| f.title.as_deref())
+            .unwrap_or(key);
+        let value = typeform_value(answer);
+        if answer.get("type").and_then(Value::as_str) == Some("email") && form.email.is_none() {
+            form.email = Some(value.trim().to_string());
+        }
+        form.answer(key, title, value);
+    }
+    for (name, value) in &response.hidden {
+        let value = text(value);
+        if !value.trim().is_empty() {
+            form.fields.entry(field_key(name)).or_insert(value);
+        }
+    }
+    Ok(form)
+}
+
+/// Checks the provider's credentials on the raw body, then reads it.
+pub fn parse(settings: &InboundSettings, headers: &HeaderMap, body: &[u8]) -> Result<InboundForm, InboundError> {
+    match settings.provider {
+        InboundProvider::Tilda => tilda(settings, headers, body),
+        InboundProvider::GoogleForms => {
+            if !verify_base64_hmac(headers, &GOOGLE_FORMS_SIGNATURE, &settings.secret, body) {
+                return Err(InboundError::Unauthorized);
+            }
+            google_forms(body)
+        }
+        InboundProvider::Typeform => {
+            if !verify_base64_hmac(headers, &TYPEFORM_SIGNATURE, &settings.secret, body) {
+                return Err(InboundError::Unauthorized);
+            }
+            typeform(body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiKeySettings;
+    use crate::utils::crypto::hmac_sha256;
+    use axum::http::{HeaderValue, header::CONTENT_TYPE};
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use serde_json::json;
+
+    const SECRET: &str = "inbound-test-secret";
+
+    fn settings(provider: InboundProvider) -> InboundSettings {
+        InboundSettings {
+            provider,
+            secret: SECRET.into(),
+            secret_field: "api_key".into(),
+            subject: "{provider}: {form}".into(),
+            caller: ApiKeySettings {
+                id: "landing".into(),
+                key_sha256: None,
+                hmac_secret: None,
+                rate_limit_max: 0,
+                rate_limit_timeframe_seconds: 60,
+                allowed_recipients: Vec::new(),
+            },
+        }
+    }
+
+    fn signed(header: HeaderName, body: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let mac = STANDARD.encode(hmac_sha256(SECRET.as_bytes(), body));
+        headers.insert(header, HeaderValue::from_str(&format!("sha256={mac}")).unwrap());
+        headers
+    }
+
+    #[test]
+    fn tilda_form_post_is_normalized() {
+        let settings = settings(InboundProvider::Tilda);
+        let body = b"Name=Ann&Email=ann%40example.com&Phone=%2B7+900&formid=form123&tranid=1%3A2&api_key=inbound-test-secret";
+        let form = parse(&settings, &HeaderMap::new(), body).unwrap();
+        assert_eq!(form.form, "form123");
+        assert_eq!(form.email.as_deref(), Some("ann@example.com"));
+        assert_eq!(form.fields["phone"], "+7 900");
+
+        let payload = form.into_payload(&settings);
+        assert_eq!(payload.subject, "tilda: form123");
+        assert_eq!(payload.message, "Name: Ann\nEmail: ann@example.com\nPhone: +7 900");
+        assert!(!payload.extra.contains_key("api_key") && !payload.extra.contains_key("tranid"));
+    }
+
+    #[test]
+    fn tilda_key_may_come_as_a_header_and_must_match() {
+        let settings = settings(InboundProvider::Tilda);
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert("api_key", HeaderValue::from_static(SECRET));
+        let form = parse(&settings, &headers, br#"{"Name":"Ann","formname":"Callback"}"#).unwrap();
+        assert_eq!(form.form, "Callback");
+
+        let wrong = b"Name=Ann&api_key=nope";
+        assert_eq!(parse(&settings, &HeaderMap::new(), wrong), Err(InboundError::Unauthorized));
+        assert!(is_tilda_test(&HeaderMap::new(), b"test=test"));
+    }
+
+    #[test]
+    fn google_forms_needs_a_valid_signature() {
+        let settings = settings(InboundProvider::GoogleForms);
+        let body = json!({
+            "form_title": "Feedback",
+            "respondent_email": "bob@example.com",
+            "answers": [
+                { "title": "Your name", "answer": "Bob" },
+                { "title": "Topics", "answer": ["pricing", "support"] },
+            ],
+        })
+        .to_string();
+        let form = parse(&settings, &signed(GOOGLE_FORMS_SIGNATURE, body.as_bytes()), body.as_bytes()).unwrap();
+        assert_eq!(form.email.as_deref(), Some("bob@example.com"));
+        assert_eq!(form.answers, [("Your name".into(), "Bob".into()), ("Topics".into(), "pricing, support".into())]);
+        assert_eq!(form.fields["your_name"], "Bob");
+
+        let tampered = body.replace("Bob", "Eve");
+        let headers = signed(GOOGLE_FORMS_SIGNATURE, body.as_bytes());
+        assert_eq!(parse(&settings, &headers, tampered.as_bytes()), Err(InboundError::Unauthorized));
+    }
+
+    #[test]
+    fn typeform_answers_use_titles_and_refs() {
+        let settings = settings(InboundProvider::Typeform);
+        let body = json!({
+            "event_id": "01",
+            "event_type": "form_response",
+            "form_response": {
+                "form_id": "lT4Z3j",
+                "definition": {
+                    "title": "Contact us",
+                    "fields": [
+                        { "id": "f1", "title": "What is your email?", "type": "email" },
+                        { "id": "f2", "title": "Phone", "type": "phone_number" },
+                        { "id": "f3", "title": "Topic", "type": "multiple_choice" },
+                    ],
+                },
+                "answers": [
+                    { "type": "email", "email": "cy@example.com", "field": { "id": "f1", "type": "email" } },
+                    { "type": "phone_number", "phone_number": "+15550100", "field": { "id": "f2", "ref": "phone", "type": "phone_number" } },
+                    { "type": "choice", "choice": { "label": "Sales" }, "field": { "id": "f3", "type": "multiple_choice" } },
+                ],
+                "hidden": { "utm_source": "ads" },
+            },
+        })
+        .to_string();
+        let form = parse(&settings, &signed(TYPEFORM_SIGNATURE, body.as_bytes()), body.as_bytes()).unwrap();
+        assert_eq!(form.form, "Contact us");
+        assert_eq!(form.email.as_deref(), Some("cy@example.com"));
+        assert_eq!(
+            form.answers,
+            [
+                ("What is your email?".into(), "cy@example.com".into()),
+                ("Phone".into(), "+15550100".into()),
+                ("Topic".into(), "Sales".into()),
+            ]
+        );
+        assert_eq!(form.fields["phone"], "+15550100");
+        assert_eq!(form.fields["utm_source"], "ads");
+
+        assert_eq!(parse(&settings, &HeaderMap::new(), body.as_bytes()), Err(InboundError::Unauthorized));
+    }
+}
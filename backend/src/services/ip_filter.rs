@@ -0,0 +1,129 @@
+use ipnet::IpNet;
+use std::{net::IpAddr, sync::Arc, time::{Duration, SystemTime}};
+use tracing::{info, warn};
+
+use crate::config::CONFIG;
+use crate::state::AppState;
+use crate::utils::parse_ip_net;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpDecision {
+    /// Exempt from rate limiting; wins over a deny entry.
+    Allowlisted,
+    Denied,
+    Neutral,
+}
+
+/// Allow and deny lists merged from `Settings` and the optional lists file.
+#[derive(Debug, Default, Clone)]
+pub struct IpFilter {
+    pub allow: Vec<IpNet>,
+    pub deny: Vec<IpNet>,
+}
+
+impl IpFilter {
+    /// Reads the configured lists plus `ip_lists_file`. A missing file counts
+    /// as empty so it can be created later and picked up on reload.
+    pub fn load() -> Result<Self, String> {
+        let mut filter = Self {
+            allow: CONFIG.ip_allowlist.clone(),
+            deny: CONFIG.ip_denylist.clone(),
+        };
+        if let Some(path) = &CONFIG.ip_lists_file {
+            match std::fs::read_to_string(path) {
+                Ok(contents) => filter.extend_from_file(&contents)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    warn!(path = %path, "IP lists file not found, using configured lists only");
+                }
+                Err(e) => return Err(format!("{path}: {e}")),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// One entry per line: `allow <ip|cidr>` or `deny <ip|cidr>`; `#` starts
+    /// a comment.
+    fn extend_from_file(&mut self, contents: &str) -> Result<(), String> {
+        for (n, raw) in contents.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (kind, value) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("line {}: expected `allow|deny <ip or cidr>`", n + 1))?;
+            let net = parse_ip_net(value)
+                .ok_or_else(|| format!("line {}: invalid IP or CIDR: {}", n + 1, value.trim()))?;
+            match kind.to_ascii_lowercase().as_str() {
+                "allow" => self.allow.push(net),
+                "deny" => self.deny.push(net),
+                other => return Err(format!("line {}: unknown list `{other}`", n + 1)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn decide(&self, ip: &IpAddr) -> IpDecision {
+        if self.allow.iter().any(|net| net.contains(ip)) {
+            IpDecision::Allowlisted
+        } else if self.deny.iter().any(|net| net.contains(ip)) {
+            IpDecision::Denied
+        } else {
+            IpDecision::Neutral
+        }
+    }
+}
+
+async fn reload(state: &AppState, reason: &str) {
+    match IpFilter::load() {
+        Ok(filter) => {
+            info!(
+                reason,
+                allow = filter.allow.len(),
+                deny = filter.deny.len(),
+                "IP lists reloaded"
+            );
+            *state.ip_filter.write().await = filter;
+        }
+        Err(e) => warn!(reason, error = %e, "IP lists reload failed, keeping previous lists"),
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reloads the lists on SIGHUP and whenever the lists file's mtime changes.
+pub fn spawn_reloader(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let path = CONFIG.ip_lists_file.clone();
+        let mut last_modified = path.as_deref().and_then(modified);
+        let mut tick = tokio::time::interval(Duration::from_secs(
+            CONFIG.ip_lists_reload_seconds.max(1) as u64,
+        ));
+
+        #[cfg(unix)]
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .expect("listen SIGHUP");
+
+        loop {
+            #[cfg(unix)]
+            tokio::select! {
+                _ = hangup.recv() => {
+                    reload(&state, "SIGHUP").await;
+                    continue;
+                }
+                _ = tick.tick() => {}
+            }
+            #[cfg(not(unix))]
+            tick.tick().await;
+
+            let Some(path) = path.as_deref() else { continue };
+            let current = modified(path);
+            if current != last_modified {
+                last_modified = current;
+                reload(&state, "file changed").await;
+            }
+        }
+    });
+}
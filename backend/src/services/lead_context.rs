@@ -0,0 +1,132 @@
+use axum::http::{
+    HeaderMap,
+    header::{REFERER, USER_AGENT},
+};
+use reqwest::Url;
+
+use crate::config::{CONFIG, LeadContextSettings};
+use crate::types::{LeadContext, Submission, Utm};
+
+/// Fills `submission.context` from its metadata and, for browser and API
+/// requests, the `Referer` and `User-Agent` headers. Anything malformed is
+/// dropped rather than rejected: a lead is never refused over its tracking.
+pub fn capture(submission: &mut Submission, headers: Option<&HeaderMap>) {
+    submission.context = context(&CONFIG.lead_context, submission, headers);
+}
+
+fn context(settings: &LeadContextSettings, submission: &Submission, headers: Option<&HeaderMap>) -> LeadContext {
+    let max = settings.max_length;
+    let header = |name| headers.and_then(|h| h.get(name)).and_then(|v| v.to_str().ok());
+    let page_url = submission.metadata.page_url.as_deref().and_then(|url| page(url, max));
+    let referer = header(REFERER).and_then(|url| page(url, max)).filter(|_| settings.referer);
+
+    let mut utm = Utm::default();
+    if settings.utm {
+        for (name, value) in submission.metadata.utm.tags() {
+            if let Some(tag) = utm.tag_mut(name) {
+                *tag = value.as_deref().and_then(|v| text(v, max));
+            }
+        }
+        // NOTE: the landing page's own query when the client sent no tags
+        if utm.is_empty()
+            && let Some(url) = page_url.as_deref().or(referer.as_deref())
+        {
+            utm = query_utm(url, max);
+        }
+    }
+
+    LeadContext {
+        page_url: page_url.filter(|_| settings.page_url),
+        referer,
+        utm,
+        user_agent: header(USER_AGENT).and_then(|v| text(v, max)).filter(|_| settings.user_agent),
+    }
+}
+
+/// An absolute http(s) URL without credentials.
+fn page(value: &str, max: usize) -> Option<String> {
+    let mut url = Url::parse(value.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+        return None;
+    }
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    text(url.as_str(), max)
+}
+
+/// Trimmed, without control characters and at most `max` characters long.
+fn text(value: &str, max: usize) -> Option<String> {
+    let value: String = value.trim().chars().filter(|c| !c.is_control()).take(max).collect();
+    let value = value.trim_end();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn query_utm(url: &str, max: usize) -> Utm {
+    let mut utm = Utm::default();
+    let Ok(url) = Url::parse(url) else {
+        return utm;
+    };
+    for (key, value) in url.query_pairs() {
+        if let Some(tag) = key.strip_prefix("utm_").and_then(|name| utm.tag_mut(name))
+            && tag.is_none()
+        {
+            *tag = text(&value, max);
+        }
+    }
+    utm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    fn submission(metadata: serde_json::Value) -> Submission {
+        serde_json::from_value(json!({"subject": "Hi", "metadata": metadata})).unwrap()
+    }
+
+    fn headers(referer: &str, user_agent: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(REFERER, HeaderValue::from_str(referer).unwrap());
+        headers.insert(USER_AGENT, HeaderValue::from_str(user_agent).unwrap());
+        headers
+    }
+
+    #[test]
+    fn values_are_validated_and_cut() {
+        let settings = LeadContextSettings {
+            max_length: 20,
+            ..LeadContextSettings::default()
+        };
+        let payload = submission(json!({
+            "page_url": "javascript:alert(1)",
+            "utm": {"source": " ya\u{7}ndex ", "campaign": "c".repeat(30)},
+        }));
+        let found = context(&settings, &payload, Some(&headers("https://user:pw@ex.com/", "Mozilla/5.0")));
+        assert_eq!(found.page_url, None);
+        assert_eq!(found.referer.as_deref(), Some("https://ex.com/"));
+        assert_eq!(found.utm.source.as_deref(), Some("yandex"));
+        assert_eq!(found.utm.campaign.as_deref(), Some("c".repeat(20).as_str()));
+        assert_eq!(found.user_agent.as_deref(), Some("Mozilla/5.0"));
+    }
+
+    #[test]
+    fn utm_falls_back_to_the_page_query_and_captures_can_be_off() {
+        let payload = submission(json!({"page_url": "https://ex.com/?utm_source=vk&utm_medium=cpc&x=1"}));
+        let found = context(&LeadContextSettings::default(), &payload, None);
+        assert_eq!(found.utm.source.as_deref(), Some("vk"));
+        assert_eq!(found.utm.medium.as_deref(), Some("cpc"));
+
+        let settings = LeadContextSettings {
+            referer: false,
+            user_agent: false,
+            page_url: false,
+            utm: false,
+            ..LeadContextSettings::default()
+        };
+        let found = context(&settings, &payload, Some(&headers("https://ex.com/?utm_source=a", "curl")));
+        assert!(found.page_url.is_none() && found.referer.is_none() && found.user_agent.is_none());
+        assert!(found.utm.is_empty());
+    }
+}
@@ -1,2 +1,26 @@
 mod send_email;
+pub mod deliver;
 pub use send_email::send_email;
+pub mod flood_control;
+pub use flood_control::FloodControl;
+pub mod nonce_store;
+pub use nonce_store::NonceStore;
+pub mod form_token;
+pub mod captcha;
+pub mod pow;
+pub mod spam;
+pub mod ip_filter;
+pub mod bans;
+pub mod idempotency;
+pub mod quarantine;
+pub mod audit;
+pub mod api_auth;
+pub mod admin_auth;
+pub mod submissions;
+pub mod webhooks;
+pub mod channels;
+pub mod crm;
+pub mod inbound;
+pub mod i18n;
+pub mod auto_reply;
+pub mod lead_context;
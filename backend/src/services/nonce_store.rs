@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+
+/// Remembers single-use identifiers until they expire.
+#[derive(Debug, Default)]
+pub struct NonceStore {
+    // NOTE: nonce -> unix time after which it can be forgotten
+    seen: HashMap<String, i64>,
+}
+
+impl NonceStore {
+    /// Records `nonce`; returns `false` if it was already used.
+    pub fn consume(&mut self, nonce: &str, expires_at: i64, now: i64) -> bool {
+        self.seen.retain(|_, &mut exp| exp > now);
+        if self.seen.contains_key(nonce) {
+            return false;
+        }
+        self.seen.insert(nonce.to_string(), expires_at);
+        true
+    }
+}
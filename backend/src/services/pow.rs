@@ -0,0 +1,212 @@
+use sha2::{Digest, Sha256};
+
+use crate::config::CONFIG;
+use crate::services::nonce_store::NonceStore;
+use crate::types::ErrorCode;
+use crate::utils::crypto::{base64url_decode, base64url_encode, hmac_sha256, random_hex, verify_hmac_sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowError {
+    Missing,
+    Invalid,
+    Expired,
+    Reused,
+    /// The hash does not have enough leading zero bits.
+    Insufficient,
+}
+
+impl PowError {
+    pub fn error_code(self) -> ErrorCode {
+        match self {
+            Self::Missing => ErrorCode::PowMissing,
+            Self::Invalid => ErrorCode::PowInvalid,
+            Self::Expired => ErrorCode::PowExpired,
+            Self::Reused => ErrorCode::PowReused,
+            Self::Insufficient => ErrorCode::PowInsufficient,
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Missing => "pow solution is required",
+            Self::Invalid => "pow challenge is invalid",
+            Self::Expired => "pow challenge has expired, request a new one",
+            Self::Reused => "pow challenge has already been used",
+            Self::Insufficient => "pow solution does not meet the difficulty",
+        }
+    }
+}
+
+/// Difficulty for the next challenge issued to a client that already has
+/// `ip_hits` requests counted in its rate-limit window.
+pub fn difficulty_for(ip_hits: usize) -> u8 {
+    if !CONFIG.pow_adaptive_difficulty {
+        return CONFIG.pow_difficulty;
+    }
+    scaled(CONFIG.pow_difficulty, CONFIG.pow_difficulty_step, CONFIG.pow_max_difficulty, ip_hits)
+}
+
+fn scaled(base: u8, step: u8, max: u8, ip_hits: usize) -> u8 {
+    let extra = (ip_hits as u32).saturating_mul(step as u32);
+    (base as u32).saturating_add(extra).min(max as u32) as u8
+}
+
+/// Issues `<issued_at>.<difficulty>.<nonce>.<signature>`; `None` when proof
+/// of work is disabled.
+pub fn issue(difficulty: u8, now: i64) -> Option<String> {
+    Some(sign(CONFIG.pow_secret.as_deref()?, difficulty, now))
+}
+
+fn sign(secret: &str, difficulty: u8, now: i64) -> String {
+    let body = format!("{now}.{difficulty}.{}", random_hex(16));
+    let signature = hmac_sha256(secret.as_bytes(), body.as_bytes());
+    format!("{body}.{}", base64url_encode(&signature))
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+/// Checks that `SHA-256(challenge ++ solution)` starts with the difficulty
+/// signed into the challenge, and burns the challenge.
+pub fn verify(
+    challenge: Option<&str>,
+    solution: Option<&str>,
+    now: i64,
+    seen: &mut NonceStore,
+) -> Result<(), PowError> {
+    let Some(secret) = CONFIG.pow_secret.as_deref() else {
+        return Ok(());
+    };
+    check(challenge, solution, secret, CONFIG.pow_max_age_seconds as i64, now, seen)
+}
+
+fn check(
+    challenge: Option<&str>,
+    solution: Option<&str>,
+    secret: &str,
+    max_age: i64,
+    now: i64,
+    seen: &mut NonceStore,
+) -> Result<(), PowError> {
+    let (Some(challenge), Some(solution)) = (challenge, solution) else {
+        return Err(PowError::Missing);
+    };
+
+    let (body, signature) = challenge.rsplit_once('.').ok_or(PowError::Invalid)?;
+    let signature = base64url_decode(signature).ok_or(PowError::Invalid)?;
+    if !verify_hmac_sha256(secret.as_bytes(), body.as_bytes(), &signature) {
+        return Err(PowError::Invalid);
+    }
+    let mut parts = body.splitn(3, '.');
+    let (Some(issued_at), Some(difficulty), Some(nonce)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(PowError::Invalid);
+    };
+    let issued_at: i64 = issued_at.parse().map_err(|_| PowError::Invalid)?;
+    let difficulty: u32 = difficulty.parse().map_err(|_| PowError::Invalid)?;
+
+    if now < issued_at {
+        return Err(PowError::Invalid);
+    }
+    if now - issued_at > max_age {
+        return Err(PowError::Expired);
+    }
+
+    let hash = Sha256::new()
+        .chain_update(challenge.as_bytes())
+        .chain_update(solution.as_bytes())
+        .finalize();
+    if leading_zero_bits(&hash) < difficulty {
+        return Err(PowError::Insufficient);
+    }
+    if !seen.consume(nonce, issued_at + max_age, now) {
+        return Err(PowError::Reused);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+    const ISSUED: i64 = 1_000_000;
+
+    fn zero_bits(challenge: &str, solution: &str) -> u32 {
+        leading_zero_bits(&Sha256::new().chain_update(challenge).chain_update(solution).finalize())
+    }
+
+    /// A solution with at least `bits` leading zero bits.
+    fn solve(challenge: &str, bits: u32) -> String {
+        (0u64..).map(|n| n.to_string()).find(|s| zero_bits(challenge, s) >= bits).unwrap()
+    }
+
+    fn verify_at(challenge: &str, solution: &str, now: i64, seen: &mut NonceStore) -> Result<(), PowError> {
+        check(Some(challenge), Some(solution), SECRET, 600, now, seen)
+    }
+
+    #[test]
+    fn counts_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0, 0, 0b0001_0000, 0xff]), 19);
+        assert_eq!(leading_zero_bits(&[0x80]), 0);
+        assert_eq!(leading_zero_bits(&[0, 0]), 16);
+    }
+
+    #[test]
+    fn solution_must_meet_the_signed_difficulty() {
+        let challenge = sign(SECRET, 8, ISSUED);
+        let weak = (0u64..).map(|n| n.to_string()).find(|s| zero_bits(&challenge, s) < 8).unwrap();
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&challenge, &weak, ISSUED + 5, &mut seen), Err(PowError::Insufficient));
+        // NOTE: a failed attempt does not burn the challenge
+        assert_eq!(verify_at(&challenge, &solve(&challenge, 8), ISSUED + 5, &mut seen), Ok(()));
+    }
+
+    #[test]
+    fn a_solved_challenge_cannot_be_replayed() {
+        let challenge = sign(SECRET, 4, ISSUED);
+        let solution = solve(&challenge, 4);
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&challenge, &solution, ISSUED + 5, &mut seen), Ok(()));
+        assert_eq!(verify_at(&challenge, &solution, ISSUED + 6, &mut seen), Err(PowError::Reused));
+    }
+
+    #[test]
+    fn tampered_or_foreign_challenges_are_invalid() {
+        let challenge = sign(SECRET, 20, ISSUED);
+        let (body, signature) = challenge.rsplit_once('.').unwrap();
+        let nonce = body.splitn(3, '.').nth(2).unwrap();
+        let easier = format!("{ISSUED}.0.{nonce}.{signature}");
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&easier, "x", ISSUED + 5, &mut seen), Err(PowError::Invalid));
+
+        let foreign = sign("another-secret-of-sufficient-size", 0, ISSUED);
+        assert_eq!(verify_at(&foreign, "x", ISSUED + 5, &mut seen), Err(PowError::Invalid));
+        assert_eq!(check(None, Some("x"), SECRET, 600, ISSUED, &mut seen), Err(PowError::Missing));
+    }
+
+    #[test]
+    fn expired_and_future_challenges_are_refused() {
+        let challenge = sign(SECRET, 0, ISSUED);
+        let mut seen = NonceStore::default();
+        assert_eq!(verify_at(&challenge, "x", ISSUED + 601, &mut seen), Err(PowError::Expired));
+        assert_eq!(verify_at(&challenge, "x", ISSUED - 1, &mut seen), Err(PowError::Invalid));
+    }
+
+    #[test]
+    fn adaptive_difficulty_steps_up_to_the_cap() {
+        assert_eq!(scaled(16, 2, 24, 0), 16);
+        assert_eq!(scaled(16, 2, 24, 3), 22);
+        assert_eq!(scaled(16, 2, 24, 10), 24);
+        assert_eq!(scaled(16, 2, 24, usize::MAX), 24);
+    }
+}
@@ -0,0 +1,328 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+use crate::config::{ApiPaths, CONFIG};
+use crate::services::{
+    deliver::{REQUEST_ID_HEADER, deliver},
+    spam::{SpamAction, SpamRule, SpamVerdict},
+};
+use crate::state::AppState;
+use crate::types::{LeadContext, locale::Locale};
+use crate::utils::crypto::random_hex;
+
+// NOTE: the digest lists this many submissions, then just counts the rest
+const DIGEST_MAX_ITEMS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineReason {
+    SpamScore,
+    BlockedDomain,
+    NewDomain,
+    FirstTimeIp,
+    /// A shadow-banned rule fired; see `shadow_rule`.
+    ShadowBanned,
+}
+
+impl QuarantineReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SpamScore => "spam_score",
+            Self::BlockedDomain => "blocked_domain",
+            Self::NewDomain => "new_domain",
+            Self::FirstTimeIp => "first_time_ip",
+            Self::ShadowBanned => "shadow_banned",
+        }
+    }
+}
+
+/// A submission held back for review, with everything needed to send it later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedSubmission {
+    pub id: String,
+    pub received_at: i64,
+    pub ip: String,
+    pub email: Option<String>,
+    /// Subject as it will be sent, including a spam tag.
+    pub subject: String,
+    /// The staff email, with the lead context.
+    pub message: String,
+    /// What the submitter wrote, the only text their auto-reply may repeat.
+    #[serde(default)]
+    pub submitted_message: String,
+    pub recipients: Vec<String>,
+    /// Recipients an earlier, partly failed approve already reached; a
+    /// retry only sends to the others.
+    #[serde(default)]
+    pub delivered_to: Vec<String>,
+    pub reasons: Vec<QuarantineReason>,
+    pub spam_score: Option<String>,
+    #[serde(default)]
+    pub spam_tagged: bool,
+    #[serde(default)]
+    pub shadow_rule: Option<String>,
+    /// Other submitted fields, for the CRM lead made on approval.
+    #[serde(default)]
+    pub extra: HashMap<String, Value>,
+    /// Page, referer, UTM tags and user agent it was sent with.
+    #[serde(default)]
+    pub context: LeadContext,
+    /// Language of the submitter's auto-reply once approved.
+    #[serde(default)]
+    pub locale: Locale,
+    /// `X-Request-Id` of the submission, repeated on the email once approved.
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+impl QuarantinedSubmission {
+    pub fn extra_headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if let Some(score) = &self.spam_score {
+            headers.push(("X-Spam-Score", score.clone()));
+        }
+        if self.spam_tagged {
+            headers.push(("X-Spam-Flag", "YES".into()));
+        }
+        if let Some(id) = &self.request_id {
+            headers.push((REQUEST_ID_HEADER, id.clone()));
+        }
+        headers
+    }
+}
+
+fn email_domain(email: Option<&str>) -> Option<String> {
+    email
+        .and_then(|e| e.rsplit_once('@'))
+        .map(|(_, d)| d.trim().to_lowercase())
+        .filter(|d| !d.is_empty())
+}
+
+/// Pending submissions plus the senders already seen delivering mail, which
+/// the new-domain and first-time-IP checks compare against.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QuarantineQueue {
+    items: Vec<QuarantinedSubmission>,
+    known_ips: HashSet<String>,
+    known_domains: HashSet<String>,
+}
+
+impl QuarantineQueue {
+    pub fn load() -> Self {
+        let path = &CONFIG.quarantine_store_file;
+        match std::fs::read_to_string(path) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+                warn!(path = %path, error = %e, "quarantine store unreadable, starting empty");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let path = &CONFIG.quarantine_store_file;
+        let tmp = format!("{path}.tmp");
+        let result = serde_json::to_vec_pretty(self)
+            .map_err(std::io::Error::other)
+            .and_then(|bytes| std::fs::write(&tmp, bytes))
+            .and_then(|_| std::fs::rename(&tmp, path));
+        if let Err(e) = result {
+            warn!(path = %path, error = %e, "Failed to persist quarantine");
+        }
+    }
+
+    /// Why a submission should be held back; empty means send it.
+    pub fn reasons(&self, ip: &str, email: Option<&str>, verdict: &SpamVerdict) -> Vec<QuarantineReason> {
+        let mut reasons = Vec::new();
+        if verdict.action == SpamAction::Quarantine {
+            reasons.push(QuarantineReason::SpamScore);
+        }
+        if CONFIG.quarantine_blocked_domains
+            && verdict.hits.iter().any(|h| h.rule == SpamRule::BlockedDomain)
+        {
+            reasons.push(QuarantineReason::BlockedDomain);
+        }
+        if CONFIG.quarantine_new_domains
+            && email_domain(email).is_some_and(|d| !self.known_domains.contains(&d))
+        {
+            reasons.push(QuarantineReason::NewDomain);
+        }
+        if CONFIG.quarantine_first_time_ips && !self.known_ips.contains(ip) {
+            reasons.push(QuarantineReason::FirstTimeIp);
+        }
+        reasons
+    }
+
+    /// Remembers a sender whose mail went out; `true` if anything was new.
+    pub fn mark_known(&mut self, ip: &str, email: Option<&str>) -> bool {
+        let mut changed = false;
+        if CONFIG.quarantine_first_time_ips {
+            changed |= self.known_ips.insert(ip.to_string());
+        }
+        if CONFIG.quarantine_new_domains
+            && let Some(domain) = email_domain(email)
+        {
+            changed |= self.known_domains.insert(domain);
+        }
+        changed
+    }
+
+    /// Queues the submission and returns its id. A full queue drops its
+    /// oldest entry and hands it back, so the caller can record its fate.
+    pub fn push(&mut self, item: QuarantinedSubmission) -> (String, Option<QuarantinedSubmission>) {
+        self.push_within(item, CONFIG.quarantine_max_items as usize)
+    }
+
+    fn push_within(
+        &mut self,
+        mut item: QuarantinedSubmission,
+        max_items: usize,
+    ) -> (String, Option<QuarantinedSubmission>) {
+        let evicted = (self.items.len() >= max_items && !self.items.is_empty()).then(|| self.items.remove(0));
+        item.id = random_hex(8);
+        let id = item.id.clone();
+        self.items.push(item);
+        (id, evicted)
+    }
+
+    /// Puts back a submission that could not be sent, keeping its id.
+    pub fn restore(&mut self, item: QuarantinedSubmission) {
+        self.items.push(item);
+        self.items.sort_by_key(|i| i.received_at);
+    }
+
+    pub fn list(&self) -> &[QuarantinedSubmission] {
+        &self.items
+    }
+
+    pub fn take(&mut self, id: &str) -> Option<QuarantinedSubmission> {
+        let pos = self.items.iter().position(|i| i.id == id)?;
+        Some(self.items.remove(pos))
+    }
+
+    /// Drops every pending submission from `ip`; returns how many.
+    pub fn discard_from(&mut self, ip: &str) -> usize {
+        let before = self.items.len();
+        self.items.retain(|i| i.ip != ip);
+        before - self.items.len()
+    }
+}
+
+fn digest_body(items: &[QuarantinedSubmission]) -> String {
+    let mut body = format!("{} submission(s) are waiting for review.\n\n", items.len());
+    for item in items.iter().take(DIGEST_MAX_ITEMS) {
+        let received = DateTime::from_timestamp(item.received_at, 0)
+            .unwrap_or_default()
+            .to_rfc3339();
+        let reasons = item
+            .reasons
+            .iter()
+            .map(|r| r.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        body.push_str(&format!(
+            "[{}] {} from {} <{}>: {} ({})\n",
+            item.id,
+            received,
+            item.ip,
+            item.email.as_deref().unwrap_or("-"),
+            item.subject,
+            reasons
+        ));
+    }
+    if items.len() > DIGEST_MAX_ITEMS {
+        body.push_str(&format!("... and {} more\n", items.len() - DIGEST_MAX_ITEMS));
+    }
+    body.push_str(&format!(
+        "\nReview them via GET {}{}{}.\n",
+        ApiPaths::V1_PREFIX,
+        ApiPaths::ADMIN_PREFIX,
+        ApiPaths::ADMIN_QUARANTINE
+    ));
+    body
+}
+
+/// Mails a summary of the pending submissions every
+/// `quarantine_digest_seconds`, skipping rounds with an empty queue or
+/// once the global daily cap is reached.
+pub fn spawn_digest(state: Arc<AppState>) {
+    if CONFIG.quarantine_digest_seconds == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick =
+            tokio::time::interval(Duration::from_secs(CONFIG.quarantine_digest_seconds as u64));
+        // NOTE: the first tick fires immediately, no digest right at startup
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            let items = state.quarantine.lock().await.list().to_vec();
+            if items.is_empty() {
+                continue;
+            }
+            let recipients = CONFIG
+                .quarantine_digest_recipients
+                .as_deref()
+                .or(CONFIG.emails.as_deref())
+                .unwrap_or_default();
+            let hit = state.flood_control.lock().await.hit_global(recipients.len(), Utc::now().timestamp());
+            if let Err(exceeded) = hit {
+                info!(pending = items.len(), quota = exceeded.kind.as_str(), "quarantine digest skipped");
+                continue;
+            }
+            let subject = format!("Quarantine: {} submission(s) waiting for review", items.len());
+            let failures = deliver(recipients, &subject, &digest_body(&items), &[]).await;
+            state
+                .submissions
+                .lock()
+                .await
+                .record_failures(None, &failures, Utc::now().timestamp());
+            if failures.is_empty() {
+                info!(pending = items.len(), "quarantine digest sent");
+            } else {
+                warn!(failed = failures.len(), "quarantine digest not fully delivered");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn held(subject: &str) -> QuarantinedSubmission {
+        serde_json::from_value(json!({
+            "id": "",
+            "received_at": 0,
+            "ip": "203.0.113.7",
+            "subject": subject,
+            "message": "Hello",
+            "recipients": ["team@example.com"],
+            "reasons": ["first_time_ip"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn a_full_queue_hands_back_its_oldest_item() {
+        let mut queue = QuarantineQueue::default();
+        let (first, evicted) = queue.push_within(held("first"), 2);
+        assert!(evicted.is_none());
+        assert!(queue.push_within(held("second"), 2).1.is_none());
+
+        let (third, evicted) = queue.push_within(held("third"), 2);
+        let evicted = evicted.unwrap();
+        assert_eq!((evicted.id, evicted.subject.as_str()), (first, "first"));
+        let left: Vec<_> = queue.list().iter().map(|i| i.subject.as_str()).collect();
+        assert_eq!(left, ["second", "third"]);
+        assert_eq!(queue.list()[1].id, third);
+    }
+}
@@ -1,4 +1,5 @@
 use lettre::message::Mailbox;
+use lettre::message::header::{HeaderName, HeaderValue};
 use lettre::transport::smtp::authentication::{Credentials, Mechanism};
 use lettre::{Message, SmtpTransport, Transport};
 use tracing::error;
@@ -10,15 +11,20 @@ pub fn send_email(
     recipient: &str,
     subject: &str,
     body: &str,
+    extra_headers: &[(&'static str, String)],
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let from: Mailbox = CONFIG.smtp_user.parse()?;
     let to: Mailbox = recipient.parse()?;
 
-    let email = Message::builder()
+    let mut email = Message::builder()
         .from(from)
         .to(to)
         .subject(subject)
         .body(body.to_string())?;
+    for (name, value) in extra_headers {
+        let name = HeaderName::new_from_ascii((*name).to_string())?;
+        email.headers_mut().insert_raw(HeaderValue::new(name, value.clone()));
+    }
 
     let creds = Credentials::new(CONFIG.smtp_user.clone(), CONFIG.smtp_password.clone());
     let mailer = SmtpTransport::relay(&CONFIG.smtp_server)?
@@ -0,0 +1,365 @@
+use std::fmt;
+
+use crate::config::SpamSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamRule {
+    Links,
+    BlockedDomain,
+    BlockedWord,
+    MixedScript,
+    Gibberish,
+    Caps,
+    RepeatedChars,
+}
+
+impl SpamRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Links => "links",
+            Self::BlockedDomain => "blocked_domain",
+            Self::BlockedWord => "blocked_word",
+            Self::MixedScript => "mixed_script",
+            Self::Gibberish => "gibberish",
+            Self::Caps => "caps",
+            Self::RepeatedChars => "repeated_chars",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpamAction {
+    Accept,
+    Tag,
+    Quarantine,
+    Reject,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleHit {
+    pub rule: SpamRule,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpamVerdict {
+    pub score: f64,
+    pub hits: Vec<RuleHit>,
+    pub action: SpamAction,
+}
+
+impl SpamVerdict {
+    /// Value for the `X-Spam-Score` header, e.g. `6.5 (links=2.0, caps=2.0)`.
+    pub fn header_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for SpamVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}", self.score)?;
+        if !self.hits.is_empty() {
+            let rules = self
+                .hits
+                .iter()
+                .map(|h| format!("{}={:.1}", h.rule.as_str(), h.score))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, " ({rules})")?;
+        }
+        Ok(())
+    }
+}
+
+const LATIN_VOWELS: &str = "aeiouy";
+const CYRILLIC_VOWELS: &str = "аеёиоуыэюя";
+// NOTE: caps and gibberish rules need enough text to be meaningful
+const MIN_LETTERS_FOR_CAPS: usize = 20;
+const MIN_GIBBERISH_LEN: usize = 6;
+const MAX_CONSONANT_RUN: usize = 5;
+// NOTE: per-word rules stop adding weight after this many words
+const MAX_WORD_HITS: usize = 3;
+
+fn is_cyrillic(c: char) -> bool {
+    matches!(c, '\u{0400}'..='\u{04FF}')
+}
+
+fn link_host(token: &str) -> Option<String> {
+    let lower = token.to_lowercase();
+    let rest = match lower.split_once("://") {
+        Some((_, rest)) => rest,
+        None if lower.starts_with("www.") => lower.as_str(),
+        None => return None,
+    };
+    let host = rest
+        .split(['/', ':', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(['.', ',', ')', ';']);
+    Some(host.to_string())
+}
+
+fn domain_matches(host: &str, blocked: &str) -> bool {
+    let blocked = blocked.trim().trim_start_matches('.').to_lowercase();
+    !blocked.is_empty() && (host == blocked || host.ends_with(&format!(".{blocked}")))
+}
+
+fn is_gibberish(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    if lower.chars().count() < MIN_GIBBERISH_LEN {
+        return false;
+    }
+    let is_vowel = |c: char| LATIN_VOWELS.contains(c) || CYRILLIC_VOWELS.contains(c);
+    let mut run = 0;
+    let mut longest = 0;
+    let mut vowels = 0;
+    for c in lower.chars() {
+        if is_vowel(c) {
+            vowels += 1;
+            run = 0;
+        } else {
+            run += 1;
+            longest = longest.max(run);
+        }
+    }
+    vowels == 0 || longest > MAX_CONSONANT_RUN
+}
+
+fn longest_run(text: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev = None;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            prev = None;
+            run = 0;
+            continue;
+        }
+        run = if prev == Some(c) { run + 1 } else { 1 };
+        prev = Some(c);
+        longest = longest.max(run);
+    }
+    longest
+}
+
+/// Scores a submission against the configured rules. `email` is the
+/// submitter's address, checked against the blocked domains.
+pub fn score(subject: &str, message: &str, email: Option<&str>, settings: &SpamSettings) -> SpamVerdict {
+    if !settings.enabled {
+        return SpamVerdict {
+            score: 0.0,
+            hits: Vec::new(),
+            action: SpamAction::Accept,
+        };
+    }
+
+    let mut hits: Vec<RuleHit> = Vec::new();
+    let mut hit = |rule: SpamRule, score: f64| {
+        if score > 0.0 {
+            hits.push(RuleHit { rule, score });
+        }
+    };
+
+    let text = format!("{subject}\n{message}");
+    let lower = text.to_lowercase();
+    let hosts: Vec<String> = text.split_whitespace().filter_map(link_host).collect();
+
+    let extra_links = hosts.len().saturating_sub(settings.max_links as usize);
+    hit(SpamRule::Links, extra_links as f64 * settings.link_weight);
+
+    let email_domain = email
+        .and_then(|e| e.rsplit_once('@'))
+        .map(|(_, d)| d.trim().to_lowercase());
+    let blocked_domains = settings.blocked_domains.as_deref().unwrap_or_default();
+    let domain_hits = blocked_domains
+        .iter()
+        .filter(|d| {
+            hosts.iter().chain(email_domain.iter()).any(|h| domain_matches(h, d))
+        })
+        .count();
+    hit(SpamRule::BlockedDomain, domain_hits as f64 * settings.blocked_domain_weight);
+
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let lower_words: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let blocked_words = settings.blocked_words.as_deref().unwrap_or_default();
+    let word_hits = blocked_words
+        .iter()
+        .map(|w| w.trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .filter(|w| {
+            if w.contains(' ') {
+                lower.contains(w.as_str())
+            } else {
+                lower_words.iter().any(|lw| lw == w)
+            }
+        })
+        .count();
+    hit(SpamRule::BlockedWord, word_hits as f64 * settings.blocked_word_weight);
+
+    let mixed = words
+        .iter()
+        .filter(|w| {
+            w.chars().any(|c| c.is_ascii_alphabetic()) && w.chars().any(is_cyrillic)
+        })
+        .count()
+        .min(MAX_WORD_HITS);
+    hit(SpamRule::MixedScript, mixed as f64 * settings.mixed_script_weight);
+
+    let gibberish = words
+        .iter()
+        .filter(|w| w.chars().all(char::is_alphabetic) && is_gibberish(w))
+        .count()
+        .min(MAX_WORD_HITS);
+    hit(SpamRule::Gibberish, gibberish as f64 * settings.gibberish_weight);
+
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() >= MIN_LETTERS_FOR_CAPS {
+        let upper = letters.iter().filter(|c| c.is_uppercase()).count();
+        if upper as f64 / letters.len() as f64 >= settings.caps_ratio {
+            hit(SpamRule::Caps, settings.caps_weight);
+        }
+    }
+
+    if settings.max_repeated_chars > 0 && longest_run(&text) > settings.max_repeated_chars as usize {
+        hit(SpamRule::RepeatedChars, settings.repeated_chars_weight);
+    }
+
+    let total = hits.iter().fold(0.0, |acc, h| acc + h.score);
+    let reached = |threshold: f64| threshold > 0.0 && total >= threshold;
+    let action = if reached(settings.reject_threshold) {
+        SpamAction::Reject
+    } else if reached(settings.quarantine_threshold) {
+        SpamAction::Quarantine
+    } else if reached(settings.tag_threshold) {
+        SpamAction::Tag
+    } else {
+        SpamAction::Accept
+    };
+
+    SpamVerdict {
+        score: total,
+        hits,
+        action,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> SpamSettings {
+        SpamSettings {
+            enabled: true,
+            ..SpamSettings::default()
+        }
+    }
+
+    fn rules(verdict: &SpamVerdict) -> Vec<(SpamRule, f64)> {
+        verdict.hits.iter().map(|h| (h.rule, h.score)).collect()
+    }
+
+    #[test]
+    fn disabled_by_default_and_then_accepts_anything() {
+        let settings = SpamSettings::default();
+        assert!(!settings.enabled);
+        let verdict = score("FREE MONEY!!!!!!!!", "xkcdqwrtz https://a.ru https://b.ru https://c.ru", None, &settings);
+        assert_eq!(verdict.action, SpamAction::Accept);
+        assert!(verdict.hits.is_empty() && verdict.score == 0.0);
+    }
+
+    #[test]
+    fn clean_text_scores_nothing() {
+        let verdict = score("Order", "Hello, please call me back tomorrow.", Some("ann@example.com"), &enabled());
+        assert!(verdict.hits.is_empty());
+        assert_eq!(verdict.action, SpamAction::Accept);
+        assert_eq!(verdict.header_value(), "0.0");
+    }
+
+    #[test]
+    fn links_beyond_the_allowance_each_score() {
+        let message = "see https://a.com/x www.b.com http://c.com https://d.com";
+        let verdict = score("Hi", message, None, &enabled());
+        assert_eq!(rules(&verdict), [(SpamRule::Links, 2.0)]);
+        assert_eq!(verdict.header_value(), "2.0 (links=2.0)");
+    }
+
+    #[test]
+    fn blocked_domains_match_link_hosts_subdomains_and_the_email() {
+        let settings = SpamSettings {
+            blocked_domains: Some(vec!["casino.com".into(), "spam.ru".into(), "other.org".into()]),
+            ..enabled()
+        };
+        let verdict = score("Hi", "go to https://win.casino.com/now", Some("bot@spam.ru"), &settings);
+        assert_eq!(rules(&verdict), [(SpamRule::BlockedDomain, 10.0)]);
+        assert_eq!(verdict.action, SpamAction::Reject);
+
+        let verdict = score("Hi", "notcasino.com is fine: https://notcasino.com", None, &settings);
+        assert!(verdict.hits.is_empty());
+    }
+
+    #[test]
+    fn blocked_words_and_phrases_are_case_insensitive() {
+        let settings = SpamSettings {
+            blocked_words: Some(vec!["viagra".into(), "free money".into(), "casino".into()]),
+            ..enabled()
+        };
+        let verdict = score("Cheap VIAGRA", "Get Free  Money and free money", None, &settings);
+        assert_eq!(rules(&verdict), [(SpamRule::BlockedWord, 6.0)]);
+
+        // NOTE: single words must match whole words
+        assert!(score("Hi", "casinos nearby", None, &settings).hits.is_empty());
+    }
+
+    #[test]
+    fn mixed_script_words_are_capped() {
+        // NOTE: Cyrillic `а` inside Latin words
+        let verdict = score("Hi", "pаypal bаnk аccount sеcure", None, &enabled());
+        assert_eq!(rules(&verdict), [(SpamRule::MixedScript, 3.0 * 2.0)]);
+    }
+
+    #[test]
+    fn gibberish_needs_long_vowelless_or_consonant_heavy_words() {
+        let verdict = score("Hi", "xkcdqwrtz hello", None, &enabled());
+        assert_eq!(rules(&verdict), [(SpamRule::Gibberish, 1.5)]);
+        assert!(score("Hi", "rhythm strengths", None, &enabled()).hits.is_empty());
+    }
+
+    #[test]
+    fn caps_need_enough_letters() {
+        let verdict = score("URGENT", "THIS IS A VERY LOUD MESSAGE INDEED", None, &enabled());
+        assert_eq!(rules(&verdict), [(SpamRule::Caps, 2.0)]);
+        assert!(score("OK", "YES", None, &enabled()).hits.is_empty());
+    }
+
+    #[test]
+    fn repeated_characters_over_the_limit() {
+        let verdict = score("Hi", "call me!!!!!!", None, &enabled());
+        assert_eq!(rules(&verdict), [(SpamRule::RepeatedChars, 1.0)]);
+        assert!(score("Hi", "call me!!!!!", None, &enabled()).hits.is_empty());
+    }
+
+    #[test]
+    fn thresholds_pick_the_action_and_zero_disables_one() {
+        let settings = SpamSettings {
+            blocked_words: Some(vec!["one".into(), "two".into(), "three".into(), "four".into()]),
+            ..enabled()
+        };
+        let action = |message: &str, settings: &SpamSettings| score("Hi", message, None, settings).action;
+        assert_eq!(action("zero", &settings), SpamAction::Accept);
+        assert_eq!(action("one", &settings), SpamAction::Tag);
+        assert_eq!(action("one two", &settings), SpamAction::Quarantine);
+        assert_eq!(action("one two three", &settings), SpamAction::Quarantine);
+        assert_eq!(action("one two three four", &settings), SpamAction::Reject);
+
+        let no_tag_or_reject = SpamSettings {
+            tag_threshold: 0.0,
+            reject_threshold: 0.0,
+            ..settings
+        };
+        assert_eq!(action("one", &no_tag_or_reject), SpamAction::Accept);
+        assert_eq!(action("one two three four", &no_tag_or_reject), SpamAction::Quarantine);
+    }
+}
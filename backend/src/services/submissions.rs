@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+use crate::config::CONFIG;
+use crate::services::channels::ChannelFailure;
+use crate::types::LeadContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionOutcome {
+    Sent,
+    /// At least one recipient could not be reached.
+    Failed,
+    Quarantined,
+    /// Dropped silently by a shadow-banned or bot rule.
+    Discarded,
+    /// Rejected by a moderator from the quarantine.
+    Rejected,
+    /// Dropped unreviewed to make room in a full quarantine.
+    Evicted,
+}
+
+impl SubmissionOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+            Self::Quarantined => "quarantined",
+            Self::Discarded => "discarded",
+            Self::Rejected => "rejected",
+            Self::Evicted => "evicted",
+        }
+    }
+}
+
+/// A submission that got past validation. Quarantined ones share the
+/// quarantine id so moderation updates the same record.
+#[derive(Debug, Clone)]
+pub struct SubmissionRecord {
+    pub id: String,
+    pub received_at: i64,
+    pub ip: String,
+    pub api_key: Option<String>,
+    pub email: Option<String>,
+    pub subject: String,
+    pub recipients: Vec<String>,
+    pub outcome: SubmissionOutcome,
+    /// Set once the CRM has created a lead for it.
+    pub crm_lead_id: Option<String>,
+    pub context: LeadContext,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeliveryFailure {
+    pub at: i64,
+    /// `None` for mail not tied to a submission, such as the quarantine digest.
+    pub submission_id: Option<String>,
+    pub recipient: String,
+    pub error: String,
+}
+
+/// The last `admin_history_size` submissions and delivery failures, newest
+/// last, for the admin API. Kept in memory only.
+#[derive(Debug, Default)]
+pub struct SubmissionLog {
+    submissions: VecDeque<SubmissionRecord>,
+    failures: VecDeque<DeliveryFailure>,
+}
+
+fn push_bounded<T>(queue: &mut VecDeque<T>, item: T) {
+    let cap = CONFIG.admin_history_size as usize;
+    if cap == 0 {
+        return;
+    }
+    while queue.len() >= cap {
+        queue.pop_front();
+    }
+    queue.push_back(item);
+}
+
+impl SubmissionLog {
+    pub fn record(&mut self, record: SubmissionRecord) {
+        push_bounded(&mut self.submissions, record);
+    }
+
+    pub fn set_outcome(&mut self, id: &str, outcome: SubmissionOutcome) {
+        if let Some(record) = self.submissions.iter_mut().rev().find(|r| r.id == id) {
+            record.outcome = outcome;
+        }
+    }
+
+    pub fn set_crm_lead(&mut self, id: &str, lead: String) {
+        if let Some(record) = self.submissions.iter_mut().rev().find(|r| r.id == id) {
+            record.crm_lead_id = Some(lead);
+        }
+    }
+
+    /// Records every `(recipient, error)` pair returned by `deliver`.
+    pub fn record_failures(&mut self, submission_id: Option<&str>, failures: &[(String, String)], now: i64) {
+        for (recipient, error) in failures {
+            push_bounded(
+                &mut self.failures,
+                DeliveryFailure {
+                    at: now,
+                    submission_id: submission_id.map(str::to_string),
+                    recipient: recipient.clone(),
+                    error: error.clone(),
+                },
+            );
+        }
+    }
+
+    /// Records channels that could not be notified, with `channel:<id>` in
+    /// place of the recipient.
+    pub fn record_channel_failures(&mut self, submission_id: &str, failures: &[ChannelFailure], now: i64) {
+        let failures: Vec<(String, String)> = failures
+            .iter()
+            .map(|f| (format!("channel:{}", f.channel), f.error.clone()))
+            .collect();
+        self.record_failures(Some(submission_id), &failures, now);
+    }
+
+    /// Newest first.
+    pub fn recent(&self, limit: usize) -> impl Iterator<Item = &SubmissionRecord> {
+        self.submissions.iter().rev().take(limit)
+    }
+
+    /// Newest first.
+    pub fn failures(&self, limit: usize) -> impl Iterator<Item = &DeliveryFailure> {
+        self.failures.iter().rev().take(limit)
+    }
+}
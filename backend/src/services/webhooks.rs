@@ -0,0 +1,337 @@
+use axum::http::{HeaderName, header::CONTENT_TYPE};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::{collections::VecDeque, time::Duration};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, WebhookEvent};
+use crate::state::AppState;
+use crate::utils::crypto::{hmac_sha256, random_hex};
+
+pub const SIGNATURE: HeaderName = HeaderName::from_static("x-webhook-signature");
+pub const EVENT: HeaderName = HeaderName::from_static("x-webhook-event");
+pub const DELIVERY: HeaderName = HeaderName::from_static("x-webhook-delivery");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Not answered with 2xx yet, another attempt is scheduled.
+    Pending,
+    Delivered,
+    /// Every attempt failed.
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    pub at: i64,
+    /// HTTP status of the answer, `None` if there was none.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook: String,
+    pub event: WebhookEvent,
+    pub submission_id: Option<String>,
+    pub status: DeliveryStatus,
+    pub attempts: Vec<Attempt>,
+}
+
+/// The most recent deliveries and their attempts, for the admin API.
+#[derive(Debug)]
+pub struct WebhookLog {
+    capacity: usize,
+    deliveries: VecDeque<WebhookDelivery>,
+}
+
+impl WebhookLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            deliveries: VecDeque::new(),
+        }
+    }
+
+    fn start(&mut self, delivery: WebhookDelivery) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.deliveries.len() >= self.capacity {
+            self.deliveries.pop_front();
+        }
+        self.deliveries.push_back(delivery);
+    }
+
+    fn attempt(&mut self, id: &str, attempt: Attempt, status: DeliveryStatus) {
+        if let Some(delivery) = self.deliveries.iter_mut().rev().find(|d| d.id == id) {
+            delivery.attempts.push(attempt);
+            delivery.status = status;
+        }
+    }
+
+    /// Newest first.
+    pub fn recent(&self, limit: usize) -> impl Iterator<Item = &WebhookDelivery> {
+        self.deliveries.iter().rev().take(limit)
+    }
+}
+
+/// `X-Webhook-Signature` value: `t=<unix seconds>,v1=<hex HMAC-SHA256 of
+/// "<t>.<body>">`, the same scheme signed API requests use.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut signed = format!("{timestamp}.").into_bytes();
+    signed.extend_from_slice(body);
+    let mac: String = hmac_sha256(secret.as_bytes(), &signed)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("t={timestamp},v1={mac}")
+}
+
+pub struct WebhookDispatch<'a> {
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubles after each failure.
+    pub retry_base: Duration,
+}
+
+impl WebhookDispatch<'_> {
+    /// Posts `body` until the receiver answers 2xx or the attempts run out,
+    /// recording every try in `log`. Each attempt is signed afresh.
+    pub async fn run(
+        &self,
+        client: &reqwest::Client,
+        log: &Mutex<WebhookLog>,
+        delivery_id: &str,
+        event: WebhookEvent,
+        body: &[u8],
+    ) -> DeliveryStatus {
+        let mut wait = self.retry_base;
+        for n in 1..=self.max_attempts {
+            let at = Utc::now().timestamp();
+            let result = client
+                .post(self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(EVENT, event.as_str())
+                .header(DELIVERY, delivery_id)
+                .header(SIGNATURE, sign(self.secret, at, body))
+                .body(body.to_vec())
+                .send()
+                .await;
+            let attempt = match result {
+                Ok(answer) if answer.status().is_success() => Attempt {
+                    at,
+                    status: Some(answer.status().as_u16()),
+                    error: None,
+                },
+                Ok(answer) => Attempt {
+                    at,
+                    status: Some(answer.status().as_u16()),
+                    error: Some(format!("receiver answered {}", answer.status())),
+                },
+                Err(e) => Attempt {
+                    at,
+                    status: None,
+                    error: Some(e.to_string()),
+                },
+            };
+            let status = if attempt.error.is_none() {
+                DeliveryStatus::Delivered
+            } else if n == self.max_attempts {
+                DeliveryStatus::Failed
+            } else {
+                DeliveryStatus::Pending
+            };
+            log.lock().await.attempt(delivery_id, attempt, status);
+            if status != DeliveryStatus::Pending {
+                return status;
+            }
+            tokio::time::sleep(wait).await;
+            wait = wait.saturating_mul(2);
+        }
+        DeliveryStatus::Failed
+    }
+}
+
+/// Sends `event` to every subscribed webhook in the background.
+pub async fn emit(state: &AppState, event: WebhookEvent, submission_id: Option<&str>, data: Value) {
+    let now = Utc::now().timestamp();
+    for hook in CONFIG.webhooks.iter().filter(|h| h.wants(event)) {
+        let id = random_hex(8);
+        let body = json!({
+            "id": id,
+            "event": event.as_str(),
+            "created_at": DateTime::from_timestamp(now, 0).unwrap_or_default().to_rfc3339(),
+            "data": data,
+        })
+        .to_string();
+        state.webhooks.lock().await.start(WebhookDelivery {
+            id: id.clone(),
+            webhook: hook.id.clone(),
+            event,
+            submission_id: submission_id.map(str::to_string),
+            status: DeliveryStatus::Pending,
+            attempts: Vec::new(),
+        });
+
+        let client = state.http.clone();
+        let log = state.webhooks.clone();
+        tokio::spawn(async move {
+            let dispatch = WebhookDispatch {
+                url: &hook.url,
+                secret: &hook.secret,
+                max_attempts: CONFIG.webhook_max_attempts,
+                retry_base: Duration::from_secs(CONFIG.webhook_retry_base_seconds as u64),
+            };
+            match dispatch.run(&client, &log, &id, event, body.as_bytes()).await {
+                DeliveryStatus::Delivered => info!(webhook = %hook.id, delivery = %id, event = event.as_str(), "webhook delivered"),
+                _ => warn!(webhook = %hook.id, delivery = %id, event = event.as_str(), "webhook delivery failed"),
+            }
+        });
+    }
+}
+
+/// `email.sent` for the recipients that were reached and `email.failed` for
+/// the `(recipient, error)` pairs `deliver` returned.
+pub async fn emit_delivery(
+    state: &AppState,
+    submission_id: &str,
+    recipients: &[String],
+    failures: &[(String, String)],
+) {
+    let sent: Vec<&String> = recipients
+        .iter()
+        .filter(|r| !failures.iter().any(|(f, _)| f == *r))
+        .collect();
+    if !sent.is_empty() {
+        let data = json!({ "submission_id": submission_id, "recipients": sent });
+        emit(state, WebhookEvent::EmailSent, Some(submission_id), data).await;
+    }
+    if !failures.is_empty() {
+        let failures: Vec<Value> = failures
+            .iter()
+            .map(|(recipient, error)| json!({ "recipient": recipient, "error": error }))
+            .collect();
+        let data = json!({ "submission_id": submission_id, "failures": failures });
+        emit(state, WebhookEvent::EmailFailed, Some(submission_id), data).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Bytes, extract::State, http::{HeaderMap, StatusCode}, routing::post};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::utils::crypto::{hex_decode, verify_hmac_sha256};
+
+    const SECRET: &str = "webhook-test-secret";
+
+    /// Local stand-in receiver: answers 500 to the first `fail_first`
+    /// requests and 204 afterwards, and checks every signature.
+    async fn stand_in(fail_first: usize) -> (String, Arc<AtomicUsize>) {
+        async fn receive(
+            State((fail_first, hits)): State<(usize, Arc<AtomicUsize>)>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> StatusCode {
+            let signature = headers[&SIGNATURE].to_str().unwrap();
+            let (t, v1) = signature.split_once(",v1=").unwrap();
+            let mut signed = format!("{}.", t.trim_start_matches("t=")).into_bytes();
+            signed.extend_from_slice(&body);
+            assert!(verify_hmac_sha256(SECRET.as_bytes(), &signed, &hex_decode(v1).unwrap()));
+            assert_eq!(headers[&EVENT], "email.failed");
+            assert_eq!(headers[&DELIVERY], "d1");
+
+            if hits.fetch_add(1, Ordering::SeqCst) < fail_first {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::NO_CONTENT
+            }
+        }
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state((fail_first, hits.clone()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/hook"), hits)
+    }
+
+    async fn run(url: &str, max_attempts: u32) -> (DeliveryStatus, WebhookDelivery) {
+        let log = Mutex::new(WebhookLog::new(10));
+        log.lock().await.start(WebhookDelivery {
+            id: "d1".into(),
+            webhook: "crm".into(),
+            event: WebhookEvent::EmailFailed,
+            submission_id: Some("s1".into()),
+            status: DeliveryStatus::Pending,
+            attempts: Vec::new(),
+        });
+        let status = WebhookDispatch {
+            url,
+            secret: SECRET,
+            max_attempts,
+            retry_base: Duration::from_millis(10),
+        }
+        .run(&reqwest::Client::new(), &log, "d1", WebhookEvent::EmailFailed, br#"{"id":"d1"}"#)
+        .await;
+        let delivery = log.lock().await.recent(1).next().cloned().unwrap();
+        (status, delivery)
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_receiver_accepts() {
+        let (url, hits) = stand_in(2).await;
+        let (status, delivery) = run(&url, 5).await;
+        assert_eq!(status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        let codes: Vec<_> = delivery.attempts.iter().map(|a| a.status).collect();
+        assert_eq!(codes, [Some(500), Some(500), Some(204)]);
+        assert!(delivery.attempts[2].error.is_none());
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (url, hits) = stand_in(usize::MAX).await;
+        let (status, delivery) = run(&url, 3).await;
+        assert_eq!(status, DeliveryStatus::Failed);
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        assert_eq!(delivery.attempts.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn unreachable_receiver_is_recorded_without_status() {
+        let (status, delivery) = run("http://127.0.0.1:9/hook", 2).await;
+        assert_eq!(status, DeliveryStatus::Failed);
+        assert!(delivery.attempts.iter().all(|a| a.status.is_none() && a.error.is_some()));
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let a = sign(SECRET, 1_700_000_000, b"{}");
+        assert!(a.starts_with("t=1700000000,v1="));
+        assert_ne!(a, sign(SECRET, 1_700_000_001, b"{}"));
+        assert_ne!(a, sign(SECRET, 1_700_000_000, b"[]"));
+    }
+}
@@ -1,7 +1,31 @@
-use std::collections::HashMap;
-use tokio::sync::Mutex;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::services::{
+    FloodControl, NonceStore, bans::BanList, idempotency::IdempotencyStore, ip_filter::IpFilter,
+    quarantine::QuarantineQueue, submissions::SubmissionLog, webhooks::WebhookLog,
+};
 
 pub struct AppState {
-    // NOTE: IP -> timestamps
-    pub flood_control: Mutex<HashMap<String, Vec<i64>>>,
+    // NOTE: quota key -> timestamps, one map per quota layer
+    pub flood_control: Mutex<FloodControl>,
+    // NOTE: nonces of form tokens already submitted
+    pub form_tokens: Mutex<NonceStore>,
+    // NOTE: nonces of proof-of-work challenges already solved
+    pub pow_challenges: Mutex<NonceStore>,
+    // NOTE: signatures of signed API requests already accepted
+    pub signatures: Mutex<NonceStore>,
+    // NOTE: shared client for outbound calls (CAPTCHA siteverify, ...)
+    pub http: reqwest::Client,
+    // NOTE: swapped wholesale on reload
+    pub ip_filter: RwLock<IpFilter>,
+    pub bans: Mutex<BanList>,
+    pub quarantine: Mutex<QuarantineQueue>,
+    // NOTE: Idempotency-Key answers and recent content hashes
+    pub idempotency: Mutex<IdempotencyStore>,
+    // NOTE: recent submissions and delivery failures for the admin API
+    // NOTE: shared with the background tasks exporting CRM leads
+    pub submissions: Arc<Mutex<SubmissionLog>>,
+    // NOTE: shared with the background tasks delivering webhooks
+    pub webhooks: Arc<Mutex<WebhookLog>>,
 }
@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    Turnstile,
+    Hcaptcha,
+    Recaptcha,
+    /// Yandex SmartCaptcha
+    Smartcaptcha,
+}
+
+impl CaptchaProvider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Turnstile => "turnstile",
+            Self::Hcaptcha => "hcaptcha",
+            Self::Recaptcha => "recaptcha",
+            Self::Smartcaptcha => "smartcaptcha",
+        }
+    }
+
+    pub fn default_verify_url(self) -> &'static str {
+        match self {
+            Self::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            Self::Hcaptcha => "https://api.hcaptcha.com/siteverify",
+            Self::Recaptcha => "https://www.google.com/recaptcha/api/siteverify",
+            Self::Smartcaptcha => "https://smartcaptcha.yandexcloud.net/validate",
+        }
+    }
+
+    /// Field name the provider's widget uses when it injects the token into
+    /// a plain HTML form.
+    pub fn widget_field(self) -> &'static str {
+        match self {
+            Self::Turnstile => "cf-turnstile-response",
+            Self::Hcaptcha => "h-captcha-response",
+            Self::Recaptcha => "g-recaptcha-response",
+            Self::Smartcaptcha => "smart-token",
+        }
+    }
+}
@@ -1,5 +1,11 @@
 pub mod logger;
+pub mod captcha;
+pub mod locale;
 mod responses;
-pub use responses::{ApiError, ApiMessage, HealthResponse};
+pub use responses::{
+    ApiError, ApiMessage, BanEntry, ConfigView, DeliveryFailureEntry, ErrorCode, FieldError, FormTokenResponse, HealthResponse,
+    PowChallengeResponse, ProblemDetails, QuarantineEntry, QuotaUsage, RateLimitState, SubmissionEntry, SubmissionResponse,
+    SubmissionStatus, WebhookAttemptEntry, WebhookDeliveryEntry,
+};
 mod requests;
-pub use requests::RequestPayload;
+pub use requests::{Contact, FieldKind, FormField, HistoryQuery, LeadContext, Metadata, PowSolution, RequestPayload, Submission, Utm};
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+    /// Newest entries to return, at most `APP__ADMIN_HISTORY_SIZE`.
+    #[param(example = 50)]
+    pub limit: Option<usize>,
+}
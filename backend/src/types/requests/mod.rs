@@ -1,2 +1,8 @@
 mod payload;
 pub use payload::RequestPayload;
+mod submission;
+pub use submission::{Contact, FieldKind, FormField, LeadContext, Metadata, Submission, Utm};
+mod pow;
+pub use pow::PowSolution;
+mod admin;
+pub use admin::HistoryQuery;
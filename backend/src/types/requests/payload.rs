@@ -1,11 +1,32 @@
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use utoipa::ToSchema;
 
+use super::PowSolution;
+
 #[allow(dead_code)]
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct RequestPayload {
     pub subject: String,
     pub message: String,
+    /// Submitter's contact address, counted against the per-email quota.
+    #[schema(example = "client@example.com")]
+    pub email: Option<String>,
     #[schema(example = json!(["team@example.com","me@example.com"]))]
     pub recipients: Option<Vec<String>>,
+    /// Token from `GET /api/v1/form-token`, required when form tokens are enabled.
+    pub form_token: Option<String>,
+    /// CAPTCHA widget response, required when a CAPTCHA provider is configured.
+    pub captcha_token: Option<String>,
+    /// Solved puzzle, required when proof of work is enabled.
+    pub pow: Option<PowSolution>,
+    /// Language of the answer and the auto-reply (`en`, `ru`, or a tag like
+    /// `ru-RU`); overrides `Accept-Language`, unsupported values are ignored.
+    #[schema(example = "ru")]
+    pub locale: Option<String>,
+    /// Any other fields, e.g. the configured honeypot.
+    #[serde(flatten)]
+    #[schema(ignore)]
+    pub extra: HashMap<String, Value>,
 }
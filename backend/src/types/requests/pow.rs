@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PowSolution {
+    /// Challenge string from `GET /api/v1/pow-challenge`, unchanged.
+    pub challenge: String,
+    /// Any string such that `SHA-256(challenge + solution)` has `difficulty` leading zero bits.
+    #[schema(example = "48213")]
+    pub solution: String,
+}
@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use super::{PowSolution, RequestPayload};
+use crate::utils::null_as_default;
+
+/// Who to answer.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct Contact {
+    #[schema(example = "Anna Petrova")]
+    pub name: Option<String>,
+    /// Counted against the per-email quota and used for the auto-reply.
+    #[schema(example = "client@example.com")]
+    pub email: Option<String>,
+    #[schema(example = "+7 900 000-00-00")]
+    pub phone: Option<String>,
+    pub company: Option<String>,
+}
+
+/// UTM tags of the landing page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct Utm {
+    #[schema(example = "yandex")]
+    pub source: Option<String>,
+    #[schema(example = "cpc")]
+    pub medium: Option<String>,
+    #[schema(example = "spring_sale")]
+    pub campaign: Option<String>,
+    pub term: Option<String>,
+    pub content: Option<String>,
+}
+
+/// Where the form was filled in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct Metadata {
+    #[schema(example = "https://example.com/contacts?utm_source=yandex")]
+    pub page_url: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub utm: Utm,
+    /// Language of the answer and the auto-reply; overrides `Accept-Language`.
+    #[schema(example = "ru")]
+    pub locale: Option<String>,
+}
+
+impl Utm {
+    /// Tag names, `utm_<name>` as query parameters and form fields.
+    pub const NAMES: [&'static str; 5] = ["source", "medium", "campaign", "term", "content"];
+
+    pub fn tags(&self) -> [(&'static str, &Option<String>); 5] {
+        [
+            ("source", &self.source),
+            ("medium", &self.medium),
+            ("campaign", &self.campaign),
+            ("term", &self.term),
+            ("content", &self.content),
+        ]
+    }
+
+    pub fn tag_mut(&mut self, name: &str) -> Option<&mut Option<String>> {
+        match name {
+            "source" => Some(&mut self.source),
+            "medium" => Some(&mut self.medium),
+            "campaign" => Some(&mut self.campaign),
+            "term" => Some(&mut self.term),
+            "content" => Some(&mut self.content),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags().iter().all(|(_, value)| value.is_none())
+    }
+}
+
+/// Where a lead came from: captured on the server, validated, cut to
+/// `APP__LEAD_CONTEXT__MAX_LENGTH` and left empty where capture is off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct LeadContext {
+    #[schema(example = "https://example.com/contacts?utm_source=yandex")]
+    pub page_url: Option<String>,
+    #[schema(example = "https://yandex.ru/")]
+    pub referer: Option<String>,
+    #[serde(default)]
+    pub utm: Utm,
+    #[schema(example = "Mozilla/5.0 (Windows NT 10.0; Win64; x64)")]
+    pub user_agent: Option<String>,
+}
+
+impl LeadContext {
+    /// `Label: value` lines for the staff email; labels are catalog ids.
+    pub fn lines(&self, labels: impl Fn(&str) -> String) -> Vec<String> {
+        let utm = self
+            .utm
+            .tags()
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("{name}={}", value.as_deref()?)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let lines = [
+            ("ContextPage", self.page_url.clone()),
+            ("ContextReferer", self.referer.clone()),
+            ("ContextUtm", (!utm.is_empty()).then_some(utm)),
+            ("ContextUserAgent", self.user_agent.clone()),
+        ];
+        lines
+            .into_iter()
+            .filter_map(|(label, value)| Some(format!("{}: {}", labels(label), value?)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    Text,
+    Number,
+    Boolean,
+    Email,
+    Phone,
+    /// `YYYY-MM-DD`.
+    Date,
+    /// One or more selected options.
+    Choice,
+}
+
+impl FieldKind {
+    pub const ALL: [FieldKind; 7] = [
+        FieldKind::Text,
+        FieldKind::Number,
+        FieldKind::Boolean,
+        FieldKind::Email,
+        FieldKind::Phone,
+        FieldKind::Date,
+        FieldKind::Choice,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::Date => "date",
+            Self::Choice => "choice",
+        }
+    }
+}
+
+/// A form input beyond the fixed ones; `value` must match `type`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FormField {
+    /// Key in webhook events and CRM lead placeholders.
+    #[schema(example = "budget")]
+    pub name: String,
+    /// Shown in the email instead of `name`.
+    #[schema(example = "Budget")]
+    pub label: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: FieldKind,
+    /// String, number, boolean or, for `choice`, an array of strings.
+    #[schema(value_type = Object, example = 150000)]
+    pub value: Value,
+}
+
+impl FormField {
+    /// The value as it reads in an email.
+    pub fn display(&self) -> String {
+        match &self.value {
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(", "),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// `POST /api/v2/request`, and what every v1 submission is mapped onto.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct Submission {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub contact: Contact,
+    pub subject: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub message: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub fields: Vec<FormField>,
+    #[schema(example = json!(["team@example.com"]))]
+    pub recipients: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub metadata: Metadata,
+    /// Token from `GET /api/v1/form-token`, required when form tokens are enabled.
+    pub form_token: Option<String>,
+    /// CAPTCHA widget response, required when a CAPTCHA provider is configured.
+    pub captcha_token: Option<String>,
+    /// Solved puzzle, required when proof of work is enabled.
+    pub pow: Option<PowSolution>,
+    /// Any other fields, e.g. the configured honeypot.
+    #[serde(flatten)]
+    #[schema(ignore)]
+    pub extra: HashMap<String, Value>,
+    /// Filled in by the server from `metadata` and the request headers.
+    #[serde(skip)]
+    #[schema(ignore)]
+    pub context: LeadContext,
+}
+
+impl From<RequestPayload> for Submission {
+    /// `page_url` and `utm_*` among the other fields become the metadata,
+    /// as hidden inputs of a v1 form send them.
+    fn from(mut payload: RequestPayload) -> Self {
+        let mut take = |name: &str| match payload.extra.remove(name) {
+            Some(Value::String(value)) => Some(value),
+            Some(other) => {
+                payload.extra.insert(name.to_string(), other);
+                None
+            }
+            None => None,
+        };
+        let page_url = take("page_url");
+        let mut utm = Utm::default();
+        for name in Utm::NAMES {
+            if let Some(tag) = utm.tag_mut(name) {
+                *tag = take(&format!("utm_{name}"));
+            }
+        }
+        Self {
+            contact: Contact {
+                email: payload.email,
+                ..Contact::default()
+            },
+            subject: payload.subject,
+            message: payload.message,
+            fields: Vec::new(),
+            recipients: payload.recipients,
+            metadata: Metadata {
+                page_url,
+                utm,
+                locale: payload.locale,
+            },
+            form_token: payload.form_token,
+            captcha_token: payload.captcha_token,
+            pow: payload.pow,
+            extra: payload.extra,
+            context: LeadContext::default(),
+        }
+    }
+}
+
+impl Submission {
+    pub fn email(&self) -> Option<&str> {
+        self.contact.email.as_deref()
+    }
+
+    /// What the submitter wrote: the message, then the other contact details
+    /// and the typed fields as `Label: value` lines. A v1 submission is its
+    /// message.
+    pub fn body(&self, labels: impl Fn(&str) -> String) -> String {
+        let contact = [
+            ("ContactName", &self.contact.name),
+            ("ContactPhone", &self.contact.phone),
+            ("ContactCompany", &self.contact.company),
+        ];
+        let lines: Vec<String> = contact
+            .into_iter()
+            .filter_map(|(label, value)| Some(format!("{}: {}", labels(label), value.as_deref()?)))
+            .chain(
+                self.fields
+                    .iter()
+                    .map(|f| format!("{}: {}", f.label.as_deref().unwrap_or(&f.name), f.display())),
+            )
+            .collect();
+        match (self.message.is_empty(), lines.is_empty()) {
+            (_, true) => self.message.clone(),
+            (true, false) => lines.join("\n"),
+            (false, false) => format!("{}\n\n{}", self.message, lines.join("\n")),
+        }
+    }
+
+    /// The staff email: `body`, then the lead context.
+    pub fn staff_body(&self, labels: impl Fn(&str) -> String) -> String {
+        let body = self.body(&labels);
+        let context = self.context.lines(labels);
+        match (body.is_empty(), context.is_empty()) {
+            (_, true) => body,
+            (true, false) => context.join("\n"),
+            (false, false) => format!("{body}\n\n{}", context.join("\n")),
+        }
+    }
+
+    /// Values a CRM lead template can use besides the notification ones:
+    /// other submitted fields, the contact block, typed fields by `name`
+    /// and the lead context (`page_url`, `referer`, `user_agent`, `utm_*`).
+    pub fn lead_fields(&self) -> HashMap<String, Value> {
+        let mut fields = self.extra.clone();
+        let contact = [
+            ("name", &self.contact.name),
+            ("phone", &self.contact.phone),
+            ("company", &self.contact.company),
+            ("page_url", &self.context.page_url),
+            ("referer", &self.context.referer),
+            ("user_agent", &self.context.user_agent),
+        ];
+        for (name, value) in contact {
+            if let Some(value) = value {
+                fields.insert(name.to_string(), Value::String(value.clone()));
+            }
+        }
+        for (name, value) in self.context.utm.tags() {
+            if let Some(value) = value {
+                fields.insert(format!("utm_{name}"), Value::String(value.clone()));
+            }
+        }
+        for field in &self.fields {
+            fields.insert(field.name.clone(), field.value.clone());
+        }
+        fields
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::types::LeadContext;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmissionEntry {
+    /// Equals the quarantine id for quarantined submissions.
+    pub id: String,
+    /// RFC 3339 timestamp.
+    pub received_at: String,
+    pub ip: String,
+    /// Id of the API key the submission was authenticated with.
+    pub api_key: Option<String>,
+    pub email: Option<String>,
+    pub subject: String,
+    pub recipients: Vec<String>,
+    /// `sent`, `failed`, `quarantined`, `discarded` or `rejected`.
+    #[schema(example = "sent")]
+    pub outcome: String,
+    /// Id of the lead the CRM export created.
+    pub crm_lead_id: Option<String>,
+    /// Where the lead came from.
+    pub context: LeadContext,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeliveryFailureEntry {
+    /// RFC 3339 timestamp.
+    pub at: String,
+    /// `null` for mail not tied to a submission, e.g. the quarantine digest.
+    pub submission_id: Option<String>,
+    pub recipient: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuotaUsage {
+    /// IP, email, recipient or API key id; `global` for the daily cap.
+    pub key: String,
+    pub hits: usize,
+    /// `0` means the quota is disabled.
+    pub limit: u32,
+    pub window_seconds: i64,
+    /// Seconds until the oldest counted hit leaves the window.
+    pub reset_seconds: i64,
+}
+
+/// Live counters of every quota layer, busiest keys first.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RateLimitState {
+    pub enabled: bool,
+    pub ip: Vec<QuotaUsage>,
+    pub email: Vec<QuotaUsage>,
+    pub recipient: Vec<QuotaUsage>,
+    pub api_key: Vec<QuotaUsage>,
+    pub global_daily: Vec<QuotaUsage>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigView {
+    /// Effective settings with secrets masked, as written to the debug log.
+    pub config: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookAttemptEntry {
+    /// RFC 3339 timestamp.
+    pub at: String,
+    /// HTTP status of the receiver's answer; `null` if it could not be reached.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDeliveryEntry {
+    /// Also sent as `X-Webhook-Delivery` and the body's `id`.
+    pub id: String,
+    /// Id of the `[[webhooks]]` subscription.
+    pub webhook: String,
+    #[schema(example = "submission.received")]
+    pub event: String,
+    pub submission_id: Option<String>,
+    /// `pending` (retry scheduled), `delivered` or `failed`.
+    #[schema(example = "delivered")]
+    pub status: String,
+    pub attempts: Vec<WebhookAttemptEntry>,
+}
@@ -1,8 +1,215 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+/// Stable machine-readable error codes. Renaming one is a breaking change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ErrorCode {
+    InvalidJson,
+    InvalidForm,
+    ValidationFailed,
+    FieldRequired,
+    FieldInvalidType,
+    InvalidIdempotencyKey,
+    RequestInProgress,
+    IdempotencyKeyReused,
+    InvalidApiKey,
+    SignatureMalformed,
+    SignatureInvalid,
+    SignatureExpired,
+    SignatureReplayed,
+    FormTokenMissing,
+    FormTokenInvalid,
+    FormTokenExpired,
+    FormTokenReused,
+    FormTokenTooFast,
+    PowMissing,
+    PowInvalid,
+    PowExpired,
+    PowReused,
+    PowInsufficient,
+    CaptchaMissing,
+    CaptchaFailed,
+    CaptchaScoreTooLow,
+    CaptchaUnavailable,
+    IpQuotaExceeded,
+    EmailQuotaExceeded,
+    RecipientQuotaExceeded,
+    DailyQuotaExceeded,
+    ApiKeyQuotaExceeded,
+    SubjectTooLong,
+    MessageTooLong,
+    FieldTooLong,
+    BodyTooLong,
+    InvalidEmail,
+    SpamRejected,
+    RecipientNotAllowed,
+    NoRecipient,
+    EmailSendFailed,
+    NotificationFailed,
+    InboundUnauthorized,
+    InboundPayloadInvalid,
+    IpBlocked,
+    IpBanned,
+    NotFound,
+    Unauthorized,
+    FormTokenDisabled,
+    PowDisabled,
+    BanNotFound,
+    QuarantineItemNotFound,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidJson => "InvalidJson",
+            Self::InvalidForm => "InvalidForm",
+            Self::ValidationFailed => "ValidationFailed",
+            Self::FieldRequired => "FieldRequired",
+            Self::FieldInvalidType => "FieldInvalidType",
+            Self::InvalidIdempotencyKey => "InvalidIdempotencyKey",
+            Self::RequestInProgress => "RequestInProgress",
+            Self::IdempotencyKeyReused => "IdempotencyKeyReused",
+            Self::InvalidApiKey => "InvalidApiKey",
+            Self::SignatureMalformed => "SignatureMalformed",
+            Self::SignatureInvalid => "SignatureInvalid",
+            Self::SignatureExpired => "SignatureExpired",
+            Self::SignatureReplayed => "SignatureReplayed",
+            Self::FormTokenMissing => "FormTokenMissing",
+            Self::FormTokenInvalid => "FormTokenInvalid",
+            Self::FormTokenExpired => "FormTokenExpired",
+            Self::FormTokenReused => "FormTokenReused",
+            Self::FormTokenTooFast => "FormTokenTooFast",
+            Self::PowMissing => "PowMissing",
+            Self::PowInvalid => "PowInvalid",
+            Self::PowExpired => "PowExpired",
+            Self::PowReused => "PowReused",
+            Self::PowInsufficient => "PowInsufficient",
+            Self::CaptchaMissing => "CaptchaMissing",
+            Self::CaptchaFailed => "CaptchaFailed",
+            Self::CaptchaScoreTooLow => "CaptchaScoreTooLow",
+            Self::CaptchaUnavailable => "CaptchaUnavailable",
+            Self::IpQuotaExceeded => "IpQuotaExceeded",
+            Self::EmailQuotaExceeded => "EmailQuotaExceeded",
+            Self::RecipientQuotaExceeded => "RecipientQuotaExceeded",
+            Self::DailyQuotaExceeded => "DailyQuotaExceeded",
+            Self::ApiKeyQuotaExceeded => "ApiKeyQuotaExceeded",
+            Self::SubjectTooLong => "SubjectTooLong",
+            Self::MessageTooLong => "MessageTooLong",
+            Self::FieldTooLong => "FieldTooLong",
+            Self::BodyTooLong => "BodyTooLong",
+            Self::InvalidEmail => "InvalidEmail",
+            Self::SpamRejected => "SpamRejected",
+            Self::RecipientNotAllowed => "RecipientNotAllowed",
+            Self::NoRecipient => "NoRecipient",
+            Self::EmailSendFailed => "EmailSendFailed",
+            Self::NotificationFailed => "NotificationFailed",
+            Self::InboundUnauthorized => "InboundUnauthorized",
+            Self::InboundPayloadInvalid => "InboundPayloadInvalid",
+            Self::IpBlocked => "IpBlocked",
+            Self::IpBanned => "IpBanned",
+            Self::NotFound => "NotFound",
+            Self::Unauthorized => "Unauthorized",
+            Self::FormTokenDisabled => "FormTokenDisabled",
+            Self::PowDisabled => "PowDisabled",
+            Self::BanNotFound => "BanNotFound",
+            Self::QuarantineItemNotFound => "QuarantineItemNotFound",
+        }
+    }
+
+    /// Short summary of the problem type, the `title` of a problem document.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::InvalidJson => "Request body is not valid JSON for this endpoint",
+            Self::InvalidForm => "Form post could not be read",
+            Self::ValidationFailed => "One or more fields are invalid",
+            Self::FieldRequired => "Field required",
+            Self::FieldInvalidType => "Field has the wrong type",
+            Self::InvalidIdempotencyKey => "Idempotency-Key header is malformed",
+            Self::RequestInProgress => "Identical request still in progress",
+            Self::IdempotencyKeyReused => "Idempotency-Key reused with another payload",
+            Self::InvalidApiKey => "API key is not valid",
+            Self::SignatureMalformed => "Request signature headers are malformed",
+            Self::SignatureInvalid => "Request signature does not match",
+            Self::SignatureExpired => "Request signature timestamp out of range",
+            Self::SignatureReplayed => "Signed request already received",
+            Self::FormTokenMissing => "Form token missing",
+            Self::FormTokenInvalid => "Form token invalid",
+            Self::FormTokenExpired => "Form token expired",
+            Self::FormTokenReused => "Form token already used",
+            Self::FormTokenTooFast => "Form submitted too quickly",
+            Self::PowMissing => "Proof of work missing",
+            Self::PowInvalid => "Proof of work challenge invalid",
+            Self::PowExpired => "Proof of work challenge expired",
+            Self::PowReused => "Proof of work challenge already used",
+            Self::PowInsufficient => "Proof of work below the difficulty",
+            Self::CaptchaMissing => "CAPTCHA token missing",
+            Self::CaptchaFailed => "CAPTCHA verification failed",
+            Self::CaptchaScoreTooLow => "CAPTCHA score too low",
+            Self::CaptchaUnavailable => "CAPTCHA verification unavailable",
+            Self::IpQuotaExceeded => "Per-IP quota exceeded",
+            Self::EmailQuotaExceeded => "Per-email quota exceeded",
+            Self::RecipientQuotaExceeded => "Per-recipient quota exceeded",
+            Self::DailyQuotaExceeded => "Daily sending cap reached",
+            Self::ApiKeyQuotaExceeded => "API key quota exceeded",
+            Self::SubjectTooLong => "Subject too long",
+            Self::MessageTooLong => "Message too long",
+            Self::FieldTooLong => "Field too long",
+            Self::BodyTooLong => "Email text too long",
+            Self::InvalidEmail => "Email address invalid",
+            Self::SpamRejected => "Submission classified as spam",
+            Self::RecipientNotAllowed => "Recipient not allowed",
+            Self::NoRecipient => "No recipient configured",
+            Self::EmailSendFailed => "Email delivery failed",
+            Self::NotificationFailed => "Required notification channel failed",
+            Self::InboundUnauthorized => "Inbound webhook credentials invalid",
+            Self::InboundPayloadInvalid => "Inbound webhook payload not understood",
+            Self::IpBlocked => "Network blocked",
+            Self::IpBanned => "Client temporarily banned",
+            Self::NotFound => "Not found",
+            Self::Unauthorized => "Admin credentials required",
+            Self::FormTokenDisabled => "Form tokens disabled",
+            Self::PowDisabled => "Proof of work disabled",
+            Self::BanNotFound => "IP not banned",
+            Self::QuarantineItemNotFound => "Quarantined submission not found",
+        }
+    }
+}
+
+/// One invalid field of a `ValidationFailed` answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    /// JSON pointer (RFC 6901) to the field in the request body, `""` for the body itself.
+    #[schema(example = "/subject")]
+    pub pointer: String,
+    #[schema(example = "SubjectTooLong")]
+    pub code: ErrorCode,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ApiError {
-    pub error: String,
+    pub error: ErrorCode,
     pub message: String,
+    /// Every invalid field, only for `ValidationFailed`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+/// RFC 7807 problem document, sent instead of `ApiError` to clients that
+/// prefer `application/problem+json`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// `urn:problem:<code>`.
+    #[serde(rename = "type")]
+    #[schema(example = "urn:problem:SubjectTooLong")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Path of the request that failed.
+    pub instance: String,
+    pub code: ErrorCode,
+    /// Every invalid field, only for `ValidationFailed`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
 }
@@ -0,0 +1,18 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BanEntry {
+    #[schema(example = "203.0.113.7")]
+    pub ip: String,
+    /// Violation that triggered the ban: `rate_limited` or `spam`.
+    #[schema(example = "rate_limited")]
+    pub reason: String,
+    /// Escalation level, 1 for the first ban.
+    pub strike: u32,
+    /// RFC 3339 timestamp.
+    pub banned_at: String,
+    /// RFC 3339 timestamp.
+    pub until: String,
+    pub remaining_seconds: i64,
+}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FormTokenResponse {
+    pub token: String,
+    /// Seconds to wait before the token is accepted.
+    pub min_fill_seconds: u32,
+    /// Seconds the token stays valid.
+    pub expires_in: u32,
+}
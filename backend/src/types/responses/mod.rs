@@ -3,4 +3,19 @@ pub use health::HealthResponse;
 mod api_message;
 mod api_error;
 pub use api_message::ApiMessage;
-pub use api_error::ApiError;
+pub use api_error::{ApiError, ErrorCode, FieldError, ProblemDetails};
+mod form_token;
+pub use form_token::FormTokenResponse;
+mod pow_challenge;
+pub use pow_challenge::PowChallengeResponse;
+mod ban;
+pub use ban::BanEntry;
+mod submission;
+pub use submission::{SubmissionResponse, SubmissionStatus};
+mod quarantine;
+pub use quarantine::QuarantineEntry;
+mod admin;
+pub use admin::{
+    ConfigView, DeliveryFailureEntry, QuotaUsage, RateLimitState, SubmissionEntry, WebhookAttemptEntry,
+    WebhookDeliveryEntry,
+};
@@ -0,0 +1,13 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PowChallengeResponse {
+    pub challenge: String,
+    /// Required number of leading zero bits.
+    pub difficulty: u8,
+    #[schema(example = "sha256")]
+    pub algorithm: String,
+    /// Seconds the challenge stays valid.
+    pub expires_in: u32,
+}
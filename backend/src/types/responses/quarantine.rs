@@ -0,0 +1,26 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::types::LeadContext;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuarantineEntry {
+    pub id: String,
+    /// RFC 3339 timestamp.
+    pub received_at: String,
+    pub ip: String,
+    pub email: Option<String>,
+    pub subject: String,
+    pub message: String,
+    pub recipients: Vec<String>,
+    /// Why it was held back: `spam_score`, `blocked_domain`, `new_domain`,
+    /// `first_time_ip` or `shadow_banned`.
+    #[schema(example = json!(["spam_score"]))]
+    pub reasons: Vec<String>,
+    #[schema(example = "6.5 (links=2.0, caps=2.0)")]
+    pub spam_score: Option<String>,
+    /// Shadow-banned rule that fired, e.g. `captcha`.
+    pub shadow_rule: Option<String>,
+    /// Where the lead came from.
+    pub context: LeadContext,
+}
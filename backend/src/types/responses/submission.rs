@@ -0,0 +1,25 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    /// Taken over by the service; delivery or review follows.
+    Accepted,
+}
+
+/// Answer of `POST /api/v2/request`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmissionResponse {
+    /// Reference for support requests, also in webhook events and the admin API.
+    #[schema(example = "3f9a1c0e7b2d4a15")]
+    pub id: String,
+    pub status: SubmissionStatus,
+    #[schema(example = "2026-03-01T12:00:00+00:00")]
+    pub received_at: String,
+    /// `X-Request-Id` of this request.
+    pub request_id: Option<String>,
+    /// Confirmation to show the submitter, in their locale.
+    #[schema(example = "Thank you! Your message has been received.")]
+    pub message: String,
+}
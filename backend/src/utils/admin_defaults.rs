@@ -0,0 +1,7 @@
+pub fn admin_jwt_leeway_seconds_default() -> u32 {
+    60
+}
+
+pub fn admin_history_size_default() -> u32 {
+    200
+}
@@ -0,0 +1,3 @@
+pub fn signature_tolerance_seconds_default() -> u32 {
+    300
+}
@@ -0,0 +1,23 @@
+pub fn use_bans_default() -> bool {
+    false
+}
+
+pub fn ban_violations_default() -> u32 {
+    5
+}
+
+pub fn ban_violation_window_seconds_default() -> u32 {
+    3600
+}
+
+pub fn ban_durations_default() -> Vec<u64> {
+    vec![600, 3600, 86_400]
+}
+
+pub fn ban_strike_ttl_seconds_default() -> u32 {
+    7 * 86_400
+}
+
+pub fn ban_store_file_default() -> String {
+    "logs/bans.json".to_string()
+}
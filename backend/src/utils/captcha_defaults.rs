@@ -0,0 +1,3 @@
+pub fn captcha_min_score_default() -> f64 {
+    0.5
+}
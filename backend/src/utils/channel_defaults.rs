@@ -0,0 +1,3 @@
+pub fn telegram_base_url_default() -> String {
+    "https://api.telegram.org".to_string()
+}
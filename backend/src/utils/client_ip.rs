@@ -0,0 +1,295 @@
+use axum::http::HeaderMap;
+use ipnet::IpNet;
+use serde::Deserialize;
+use std::net::{IpAddr, SocketAddr};
+
+/// The one forwarding header the trusted proxy sets. The others are never
+/// read: a client could send them itself and the proxy would pass them on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProxyHeader {
+    /// RFC 7239 `Forwarded`, its `for=` parameters.
+    Forwarded,
+    /// nginx's `$proxy_add_x_forwarded_for`, the usual choice.
+    #[default]
+    XForwardedFor,
+    XRealIp,
+}
+
+/// One hop of a forwarding chain; `None` when the hop is `unknown`, an
+/// obfuscated identifier or otherwise unparseable.
+type Hop = Option<IpAddr>;
+
+fn is_trusted(ip: &IpAddr, trusted: &[IpNet]) -> bool {
+    trusted.iter().any(|net| net.contains(ip))
+}
+
+/// Accepts `203.0.113.7`, `203.0.113.7:4711`, `2001:db8::1` and
+/// `[2001:db8::1]:4711`, optionally wrapped in double quotes.
+fn parse_node(raw: &str) -> Hop {
+    let s = raw.trim().trim_matches('"');
+    let ip = s
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| s.parse::<SocketAddr>().ok().map(|sock| sock.ip()))
+        .or_else(|| {
+            s.strip_prefix('[')
+                .and_then(|r| r.strip_suffix(']'))
+                .and_then(|r| r.parse().ok())
+        })?;
+    Some(ip.to_canonical())
+}
+
+/// Reads the `for=` parameters of every RFC 7239 `Forwarded` element, in
+/// header order.
+fn forwarded_chain(headers: &HeaderMap) -> Option<Vec<Hop>> {
+    let values: Vec<&str> = headers
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    let chain = values
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                key.trim().eq_ignore_ascii_case("for").then(|| parse_node(value))
+            })?
+        })
+        .collect();
+    Some(chain)
+}
+
+fn x_forwarded_for_chain(headers: &HeaderMap) -> Option<Vec<Hop>> {
+    let values: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().flat_map(|v| v.split(',')).map(parse_node).collect())
+}
+
+fn x_real_ip_chain(headers: &HeaderMap) -> Option<Vec<Hop>> {
+    let value = headers.get("x-real-ip")?.to_str().ok()?;
+    Some(vec![parse_node(value)])
+}
+
+/// Resolves the client address for a connection from `peer`.
+///
+/// Forwarding headers are only read when `peer` is a trusted proxy, and
+/// only the `header` that proxy sets. Its chain is then walked right to
+/// left, skipping trusted hops; the first untrusted hop is the client. An
+/// unparseable hop stops the walk at the last verified address, so nothing
+/// left of it can be spoofed in.
+pub fn resolve_client_ip(peer: IpAddr, headers: &HeaderMap, trusted: &[IpNet], header: ProxyHeader) -> IpAddr {
+    let peer = peer.to_canonical();
+    if !is_trusted(&peer, trusted) {
+        return peer;
+    }
+    let chain = match header {
+        ProxyHeader::Forwarded => forwarded_chain(headers),
+        ProxyHeader::XForwardedFor => x_forwarded_for_chain(headers),
+        ProxyHeader::XRealIp => x_real_ip_chain(headers),
+    };
+    let Some(chain) = chain else {
+        return peer;
+    };
+
+    let mut client = peer;
+    for hop in chain.into_iter().rev() {
+        let Some(ip) = hop else { break };
+        client = ip;
+        if !is_trusted(&ip, trusted) {
+            break;
+        }
+    }
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const PROXY: &str = "10.0.0.1";
+    const CLIENT: &str = "203.0.113.7";
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn nets(list: &[&str]) -> Vec<IpNet> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    fn resolve_via(header: ProxyHeader, peer: &str, pairs: &[(&'static str, &str)], trusted: &[&str]) -> IpAddr {
+        resolve_client_ip(ip(peer), &headers(pairs), &nets(trusted), header)
+    }
+
+    fn resolve(peer: &str, pairs: &[(&'static str, &str)], trusted: &[&str]) -> IpAddr {
+        resolve_via(ProxyHeader::XForwardedFor, peer, pairs, trusted)
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_all_headers() {
+        let spoofed = [
+            ("x-forwarded-for", "1.2.3.4"),
+            ("x-real-ip", "1.2.3.4"),
+            ("forwarded", "for=1.2.3.4"),
+        ];
+        for header in [ProxyHeader::Forwarded, ProxyHeader::XForwardedFor, ProxyHeader::XRealIp] {
+            assert_eq!(resolve_via(header, CLIENT, &spoofed, &["10.0.0.0/8"]), ip(CLIENT));
+            assert_eq!(resolve_via(header, CLIENT, &spoofed, &[]), ip(CLIENT));
+        }
+    }
+
+    #[test]
+    fn trusted_peer_without_headers_is_the_client() {
+        assert_eq!(resolve(PROXY, &[], &["10.0.0.0/8"]), ip(PROXY));
+    }
+
+    #[test]
+    fn xff_single_hop() {
+        let h = [("x-forwarded-for", CLIENT)];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn xff_spoofed_left_entries_are_ignored() {
+        let h = [("x-forwarded-for", "1.2.3.4, 5.6.7.8, 203.0.113.7")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn xff_skips_chained_trusted_proxies() {
+        let h = [("x-forwarded-for", "1.2.3.4, 203.0.113.7, 10.0.0.3, 10.0.0.2")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn xff_all_trusted_yields_leftmost() {
+        let h = [("x-forwarded-for", "10.0.0.9, 10.0.0.2")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip("10.0.0.9"));
+    }
+
+    #[test]
+    fn xff_garbage_hop_stops_at_last_verified_address() {
+        let h = [("x-forwarded-for", "1.2.3.4, garbage")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(PROXY));
+
+        let h = [("x-forwarded-for", "1.2.3.4, not-an-ip, 10.0.0.2")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn xff_multiple_header_lines_are_concatenated() {
+        let h = [
+            ("x-forwarded-for", "1.2.3.4"),
+            ("x-forwarded-for", "203.0.113.7, 10.0.0.2"),
+        ];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn xff_accepts_ports_and_ipv6() {
+        let h = [("x-forwarded-for", "203.0.113.7:5555")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+
+        let h = [("x-forwarded-for", "[2001:db8::1]:443")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip("2001:db8::1"));
+
+        let h = [("x-forwarded-for", "2001:db8::1")];
+        assert_eq!(resolve("fd00::1", &h, &["fd00::/8"]), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn trusted_single_host_without_prefix_length() {
+        let trusted = vec![IpNet::from(ip(PROXY))];
+        let h = headers(&[("x-forwarded-for", CLIENT)]);
+        assert_eq!(resolve_client_ip(ip(PROXY), &h, &trusted, ProxyHeader::XForwardedFor), ip(CLIENT));
+        assert_eq!(resolve_client_ip(ip("10.0.0.2"), &h, &trusted, ProxyHeader::XForwardedFor), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn ipv4_mapped_peer_is_canonicalized() {
+        let h = [("x-forwarded-for", CLIENT)];
+        assert_eq!(resolve("::ffff:10.0.0.1", &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn x_real_ip_from_trusted_peer() {
+        let h = [("x-real-ip", CLIENT)];
+        assert_eq!(resolve_via(ProxyHeader::XRealIp, PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+
+        let h = [("x-real-ip", "nonsense")];
+        assert_eq!(resolve_via(ProxyHeader::XRealIp, PROXY, &h, &["10.0.0.0/8"]), ip(PROXY));
+    }
+
+    #[test]
+    fn forwarded_basic_element() {
+        let h = [("forwarded", "for=203.0.113.7;proto=https;by=10.0.0.1")];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn forwarded_walks_elements_right_to_left() {
+        let h = [("forwarded", "for=1.2.3.4, for=203.0.113.7, for=10.0.0.2")];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+
+        let h = [
+            ("forwarded", "for=1.2.3.4"),
+            ("forwarded", "For=203.0.113.7"),
+        ];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+
+    #[test]
+    fn forwarded_quoted_ipv6_with_port() {
+        let h = [("forwarded", r#"for="[2001:db8:cafe::17]:4711""#)];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip("2001:db8:cafe::17"));
+    }
+
+    #[test]
+    fn forwarded_unknown_and_obfuscated_nodes_stop_the_walk() {
+        let h = [("forwarded", "for=1.2.3.4, for=unknown")];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip(PROXY));
+
+        let h = [("forwarded", "for=1.2.3.4, for=_hidden, for=10.0.0.2")];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip("10.0.0.2"));
+
+        let h = [("forwarded", "for=1.2.3.4, proto=https")];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip(PROXY));
+    }
+
+    #[test]
+    fn client_sent_headers_the_proxy_does_not_set_are_ignored() {
+        // NOTE: nginx appends the peer to X-Forwarded-For and passes the client's own Forwarded on
+        let h = [
+            ("forwarded", "for=1.2.3.4"),
+            ("x-real-ip", "1.2.3.4"),
+            ("x-forwarded-for", CLIENT),
+        ];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+
+        let h = [("forwarded", "for=1.2.3.4")];
+        assert_eq!(resolve(PROXY, &h, &["10.0.0.0/8"]), ip(PROXY));
+
+        let h = [("x-forwarded-for", "1.2.3.4"), ("forwarded", "for=203.0.113.7")];
+        assert_eq!(resolve_via(ProxyHeader::Forwarded, PROXY, &h, &["10.0.0.0/8"]), ip(CLIENT));
+    }
+}
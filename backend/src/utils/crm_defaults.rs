@@ -0,0 +1,7 @@
+pub fn crm_max_attempts_default() -> u32 {
+    5
+}
+
+pub fn crm_retry_base_seconds_default() -> u32 {
+    5
+}
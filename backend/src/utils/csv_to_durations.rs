@@ -0,0 +1,32 @@
+use serde::{Deserializer, de::Error};
+
+use super::csv_to_vec;
+
+/// Parses `90`, `90s`, `10m`, `1h` or `7d` into seconds.
+pub fn parse_duration_secs(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = num.parse().ok()?;
+    let mul = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return None,
+    };
+    n.checked_mul(mul)
+}
+
+pub fn csv_to_durations<'de, D>(de: D) -> Result<Vec<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    csv_to_vec(de)?
+        .unwrap_or_default()
+        .iter()
+        .map(|s| parse_duration_secs(s).ok_or_else(|| D::Error::custom(format!("invalid duration: {s}"))))
+        .collect()
+}
@@ -0,0 +1,24 @@
+use ipnet::IpNet;
+use serde::{Deserializer, de::Error};
+use std::net::IpAddr;
+
+use super::csv_to_vec;
+
+/// Parses a CIDR range, treating a bare address as a single-host network.
+pub fn parse_ip_net(s: &str) -> Option<IpNet> {
+    let s = s.trim();
+    s.parse::<IpNet>()
+        .ok()
+        .or_else(|| s.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+pub fn csv_to_ip_nets<'de, D>(de: D) -> Result<Vec<IpNet>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    csv_to_vec(de)?
+        .unwrap_or_default()
+        .iter()
+        .map(|s| parse_ip_net(s).ok_or_else(|| D::Error::custom(format!("invalid IP or CIDR: {s}"))))
+        .collect()
+}
@@ -0,0 +1,7 @@
+pub fn form_token_min_fill_seconds_default() -> u32 {
+    3
+}
+
+pub fn form_token_max_age_seconds_default() -> u32 {
+    3600
+}
@@ -0,0 +1,7 @@
+pub fn idempotency_window_seconds_default() -> u32 {
+    86_400
+}
+
+pub fn duplicate_window_seconds_default() -> u32 {
+    60
+}
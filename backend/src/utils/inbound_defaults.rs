@@ -0,0 +1,7 @@
+pub fn inbound_secret_field_default() -> String {
+    "api_key".to_string()
+}
+
+pub fn inbound_subject_default() -> String {
+    "New submission: {form}".to_string()
+}
@@ -0,0 +1,3 @@
+pub fn ip_lists_reload_seconds_default() -> u32 {
+    30
+}
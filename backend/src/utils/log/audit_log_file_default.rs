@@ -0,0 +1,3 @@
+pub fn audit_log_file_default() -> String {
+    "logs/audit.jsonl".to_string()
+}
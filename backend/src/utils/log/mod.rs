@@ -2,3 +2,5 @@ mod default_log_level;
 pub use default_log_level::default_log_level;
 mod log_email_to_file;
 pub use log_email_to_file::log_email_to_file;
+mod audit_log_file_default;
+pub use audit_log_file_default::audit_log_file_default;
@@ -2,11 +2,34 @@ mod looks_like_email;
 pub use looks_like_email::looks_like_email;
 mod csv_to_vec;
 pub use csv_to_vec::csv_to_vec;
+mod csv_to_ip_nets;
+pub use csv_to_ip_nets::{csv_to_ip_nets, parse_ip_net};
+mod csv_to_durations;
+pub use csv_to_durations::csv_to_durations;
+mod null_as_default;
+pub use null_as_default::null_as_default;
+pub mod client_ip;
 pub mod mask_string;
 mod log;
-pub use log::{log_email_to_file, default_log_level};
+pub use log::{audit_log_file_default, log_email_to_file, default_log_level};
 mod allow_email_input_default;
 pub use allow_email_input_default::allow_email_input_default;
 pub mod rate_defaults;
+pub mod form_token_defaults;
+pub mod captcha_defaults;
+pub mod pow_defaults;
+pub mod ban_defaults;
+pub mod idempotency_defaults;
+pub mod quarantine_defaults;
+pub mod api_key_defaults;
+pub mod admin_defaults;
+pub mod webhook_defaults;
+pub mod channel_defaults;
+pub mod crm_defaults;
+pub mod inbound_defaults;
+pub mod crypto;
+pub mod template;
 mod duplicate_emails;
 pub use duplicate_emails::duplicate_emails_to_deafult_recipients_everytime_default;
+mod ip_lists_reload_default;
+pub use ip_lists_reload_default::ip_lists_reload_seconds_default;
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Deserializer};
+
+/// For `#[serde(default)]` fields that also accept an explicit `null`.
+pub fn null_as_default<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(de)?.unwrap_or_default())
+}
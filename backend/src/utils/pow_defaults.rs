@@ -0,0 +1,19 @@
+pub fn pow_difficulty_default() -> u8 {
+    16
+}
+
+pub fn pow_adaptive_difficulty_default() -> bool {
+    false
+}
+
+pub fn pow_difficulty_step_default() -> u8 {
+    2
+}
+
+pub fn pow_max_difficulty_default() -> u8 {
+    24
+}
+
+pub fn pow_max_age_seconds_default() -> u32 {
+    600
+}
@@ -0,0 +1,11 @@
+pub fn quarantine_store_file_default() -> String {
+    "logs/quarantine.json".to_string()
+}
+
+pub fn quarantine_max_items_default() -> u32 {
+    1000
+}
+
+pub fn quarantine_digest_seconds_default() -> u32 {
+    86_400
+}
@@ -9,3 +9,15 @@ pub fn rate_limit_timeframe_seconds_default() -> u32 {
 pub fn rate_limit_max_default() -> u32 {
     2
 }
+
+pub fn rate_limit_email_max_default() -> u32 {
+    2
+}
+
+pub fn rate_limit_recipient_max_default() -> u32 {
+    0
+}
+
+pub fn rate_limit_global_daily_max_default() -> u32 {
+    0
+}
@@ -0,0 +1,21 @@
+/// Fills `{name}` placeholders in one pass, so braces inside substituted
+/// values are never expanded. Names `resolve` does not know stay as written.
+pub fn fill(template: &str, mut resolve: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let Some(end) = tail.find('}') else {
+            rest = tail;
+            break;
+        };
+        match resolve(&tail[1..end]) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
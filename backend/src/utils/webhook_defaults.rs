@@ -0,0 +1,7 @@
+pub fn webhook_max_attempts_default() -> u32 {
+    5
+}
+
+pub fn webhook_retry_base_seconds_default() -> u32 {
+    2
+}
@@ -70,8 +70,12 @@ export function useContactForm() {
     if (axios.isAxiosError(err) && err.response) {
       const s = err.response.status
       if (s === 429) {
+        const retryAfter = Number(err.response.headers['retry-after'])
+        const wait = Number.isFinite(retryAfter) && retryAfter > 0
+          ? ` Повторная отправка будет доступна через ${Math.ceil(retryAfter / 60)} мин.`
+          : ''
         state.serverOk = false
-        state.serverMsg = 'Вы уже отправили максимум заявок. Пожалуйста, подождите. Если с вами не свяжутся, вы сможете отправить повторную заявку.'
+        state.serverMsg = 'Вы уже отправили максимум заявок. Пожалуйста, подождите. Если с вами не свяжутся, вы сможете отправить повторную заявку.' + wait
         return
       }
       if ([400, 401, 403, 404, 409, 422].includes(s)) {
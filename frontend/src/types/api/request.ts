@@ -1,5 +1,10 @@
 export interface RequestPayload {
     subject: string;
     message: string;
+    email?: string;
     recipients?: string[];
+    form_token?: string;
+    captcha_token?: string;
+    pow?: { challenge: string; solution: string };
+    [field: string]: unknown;
 }